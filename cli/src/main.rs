@@ -0,0 +1,309 @@
+//! Administration of voter-weight-addin-deposits registrars from the command
+//! line: creating and configuring registrars, granting locked deposits and
+//! inspecting registrar and voter accounts.
+//!
+//! Registrars are addressed by their realm. Transactions are signed and paid
+//! by `--keypair`, which must be the registrar authority for configuration
+//! commands.
+
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use voter_weight_addin_deposits::account::{LockupKind, Registrar, Voter};
+use voter_weight_addin_deposits::args::GrantArgs;
+use voter_weight_addin_deposits::client::instructions::*;
+
+#[derive(Parser)]
+#[clap(about = "Administer voter-weight-addin-deposits registrars")]
+struct Cli {
+    /// JSON RPC URL of the cluster.
+    #[clap(long, default_value = "http://localhost:8899")]
+    url: String,
+
+    /// Keypair file of the signer and fee payer.
+    #[clap(long, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Parser)]
+enum Command {
+    /// Creates the registrar of a realm.
+    CreateRegistrar {
+        #[clap(long)]
+        realm: Pubkey,
+        #[clap(long)]
+        governance_program: Pubkey,
+        #[clap(long)]
+        community_mint: Pubkey,
+        /// Registrar authority, defaults to the signer.
+        #[clap(long)]
+        authority: Option<Pubkey>,
+    },
+    /// Adds a voting mint or changes its rate.
+    ConfigureVotingMint {
+        #[clap(long)]
+        realm: Pubkey,
+        #[clap(long)]
+        idx: u8,
+        #[clap(long)]
+        mint: Pubkey,
+        #[clap(long)]
+        rate: u64,
+    },
+    /// Sets the lockup saturation and the maximum extra lockup weight.
+    ConfigureLockupParams {
+        #[clap(long)]
+        realm: Pubkey,
+        #[clap(long)]
+        lockup_saturation_secs: u64,
+        #[clap(long)]
+        max_extra_lockup_vote_weight_factor: u64,
+    },
+    /// Grants locked tokens from the signer's token account to a voter.
+    Grant {
+        #[clap(long)]
+        realm: Pubkey,
+        #[clap(long)]
+        voter_authority: Pubkey,
+        #[clap(long)]
+        mint: Pubkey,
+        /// Token account of the signer to fund the grant from.
+        #[clap(long)]
+        source: Pubkey,
+        #[clap(long)]
+        deposit_entry_index: u8,
+        /// none, cliff, daily or constant.
+        #[clap(long, parse(try_from_str = parse_lockup_kind))]
+        kind: LockupKind,
+        /// Lockup duration in days.
+        #[clap(long)]
+        periods: u32,
+        /// Native tokens to grant.
+        #[clap(long)]
+        amount: u64,
+        /// Start of the lockup, defaults to now.
+        #[clap(long)]
+        start_ts: Option<i64>,
+    },
+    /// Prints the registrar's configuration.
+    ShowRegistrar {
+        #[clap(long)]
+        realm: Pubkey,
+    },
+    /// Prints a voter's deposits and current weight.
+    ShowVoter {
+        #[clap(long)]
+        realm: Pubkey,
+        #[clap(long)]
+        authority: Pubkey,
+    },
+}
+
+fn parse_lockup_kind(s: &str) -> Result<LockupKind, String> {
+    match s {
+        "none" => Ok(LockupKind::None),
+        "cliff" => Ok(LockupKind::Cliff),
+        "daily" => Ok(LockupKind::Daily),
+        "constant" => Ok(LockupKind::Constant),
+        _ => Err(format!("unknown lockup kind: {}", s)),
+    }
+}
+
+fn read_keypair(path: &str) -> Result<Keypair> {
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", std::env::var("HOME")?, rest),
+        None => path.to_string(),
+    };
+    read_keypair_file(&path).map_err(|e| anyhow!("reading keypair {}: {}", path, e))
+}
+
+fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    let data = rpc
+        .get_account_data(address)
+        .with_context(|| format!("fetching account {}", address))?;
+    let mut data_slice: &[u8] = &data;
+    T::try_deserialize(&mut data_slice).with_context(|| format!("parsing account {}", address))
+}
+
+fn send(rpc: &RpcClient, signer: &Keypair, instructions: &[Instruction]) -> Result<()> {
+    let (blockhash, _) = rpc.get_recent_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signer.pubkey()),
+        &[signer],
+        blockhash,
+    );
+    let signature = rpc.send_and_confirm_transaction(&transaction)?;
+    println!("{}", signature);
+    Ok(())
+}
+
+fn show_registrar(address: &Pubkey, registrar: &Registrar) {
+    let authority = registrar.authority;
+    let realm = registrar.realm;
+    let lockup_saturation_secs = registrar.lockup_saturation_secs;
+    let max_extra_lockup_vote_weight_factor = registrar.max_extra_lockup_vote_weight_factor;
+    let withdrawal_cooldown_secs = registrar.withdrawal_cooldown_secs;
+    println!("registrar: {}", address);
+    println!("realm: {}", realm);
+    println!("authority: {}", authority);
+    println!("vault shards: {}", registrar.vault_shard_count);
+    println!("lockup saturation secs: {}", lockup_saturation_secs);
+    println!(
+        "max extra lockup vote weight factor: {}",
+        max_extra_lockup_vote_weight_factor
+    );
+    println!("withdrawal cooldown secs: {}", withdrawal_cooldown_secs);
+    for (idx, config) in registrar.voting_mints.iter().enumerate() {
+        if !config.in_use() {
+            continue;
+        }
+        let mint = config.mint;
+        let rate = config.rate;
+        let total_deposited_native = config.total_deposited_native;
+        println!(
+            "voting mint {}: {} rate {} digit shift {} deposited {}",
+            idx, mint, rate, config.digit_shift, total_deposited_native
+        );
+    }
+}
+
+fn show_voter(
+    rpc: &RpcClient,
+    address: &Pubkey,
+    voter: &Voter,
+    registrar: &Registrar,
+) -> Result<()> {
+    let curr_ts = rpc.get_block_time(rpc.get_slot()?)?;
+    let authority = voter.authority;
+    println!("voter: {}", address);
+    println!("authority: {}", authority);
+    println!("frozen: {}", voter.frozen);
+    for (idx, deposit) in voter.deposits.iter().enumerate() {
+        if !deposit.is_used {
+            continue;
+        }
+        let mint = registrar.voting_mints[deposit.voting_mint_config_idx as usize].mint;
+        let kind = deposit.lockup.kind;
+        let end_ts = deposit.lockup.end_ts;
+        let amount_deposited_native = deposit.amount_deposited_native;
+        println!(
+            "deposit {}: {} {} locked {} lockup {:?} until {} weight {}",
+            idx,
+            amount_deposited_native,
+            mint,
+            deposit.amount_locked(curr_ts),
+            kind,
+            end_ts,
+            deposit.voting_power(registrar, curr_ts)?,
+        );
+    }
+    println!("weight: {}", voter.weight(registrar, curr_ts)?);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+
+    match cli.command {
+        Command::CreateRegistrar {
+            realm,
+            governance_program,
+            community_mint,
+            authority,
+        } => {
+            let signer = read_keypair(&cli.keypair)?;
+            let authority = authority.unwrap_or_else(|| signer.pubkey());
+            let ix = create_registrar_ix(
+                &governance_program,
+                &realm,
+                &community_mint,
+                &authority,
+                &signer.pubkey(),
+            );
+            send(&rpc, &signer, &[ix])
+        }
+        Command::ConfigureVotingMint {
+            realm,
+            idx,
+            mint,
+            rate,
+        } => {
+            let signer = read_keypair(&cli.keypair)?;
+            let (registrar, _) = find_registrar_address(&realm);
+            let ix = configure_voting_mint_ix(&registrar, &signer.pubkey(), &mint, idx, rate);
+            send(&rpc, &signer, &[ix])
+        }
+        Command::ConfigureLockupParams {
+            realm,
+            lockup_saturation_secs,
+            max_extra_lockup_vote_weight_factor,
+        } => {
+            let signer = read_keypair(&cli.keypair)?;
+            let (registrar, _) = find_registrar_address(&realm);
+            let ix = configure_lockup_params_ix(
+                &registrar,
+                &signer.pubkey(),
+                lockup_saturation_secs,
+                max_extra_lockup_vote_weight_factor,
+            );
+            send(&rpc, &signer, &[ix])
+        }
+        Command::Grant {
+            realm,
+            voter_authority,
+            mint,
+            source,
+            deposit_entry_index,
+            kind,
+            periods,
+            amount,
+            start_ts,
+        } => {
+            let signer = read_keypair(&cli.keypair)?;
+            let (registrar, _) = find_registrar_address(&realm);
+            let (voter_address, _) = find_voter_address(&registrar, &voter_authority);
+            let voter: Voter = fetch(&rpc, &voter_address)?;
+            let ix = grant_ix(
+                &registrar,
+                &voter_authority,
+                voter.vault_shard,
+                &mint,
+                &source,
+                &signer.pubkey(),
+                &signer.pubkey(),
+                GrantArgs::V1 {
+                    deposit_entry_index,
+                    kind,
+                    start_ts,
+                    periods,
+                    amount,
+                },
+            );
+            send(&rpc, &signer, &[ix])
+        }
+        Command::ShowRegistrar { realm } => {
+            let (address, _) = find_registrar_address(&realm);
+            let registrar: Registrar = fetch(&rpc, &address)?;
+            show_registrar(&address, &registrar);
+            Ok(())
+        }
+        Command::ShowVoter { realm, authority } => {
+            let (registrar_address, _) = find_registrar_address(&realm);
+            let registrar: Registrar = fetch(&rpc, &registrar_address)?;
+            let (address, _) = find_voter_address(&registrar_address, &authority);
+            let voter: Voter = fetch(&rpc, &address)?;
+            show_voter(&rpc, &address, &voter, &registrar)
+        }
+    }
+}
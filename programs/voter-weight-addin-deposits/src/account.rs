@@ -1,11 +1,247 @@
 use crate::error::*;
+use crate::gateway::GatewayToken;
+use crate::governance::TokenOwnerRecordHeader;
+use crate::oracle::{OraclePrice, OraclePrices};
+use crate::seeds::{find_voter_checkpoints_address, find_voter_delegations_address};
 use anchor_lang::prelude::*;
-use anchor_spl::vote_weight_record;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::sysvar::instructions as tx_instructions;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{Mint, TokenAccount};
+use std::convert::TryFrom;
 
-// Generate a VoteWeightRecord Anchor wrapper, owned by the current program.
-// VoteWeightRecords are unique in that they are defined by the SPL governance
-// program, but they are actaully owned by this program.
-vote_weight_record!(crate::ID);
+/// Voting weight of a voter, read by spl-governance when the voter acts.
+///
+/// The layout and the (Anchor-style) discriminator match version 2 of
+/// spl-governance's addin `VoterWeightRecord`. VoterWeightRecords are unique
+/// in that they are defined by the SPL governance program, but they are
+/// actually owned by this program.
+#[account]
+pub struct VoterWeightRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub voter_weight: u64,
+    pub voter_weight_expiry: Option<u64>,
+    /// The governance action the weight is valid for. `None` means any.
+    pub weight_action: Option<VoterWeightAction>,
+    /// The proposal or governance the weight is valid for. `None` means any.
+    pub weight_action_target: Option<Pubkey>,
+    pub reserved: [u8; 8],
+}
+
+impl VoterWeightRecord {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 3 * 32 + 8 + (1 + 8) + (1 + 1) + (1 + 32) + 8;
+}
+
+/// The governance actions a `VoterWeightRecord` can be restricted to, in
+/// spl-governance's order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum VoterWeightAction {
+    CastVote,
+    CommentProposal,
+    CreateGovernance,
+    CreateProposal,
+    SignOffProposal,
+}
+
+/// Number of `VoterWeightAction` variants.
+pub const VOTER_WEIGHT_ACTION_COUNT: usize = 5;
+
+/// Which deposits count towards the weight for a governance action, see
+/// `Registrar::weight_action_rules`.
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum WeightRule {
+    /// Deposited tokens count, with extra weight for locked ones.
+    Full,
+    /// Only locked tokens count, with their extra weight. Unlocked tokens,
+    /// which can be withdrawn at any time, have no weight.
+    LockedOnly,
+}
+
+/// Maximum voter weight of a realm's community mint, used by spl-governance
+/// for quorum calculations instead of the mint supply.
+///
+/// The layout and the (Anchor-style) discriminator match spl-governance's
+/// `MaxVoterWeightRecord`. Like VoterWeightRecords, it is defined by SPL
+/// governance but owned by this program.
+#[account]
+pub struct MaxVoterWeightRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub max_voter_weight: u64,
+    pub max_voter_weight_expiry: Option<u64>,
+    pub reserved: [u8; 8],
+}
+
+impl MaxVoterWeightRecord {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 2 * 32 + 8 + (1 + 8) + 8;
+}
+
+/// Allows `wallet` to create a voter and deposit in a registrar with
+/// `Registrar::allowlist_required`.
+#[account]
+pub struct AllowlistEntry {
+    pub registrar: Pubkey,
+    pub wallet: Pubkey,
+}
+
+impl AllowlistEntry {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 2 * 32;
+}
+
+/// An airdrop of `mint` tokens whose claims are deposited into locked
+/// deposit entries, see `claim_from_merkle`. Its tokens are held in the
+/// token account at `seeds::find_distributor_vault_address`.
+#[account]
+pub struct MerkleDistributor {
+    pub registrar: Pubkey,
+    /// Root of the merkle tree of `merkle::leaf`s.
+    pub root: [u8; 32],
+    pub mint: Pubkey,
+    /// Lockup of the claimed tokens, starting at the time of the claim.
+    pub lockup_kind: LockupKind,
+    pub lockup_periods: u32,
+    /// Native tokens claimed so far.
+    pub total_claimed: u64,
+}
+
+impl MerkleDistributor {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1 + 4 + 8;
+}
+
+/// Marks the claim at an index of a `MerkleDistributor` as done.
+#[account]
+pub struct ClaimStatus {
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+impl ClaimStatus {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 32 + 8;
+}
+
+/// Prevents `wallet` from creating a voter and depositing in a registrar.
+#[account]
+pub struct BlocklistEntry {
+    pub registrar: Pubkey,
+    pub wallet: Pubkey,
+}
+
+impl BlocklistEntry {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 2 * 32;
+}
+
+/// Community tokens of `voter` escrowed for creating `proposal`, see
+/// `escrow_proposal_deposit`. Closed when they are released.
+#[account]
+pub struct ProposalDepositEscrow {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub proposal: Pubkey,
+    /// Gets the rent back on release.
+    pub payer: Pubkey,
+    pub amount: u64,
+    /// Slot of the escrow. The proposal can only be created in it.
+    pub slot: u64,
+}
+
+impl ProposalDepositEscrow {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 4 * 32 + 8 + 8;
+}
+
+/// Community tokens of `voter` locked for voting on `proposal`, see
+/// `lock_for_proposal`. Closed when they are released.
+#[account]
+pub struct ProposalLock {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub proposal: Pubkey,
+    /// Gets the rent back on release.
+    pub payer: Pubkey,
+    /// Native community tokens locked by `lock_for_proposal`.
+    pub amount: u64,
+    /// Bit `i` is set if deposit entry `i` is locked, by
+    /// `lock_entries_for_proposal`.
+    pub deposit_entries: u32,
+}
+
+impl ProposalLock {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 4 * 32 + 8 + 4;
+}
+
+/// Deposits of the registrar's voting mints during `epoch`, recorded by
+/// `snapshot_epoch`.
+#[account]
+pub struct EpochSnapshot {
+    pub registrar: Pubkey,
+    pub epoch: u64,
+    /// When the snapshot was taken.
+    pub ts: i64,
+    /// `VotingMintConfig::total_deposited_native` of every voting mint, in
+    /// the order of `Registrar::voting_mints`.
+    pub total_deposited_native: [u64; MAX_VOTING_MINTS],
+}
+
+impl EpochSnapshot {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 * MAX_VOTING_MINTS;
+}
+
+/// A deposit entry that is tokenized as an NFT of `position_mint`, so it
+/// can be transferred to another voter with `transfer_position`. The NFT is
+/// frozen in a token account of the voter authority.
+#[account]
+pub struct Position {
+    pub registrar: Pubkey,
+    pub position_mint: Pubkey,
+    /// The voter holding the deposit entry.
+    pub voter: Pubkey,
+    /// Index of the entry in `Voter::deposits`.
+    pub deposit_entry_index: u8,
+}
+
+impl Position {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 3 * 32 + 1;
+}
+
+/// Native tokens `voter` may withdraw from a registrar with
+/// `Registrar::withdrawals_require_approval`, see `approve_withdrawal`.
+#[account]
+pub struct WithdrawalApproval {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    /// Left to withdraw, of any voting mint.
+    pub amount: u64,
+}
+
+impl WithdrawalApproval {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 2 * 32 + 8;
+
+    /// Deducts `amount` from the approval at `account`, which must have been
+    /// created for the voter by `approve_withdrawal`.
+    pub fn consume(account: &AccountInfo, amount: u64) -> Result<()> {
+        require!(!account.data_is_empty(), WithdrawalNotApproved);
+        let mut approval = Account::<WithdrawalApproval>::try_from(account)?;
+        approval.amount = approval
+            .amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::WithdrawalNotApproved)?;
+        approval.exit(&crate::ID)?;
+        Ok(())
+    }
+}
 
 /// Instance of a voting rights distributor.
 #[account(zero_copy)]
@@ -14,9 +250,909 @@ pub struct Registrar {
     pub governance_program_id: Pubkey,
     pub realm: Pubkey,
     pub realm_community_mint: Pubkey,
+    /// Mints that may be deposited, and how they convert to voting weight.
+    /// Entry 0 is the realm's community mint.
+    pub voting_mints: [VotingMintConfig; MAX_VOTING_MINTS],
     pub bump: u8,
+    /// May grant locked deposits to voters, in addition to `authority`.
+    pub grant_authority: Pubkey,
+    /// Number of vault shards. Tokens are held in that many vaults, to
+    /// avoid all deposits and withdraws write-locking the same vault.
+    pub vault_shard_count: u8,
+    /// Locked tokens get extra voting weight proportional to their remaining
+    /// lockup time, up to this many seconds.
+    pub lockup_saturation_secs: u64,
+    /// Tokens locked for at least `lockup_saturation_secs` get this factor
+    /// of their amount as extra voting weight. In units of
+    /// `1 / LOCKUP_FACTOR_SCALE`.
+    pub max_extra_lockup_vote_weight_factor: u64,
+    /// Seconds between `request_withdraw` and `withdraw`. Zero means tokens
+    /// can be withdrawn without a request.
+    pub withdrawal_cooldown_secs: u64,
+    /// Share of the voting weight that counts for each `VoterWeightAction`,
+    /// in basis points.
+    pub weight_action_bps: [u16; VOTER_WEIGHT_ACTION_COUNT],
+    /// Caps on the voting weight for specific proposals or governances.
+    pub weight_target_caps: [WeightTargetCap; MAX_WEIGHT_TARGET_CAPS],
+    /// Whether a voter's weight is the square root of the linear weight.
+    pub quadratic_weight: bool,
+    /// Largest voting weight a single voter can have. Zero means no cap.
+    pub max_voter_weight_per_voter: u64,
+    /// Length of the trailing window voting weight is averaged over. Zero
+    /// means voters use their current weight.
+    pub twab_window_secs: u64,
+    /// Seconds added to the clock, see `set_time_offset`. Only used with the
+    /// `localnet-test` feature.
+    pub time_offset: i64,
+    /// Programs that may create voters and withdraw through CPI. Unused
+    /// entries are the default pubkey.
+    pub cpi_allowlist: [Pubkey; MAX_CPI_ALLOWLIST],
+    /// Whether voters are blocked from depositing. Withdrawals always work.
+    pub deposits_paused: bool,
+    /// Whether only wallets with an `AllowlistEntry` may create voters and
+    /// deposit.
+    pub allowlist_required: bool,
+    /// Number of `BlocklistEntry` accounts of the registrar.
+    pub blocklist_len: u32,
+    /// Program issuing the gateway tokens voters need, see `gateway`.
+    pub gatekeeper_program: Pubkey,
+    /// Gatekeeper network the gateway tokens must belong to. The default
+    /// pubkey means no gateway token is needed.
+    pub gatekeeper_network: Pubkey,
+    /// Metaplex collection whose verified NFTs boost their holder's weight.
+    /// The default pubkey means no boost.
+    pub nft_collection: Pubkey,
+    /// Extra weight for holders of an `nft_collection` NFT, in basis points
+    /// of their weight.
+    pub nft_boost_bps: u16,
+    /// Addin whose `VoterWeightRecord` is combined with the weight of this
+    /// one, for stacking addins. The default pubkey means none.
+    pub upstream_addin: Pubkey,
+    pub upstream_combination: WeightCombination,
+    /// Mint of the rewards paid to voters. The default pubkey means the
+    /// registrar has no rewards.
+    pub reward_mint: Pubkey,
+    /// Native reward tokens emitted per second, shared by all voters in
+    /// proportion to their reward shares.
+    pub reward_rate_per_sec: u64,
+    /// Sum of all voters' `Voter::reward_shares`.
+    pub reward_total_shares: u64,
+    /// Rewards accrued per reward share since the registrar was created,
+    /// scaled by `REWARD_PER_SHARE_SCALE`.
+    pub reward_per_share: u128,
+    pub reward_updated_ts: i64,
+    /// Owner of the token accounts that receive the penalties of
+    /// `unlock_early`. The default pubkey means locked tokens can't be
+    /// unlocked early.
+    pub early_unlock_treasury: Pubkey,
+    /// Share of the locked tokens forfeited by `unlock_early`, in basis
+    /// points.
+    pub early_unlock_penalty_bps: u16,
+    /// Whether deposits get vote escrow weight instead of the deposited
+    /// amount plus lockup bonus, see `DepositEntry::voting_power`.
+    pub vote_escrow_weight: bool,
+    /// Layout version, see `migrate_account`.
+    pub version: u8,
+    /// Smallest amount that can be withdrawn from a deposit entry, per
+    /// voting mint, unless the withdraw empties the entry. Zero means no
+    /// minimum.
+    pub min_withdraw_native: [u64; MAX_VOTING_MINTS],
+    /// All weights written to voter weight records and the max voter
+    /// weight record are multiplied by `weight_scale_numerator /
+    /// weight_scale_denominator`, to match the units of other weight
+    /// sources of the realm. With an upstream addin, the combined weight is
+    /// scaled. A zero denominator means no scaling.
+    pub weight_scale_numerator: u64,
+    pub weight_scale_denominator: u64,
+    /// The weight rule of each `VoterWeightAction`, for example to require
+    /// locked tokens for creating proposals but not for voting.
+    pub weight_action_rules: [WeightRule; VOTER_WEIGHT_ACTION_COUNT],
+    /// Native community tokens a voter must escrow to create a proposal,
+    /// see `escrow_proposal_deposit`. Zero means no deposit.
+    pub proposal_deposit_native: u64,
+    /// Whether voters can only withdraw what the registrar authority
+    /// approved, usually by executing a governance proposal. Deposits then
+    /// work like spl-governance membership tokens.
+    pub withdrawals_require_approval: bool,
+    /// Whether locked deposit entries can be tokenized with
+    /// `create_position` and moved between voters.
+    pub position_transfers_allowed: bool,
+    /// Seconds between `request_undelegate` and `undelegate`. Zero means
+    /// delegations can be ended without a request.
+    pub undelegate_cooldown_secs: u64,
+    /// Whether votes can only be cast with the weight of tokens locked for
+    /// the proposal, see `lock_for_proposal`.
+    pub proposal_locking: bool,
+    /// Token accounts whose balances don't count towards the supply used by
+    /// `update_max_vote_weight`, like treasury or team accounts. Unused
+    /// entries are the default pubkey.
+    pub excluded_supply_accounts: [Pubkey; MAX_EXCLUDED_SUPPLY_ACCOUNTS],
+    /// Incremented by every `configure_*` instruction, so copies of the
+    /// settings in voters can be detected as outdated, see
+    /// `Voter::weight_config_generation`.
+    pub config_generation: u64,
+    /// Space for future fields, zero until used.
+    pub reserved: [u8; 48],
+}
+
+/// Current `Registrar::version`.
+pub const REGISTRAR_VERSION: u8 = 1;
+
+// The zero-copy layouts are packed, so their sizes don't depend on the
+// compiler. These fail to compile if a layout change doesn't update `LEN`.
+const _: [(); Registrar::LEN] = [(); 8 + std::mem::size_of::<Registrar>()];
+const _: [(); Voter::LEN] = [(); 8 + std::mem::size_of::<Voter>()];
+const _: [(); VoterCheckpoints::LEN] = [(); 8 + std::mem::size_of::<VoterCheckpoints>()];
+
+/// How the weight of an upstream addin is combined with the deposit weight.
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum WeightCombination {
+    Sum,
+    Max,
 }
 
+impl Registrar {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 1630;
+
+    /// The current unix timestamp, shifted by `time_offset` in builds with
+    /// the `localnet-test` feature.
+    pub fn clock_unix_timestamp(&self) -> Result<i64> {
+        let ts = Clock::get()?.unix_timestamp;
+        #[cfg(feature = "localnet-test")]
+        let ts = ts + self.time_offset;
+        Ok(ts)
+    }
+
+    /// Upgrades the registrar to `REGISTRAR_VERSION`, one version at a time.
+    pub fn migrate(&mut self) {
+        while self.version < REGISTRAR_VERSION {
+            match self.version {
+                // Registrars from before versioning have the same layout as
+                // version 1.
+                0 => {}
+                _ => unreachable!(),
+            }
+            self.version += 1;
+        }
+    }
+
+    /// Marks the settings as changed, see `config_generation`. Called by
+    /// every `configure_*` instruction.
+    pub fn bump_config_generation(&mut self) {
+        self.config_generation = self.config_generation.wrapping_add(1);
+    }
+
+    /// The settings voting weights are computed from.
+    pub fn weight_config(&self) -> WeightConfig {
+        let mut mint_rates = [0; MAX_VOTING_MINTS];
+        let mut mint_digit_shifts = [0; MAX_VOTING_MINTS];
+        for (idx, config) in self.voting_mints.iter().enumerate() {
+            mint_rates[idx] = config.rate;
+            mint_digit_shifts[idx] = config.digit_shift;
+        }
+        WeightConfig {
+            mint_rates,
+            mint_digit_shifts,
+            lockup_saturation_secs: self.lockup_saturation_secs,
+            max_extra_lockup_vote_weight_factor: self.max_extra_lockup_vote_weight_factor,
+            vote_escrow_weight: self.vote_escrow_weight,
+            twab_window_secs: self.twab_window_secs,
+            quadratic_weight: self.quadratic_weight,
+            max_voter_weight_per_voter: self.max_voter_weight_per_voter,
+            weight_action_bps: self.weight_action_bps,
+        }
+    }
+
+    /// Whether `weight_config` is all voting weights depend on: no voting
+    /// mint has an oracle, and there is no NFT boost, upstream addin,
+    /// weight target cap, weight scale, locked-only weight rule or proposal
+    /// deposit.
+    pub fn weight_config_complete(&self) -> bool {
+        self.voting_mints
+            .iter()
+            .all(|c| c.oracle == Pubkey::default())
+            && self.nft_collection == Pubkey::default()
+            && self.upstream_addin == Pubkey::default()
+            && !self.weight_target_caps.iter().any(|c| c.in_use())
+            && self.weight_scale_denominator == 0
+            && self
+                .weight_action_rules
+                .iter()
+                .all(|r| *r == WeightRule::Full)
+            && self.proposal_deposit_native == 0
+            && !self.proposal_locking
+    }
+
+    /// Whether a weight for `action` may only be written by
+    /// `escrow_proposal_deposit`. A weight that is valid for any action can
+    /// be used to create proposals too.
+    pub fn proposal_deposit_required(&self, action: Option<VoterWeightAction>) -> bool {
+        self.proposal_deposit_native > 0
+            && matches!(action, None | Some(VoterWeightAction::CreateProposal))
+    }
+
+    /// Whether a weight for `action` may only be written by
+    /// `lock_for_proposal`. A weight that is valid for any action can be
+    /// used to vote too.
+    pub fn proposal_lock_required(&self, action: Option<VoterWeightAction>) -> bool {
+        self.proposal_locking && matches!(action, None | Some(VoterWeightAction::CastVote))
+    }
+
+    /// Address spl-governance creates the proposal with index
+    /// `proposal_index` of `governance` at.
+    pub fn proposal_address(&self, governance: &Pubkey, proposal_index: u32) -> Pubkey {
+        spl_governance::state::proposal::get_proposal_address(
+            &self.governance_program_id,
+            governance,
+            &self.realm_community_mint,
+            &proposal_index.to_le_bytes(),
+        )
+    }
+
+    /// The weight rule for `action`; a weight that is valid for any action
+    /// gets the strictest rule of all actions.
+    pub fn weight_rule(&self, action: Option<VoterWeightAction>) -> WeightRule {
+        match action {
+            Some(action) => self.weight_action_rules[action as usize],
+            None if self.weight_action_rules.contains(&WeightRule::LockedOnly) => {
+                WeightRule::LockedOnly
+            }
+            None => WeightRule::Full,
+        }
+    }
+
+    /// `weight` multiplied by the registrar's weight scale, saturating.
+    pub fn scale_weight(&self, weight: u128) -> u64 {
+        let scaled = if self.weight_scale_denominator == 0 {
+            weight
+        } else {
+            weight.saturating_mul(self.weight_scale_numerator as u128)
+                / self.weight_scale_denominator as u128
+        };
+        scaled.min(u64::MAX as u128) as u64
+    }
+
+    /// Checks the minimum withdraw of the voting mint at `idx` for
+    /// withdrawing `amount` of the `deposited` tokens of a deposit entry.
+    pub fn check_withdraw_amount(&self, idx: usize, amount: u64, deposited: u64) -> Result<()> {
+        require!(
+            amount >= self.min_withdraw_native[idx] || amount == deposited,
+            WithdrawBelowMinimum
+        );
+        Ok(())
+    }
+
+    /// Whether `program_id` may create voters and withdraw through CPI.
+    pub fn is_cpi_allowed(&self, program_id: &Pubkey) -> bool {
+        *program_id != Pubkey::default() && self.cpi_allowlist.contains(program_id)
+    }
+
+    /// Checks with the `instructions` sysvar that the current instruction
+    /// was called directly, or through CPI from an allowed program.
+    pub fn check_not_cpi(&self, program_id: &Pubkey, instructions: &AccountInfo) -> Result<()> {
+        let current_index = tx_instructions::load_current_index_checked(instructions)? as usize;
+        let current_ixn =
+            tx_instructions::load_instruction_at_checked(current_index, instructions)?;
+        require!(
+            current_ixn.program_id == *program_id || self.is_cpi_allowed(&current_ixn.program_id),
+            ErrorCode::ForbiddenCpi
+        );
+        Ok(())
+    }
+
+    /// Whether `key` may grant locked deposits.
+    pub fn is_grant_authority(&self, key: &Pubkey) -> bool {
+        *key == self.authority || (*key == self.grant_authority && *key != Pubkey::default())
+    }
+
+    /// Checks that `wallet` may create a voter and deposit in the registrar
+    /// at `registrar_key`. The wallet's `AllowlistEntry` and the address of
+    /// its `BlocklistEntry` must be among `accounts` when the registrar uses
+    /// them.
+    pub fn check_depositor<'info>(
+        &self,
+        registrar_key: &Pubkey,
+        wallet: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        self.check_allowlisted(registrar_key, wallet, accounts)?;
+        self.check_not_blocklisted(registrar_key, wallet, accounts)
+    }
+
+    fn check_allowlisted<'info>(
+        &self,
+        registrar_key: &Pubkey,
+        wallet: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if !self.allowlist_required {
+            return Ok(());
+        }
+        let listed = accounts.iter().any(|info| {
+            Account::<AllowlistEntry>::try_from(info).map_or(false, |e| {
+                e.registrar == *registrar_key && e.wallet == *wallet
+            })
+        });
+        require!(listed, NotAllowlisted);
+        Ok(())
+    }
+
+    /// Absence can't be shown by leaving an account out, so while the
+    /// blocklist isn't empty the wallet's entry address must be passed even
+    /// if no account exists there.
+    fn check_not_blocklisted<'info>(
+        &self,
+        registrar_key: &Pubkey,
+        wallet: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if self.blocklist_len == 0 {
+            return Ok(());
+        }
+        let (address, _) = crate::seeds::find_blocklist_entry_address(registrar_key, wallet);
+        let info = accounts
+            .iter()
+            .find(|info| info.key() == address)
+            .ok_or(ErrorCode::MissingBlocklistAccount)?;
+        require!(
+            info.owner != &crate::ID || info.data_is_empty(),
+            Blocklisted
+        );
+        Ok(())
+    }
+
+    /// Creates the spl-governance token owner record of `wallet` for the
+    /// community mint if its address is among `accounts` and no account
+    /// exists there yet. The realm, the community mint and the governance
+    /// program must then be in `accounts` too.
+    ///
+    /// Clients that don't pass the address keep creating the record
+    /// themselves.
+    pub fn create_token_owner_record_if_missing<'info>(
+        &self,
+        wallet: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        use spl_governance::state::token_owner_record::get_token_owner_record_address;
+        let address = get_token_owner_record_address(
+            &self.governance_program_id,
+            &self.realm,
+            &self.realm_community_mint,
+            wallet.key,
+        );
+        let record = match accounts.iter().find(|info| info.key() == address) {
+            Some(record) if record.data_is_empty() => record,
+            _ => return Ok(()),
+        };
+        let find = |key: &Pubkey| {
+            accounts
+                .iter()
+                .find(|info| info.key == key)
+                .cloned()
+                .ok_or(ErrorCode::MissingTokenOwnerRecordAccounts)
+        };
+        let ix = spl_governance::instruction::create_token_owner_record(
+            &self.governance_program_id,
+            &self.realm,
+            wallet.key,
+            &self.realm_community_mint,
+            payer.key,
+        );
+        invoke(
+            &ix,
+            &[
+                find(&self.realm)?,
+                wallet.clone(),
+                record.clone(),
+                find(&self.realm_community_mint)?,
+                payer.clone(),
+                system_program.clone(),
+                find(&self.governance_program_id)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Checks that `wallet` has a valid gateway token of the registrar's
+    /// gatekeeper network among `accounts`, if the registrar has one.
+    pub fn check_gateway_token<'info>(
+        &self,
+        wallet: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+        curr_ts: i64,
+    ) -> Result<()> {
+        if self.gatekeeper_network == Pubkey::default() {
+            return Ok(());
+        }
+        let valid = accounts
+            .iter()
+            .filter(|info| info.owner == &self.gatekeeper_program)
+            .any(|info| {
+                GatewayToken::try_from_account_info(info, &self.gatekeeper_program)
+                    .map_or(false, |token| {
+                        token.is_valid_for(wallet, &self.gatekeeper_network, curr_ts)
+                    })
+            });
+        require!(valid, MissingGatewayToken);
+        Ok(())
+    }
+
+    /// Index of the voting mint config for `mint`.
+    pub fn voting_mint_config_index(&self, mint: Pubkey) -> Result<usize> {
+        self.voting_mints
+            .iter()
+            .position(|c| c.in_use() && c.mint == mint)
+            .ok_or(ErrorCode::ExchangeRateEntryNotFound.into())
+    }
+
+    /// The largest voting weight any amount of tokens can have: the
+    /// circulating supply of every voting mint, locked for the saturation
+    /// period. The circulating supply is the mint's supply without the
+    /// balances of the excluded supply accounts.
+    ///
+    /// With `quadratic_weight` the sum of all voters' weights is smaller, but
+    /// depends on how the tokens are spread over voters; this stays the
+    /// linear bound. With an NFT boost, it assumes every voter is boosted.
+    ///
+    /// `mints` are the mint accounts of all voting mints in use, in order,
+    /// and `excluded` the token accounts of all excluded supply accounts in
+    /// use, in order.
+    pub fn max_vote_weight(&self, mints: &[Mint], excluded: &[TokenAccount]) -> Result<u64> {
+        let configs = self.voting_mints.iter().filter(|c| c.in_use());
+        require!(
+            configs.clone().count() == mints.len(),
+            InvalidVotingMintAccounts
+        );
+        require!(
+            self.excluded_supply_accounts
+                .iter()
+                .filter(|a| **a != Pubkey::default())
+                .count()
+                == excluded.len(),
+            InvalidExcludedSupplyAccounts
+        );
+        for account in excluded {
+            require!(
+                configs.clone().any(|c| c.mint == account.mint),
+                InvalidExcludedSupplyAccounts
+            );
+        }
+        let max_factor = if self.vote_escrow_weight {
+            LOCKUP_FACTOR_SCALE as u128
+        } else {
+            LOCKUP_FACTOR_SCALE as u128 + self.max_extra_lockup_vote_weight_factor as u128
+        };
+        let weight = configs
+            .zip(mints)
+            .try_fold(0u128, |sum, (config, mint)| -> Result<u128> {
+                let supply = excluded
+                    .iter()
+                    .filter(|a| a.mint == config.mint)
+                    .try_fold(mint.supply, |supply, a| supply.checked_sub(a.amount))
+                    .ok_or(ErrorCode::Overflow)?;
+                let mint_weight = config
+                    .weight(supply as u128)?
+                    .checked_mul(max_factor)
+                    .ok_or(ErrorCode::Overflow)?
+                    / LOCKUP_FACTOR_SCALE as u128;
+                Ok(sum.checked_add(mint_weight).ok_or(ErrorCode::Overflow)?)
+            })?;
+        let weight = if self.nft_collection != Pubkey::default() {
+            weight
+                .checked_mul(BPS_SCALE as u128 + self.nft_boost_bps as u128)
+                .ok_or(ErrorCode::Overflow)?
+                / BPS_SCALE as u128
+        } else {
+            weight
+        };
+        Ok(self.scale_weight(weight))
+    }
+
+    /// `weight` with the NFT boost, if `accounts` show that `wallet` holds an
+    /// NFT of the registrar's collection. Still limited to
+    /// `max_voter_weight_per_voter`.
+    pub fn nft_boosted_weight<'info>(
+        &self,
+        weight: u64,
+        wallet: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<u64> {
+        if self.nft_collection == Pubkey::default()
+            || !crate::nft::holds_collection_nft(wallet, &self.nft_collection, accounts)
+        {
+            return Ok(weight);
+        }
+        let extra = weight as u128 * self.nft_boost_bps as u128 / BPS_SCALE as u128;
+        let mut weight = u64::try_from(weight as u128 + extra).map_err(|_| ErrorCode::Overflow)?;
+        if self.max_voter_weight_per_voter > 0 {
+            weight = weight.min(self.max_voter_weight_per_voter);
+        }
+        Ok(weight)
+    }
+
+    /// The vault shard used by voters of `authority`.
+    ///
+    /// Deterministic, so voters are spread evenly over the shards that
+    /// existed when they were created.
+    pub fn vault_shard_for(&self, authority: &Pubkey) -> u8 {
+        authority.to_bytes()[0] % self.vault_shard_count
+    }
+
+    /// Combines `weight` with the voter weight from the upstream addin, if the
+    /// registrar has one. The upstream `VoterWeightRecord` of `wallet` must
+    /// be among `accounts`, updated in this slot and valid for `action` and
+    /// `target`.
+    pub fn combine_upstream_weight<'info>(
+        &self,
+        weight: u64,
+        wallet: &Pubkey,
+        action: Option<VoterWeightAction>,
+        target: Option<Pubkey>,
+        accounts: &[AccountInfo<'info>],
+        slot: u64,
+    ) -> Result<u64> {
+        if self.upstream_addin == Pubkey::default() {
+            return Ok(weight);
+        }
+        let record = accounts
+            .iter()
+            .filter(|info| info.owner == &self.upstream_addin)
+            .find_map(|info| {
+                let data = info.try_borrow_data().ok()?;
+                let mut data_slice: &[u8] = &data;
+                VoterWeightRecord::try_deserialize(&mut data_slice)
+                    .ok()
+                    .filter(|r| r.governing_token_owner == *wallet)
+            })
+            .ok_or(ErrorCode::MissingUpstreamVoterWeightRecord)?;
+        require!(
+            record.realm == self.realm
+                && record.governing_token_mint == self.realm_community_mint
+                && record
+                    .voter_weight_expiry
+                    .map_or(true, |expiry| expiry >= slot)
+                && (record.weight_action.is_none() || record.weight_action == action)
+                && (record.weight_action_target.is_none() || record.weight_action_target == target),
+            InvalidUpstreamVoterWeightRecord
+        );
+        Ok(match self.upstream_combination {
+            WeightCombination::Sum => weight
+                .checked_add(record.voter_weight)
+                .ok_or(ErrorCode::Overflow)?,
+            WeightCombination::Max => weight.max(record.voter_weight),
+        })
+    }
+
+    /// Reads the prices of all voting mints with an oracle. Their oracle
+    /// accounts must be among `accounts`.
+    pub fn oracle_prices<'info>(
+        &self,
+        accounts: &[AccountInfo<'info>],
+        slot: u64,
+    ) -> Result<OraclePrices> {
+        let mut prices = [None; MAX_VOTING_MINTS];
+        for (price, config) in prices.iter_mut().zip(self.voting_mints.iter()) {
+            if !config.in_use() || config.oracle == Pubkey::default() {
+                continue;
+            }
+            let info = accounts
+                .iter()
+                .find(|info| info.key() == config.oracle)
+                .ok_or(ErrorCode::MissingOracleAccount)?;
+            *price = Some(OraclePrice::from_pyth(info, slot)?);
+        }
+        Ok(prices)
+    }
+
+    /// Accrues the rewards emitted since `reward_updated_ts` to all reward
+    /// shares.
+    pub fn update_rewards(&mut self, curr_ts: i64) -> Result<()> {
+        let elapsed = curr_ts.saturating_sub(self.reward_updated_ts).max(0) as u128;
+        if self.reward_total_shares > 0 && self.reward_updated_ts > 0 {
+            let accrued = (self.reward_rate_per_sec as u128 * elapsed)
+                .checked_mul(REWARD_PER_SHARE_SCALE)
+                .ok_or(ErrorCode::Overflow)?
+                / self.reward_total_shares as u128;
+            self.reward_per_share = self
+                .reward_per_share
+                .checked_add(accrued)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        self.reward_updated_ts = curr_ts;
+        Ok(())
+    }
+
+    /// Applies the registrar's rules for `action` and `target` to `weight`.
+    ///
+    /// The weight is scaled by the share for `action`; a weight that is valid
+    /// for any action gets the smallest share of all actions. Likewise it is
+    /// limited by the cap for `target`, or by the smallest cap if valid for
+    /// any target.
+    ///
+    /// Zero if creating proposals requires a deposit and the weight could
+    /// be used for that, see `proposal_deposit_required`.
+    pub fn weight_for_action(
+        &self,
+        weight: u64,
+        action: Option<VoterWeightAction>,
+        target: Option<Pubkey>,
+    ) -> u64 {
+        if self.proposal_deposit_required(action) || self.proposal_lock_required(action) {
+            return 0;
+        }
+        self.weight_for_escrowed_action(weight, action, target)
+    }
+
+    /// Like `weight_for_action`, for a voter that escrowed the proposal
+    /// deposit or locked tokens for the proposal.
+    pub fn weight_for_escrowed_action(
+        &self,
+        weight: u64,
+        action: Option<VoterWeightAction>,
+        target: Option<Pubkey>,
+    ) -> u64 {
+        let scaled = self.weight_config().weight_for_action(weight, action);
+        let scaled = self.scale_weight(scaled as u128);
+
+        let caps = self.weight_target_caps.iter().filter(|c| c.in_use());
+        let cap = match target {
+            Some(target) => caps
+                .filter(|c| c.target == target)
+                .map(|c| c.max_weight)
+                .min(),
+            None => caps.map(|c| c.max_weight).min(),
+        };
+        cap.map_or(scaled, |cap| scaled.min(cap))
+    }
+}
+
+/// Number of programs on a registrar's CPI allowlist.
+pub const MAX_CPI_ALLOWLIST: usize = 4;
+
+/// Number of token accounts a registrar can exclude from the circulating
+/// supply.
+pub const MAX_EXCLUDED_SUPPLY_ACCOUNTS: usize = 4;
+
+/// Number of mints a registrar can accept deposits for.
+pub const MAX_VOTING_MINTS: usize = 4;
+
+/// Number of weight caps for specific targets a registrar can have.
+pub const MAX_WEIGHT_TARGET_CAPS: usize = 8;
+
+/// Limits the voting weight for a proposal or governance.
+#[zero_copy]
+pub struct WeightTargetCap {
+    pub target: Pubkey,
+    pub max_weight: u64,
+}
+
+impl WeightTargetCap {
+    pub fn in_use(&self) -> bool {
+        self.target != Pubkey::default()
+    }
+}
+
+/// A mint that can be deposited, and its conversion to voting weight.
+#[zero_copy]
+pub struct VotingMintConfig {
+    pub mint: Pubkey,
+    /// Voting weight per native token of the mint.
+    pub rate: u64,
+    /// Smallest amount of native tokens a deposit may add.
+    pub min_deposit_native: u64,
+    /// Smallest balance, in native tokens, a withdraw may leave in a deposit
+    /// entry, unless it empties the entry.
+    pub min_remaining_native: u64,
+    /// Most native tokens of the mint a single voter may have deposited.
+    /// Zero means no cap.
+    pub max_voter_deposit_native: u64,
+    /// Native tokens of the mint deposited by all voters.
+    pub total_deposited_native: u64,
+    /// Most native tokens of the mint all voters together may have
+    /// deposited. Zero means no cap.
+    pub max_total_deposit_native: u64,
+    /// Power of ten the weight is multiplied with after applying `rate`.
+    /// Negative values divide, so mints with different decimals can be
+    /// brought to the same unit.
+    pub digit_shift: i8,
+    /// Pyth price account that scales the weight of the mint, for LP or
+    /// derivative tokens. The default pubkey means none.
+    pub oracle: Pubkey,
+}
+
+/// Largest absolute `VotingMintConfig::digit_shift`.
+pub const MAX_DIGIT_SHIFT: i8 = 18;
+
+impl VotingMintConfig {
+    pub fn in_use(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+
+    /// Voting weight of `amount` native tokens, applying `rate` and
+    /// `digit_shift`.
+    pub fn weight(&self, amount: u128) -> Result<u128> {
+        weight_at_rate(amount, self.rate, self.digit_shift)
+    }
+
+    /// Checks the configured minimums for a deposit of `amount`.
+    pub fn check_deposit(&self, amount: u64) -> Result<()> {
+        require!(amount >= self.min_deposit_native, DepositBelowMinimum);
+        Ok(())
+    }
+
+    /// Counts `amount` deposited native tokens towards the total, which must
+    /// stay within the cap.
+    pub fn track_deposit(&mut self, amount: u64) -> Result<()> {
+        self.total_deposited_native = self
+            .total_deposited_native
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            self.max_total_deposit_native == 0
+                || self.total_deposited_native <= self.max_total_deposit_native,
+            TotalDepositCapExceeded
+        );
+        Ok(())
+    }
+
+    /// Removes `amount` withdrawn native tokens from the total. Saturates,
+    /// since tokens deposited before the total was tracked aren't in it.
+    pub fn track_withdraw(&mut self, amount: u64) {
+        self.total_deposited_native = self.total_deposited_native.saturating_sub(amount);
+    }
+
+    /// Checks the configured minimums for the `remaining` balance of a
+    /// deposit entry after a withdraw.
+    pub fn check_remaining(&self, remaining: u64) -> Result<()> {
+        require!(
+            remaining == 0 || remaining >= self.min_remaining_native,
+            RemainingBalanceBelowMinimum
+        );
+        Ok(())
+    }
+}
+
+/// Voting weight of `amount` native tokens of a mint with `rate` and
+/// `digit_shift`, see `VotingMintConfig`.
+fn weight_at_rate(amount: u128, rate: u64, digit_shift: i8) -> Result<u128> {
+    let weight = amount
+        .checked_mul(rate as u128)
+        .ok_or(ErrorCode::Overflow)?;
+    let shift = 10u128.pow(digit_shift.unsigned_abs() as u32);
+    Ok(if digit_shift >= 0 {
+        weight.checked_mul(shift).ok_or(ErrorCode::Overflow)?
+    } else {
+        weight / shift
+    })
+}
+
+/// The registrar settings voting weights are computed from.
+///
+/// Voters keep a copy in `Voter::weight_config`, so that
+/// `update_voter_weight_record_cached` doesn't need the registrar.
+#[zero_copy]
+pub struct WeightConfig {
+    /// `VotingMintConfig::rate` of each voting mint.
+    pub mint_rates: [u64; MAX_VOTING_MINTS],
+    /// `VotingMintConfig::digit_shift` of each voting mint.
+    pub mint_digit_shifts: [i8; MAX_VOTING_MINTS],
+    pub lockup_saturation_secs: u64,
+    pub max_extra_lockup_vote_weight_factor: u64,
+    pub vote_escrow_weight: bool,
+    pub twab_window_secs: u64,
+    pub quadratic_weight: bool,
+    pub max_voter_weight_per_voter: u64,
+    pub weight_action_bps: [u16; VOTER_WEIGHT_ACTION_COUNT],
+}
+
+impl WeightConfig {
+    /// Voting weight of `amount` native tokens of the voting mint at `idx`.
+    fn mint_weight(&self, idx: u8, amount: u128) -> Result<u128> {
+        weight_at_rate(
+            amount,
+            self.mint_rates[idx as usize],
+            self.mint_digit_shifts[idx as usize],
+        )
+    }
+
+    /// Scales `weight` by the share for `action`; a weight that is valid for
+    /// any action gets the smallest share of all actions.
+    pub fn weight_for_action(&self, weight: u64, action: Option<VoterWeightAction>) -> u64 {
+        let action_bps = self.weight_action_bps;
+        let bps = match action {
+            Some(action) => action_bps[action as usize],
+            None => *action_bps.iter().min().unwrap(),
+        };
+        let scaled = weight as u128 * bps as u128 / BPS_SCALE as u128;
+        scaled.min(u64::MAX as u128) as u64
+    }
+}
+
+/// Largest integer whose square is at most `n`.
+pub fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method from an initial guess above the root; the iterates
+    // decrease monotonically until they reach it.
+    let n = n as u128;
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x as u64
+}
+
+/// Voting power of `deposit` at `curr_ts`, at the price of its mint in
+/// `prices`.
+fn deposit_power(
+    deposit: &DepositEntry,
+    config: &WeightConfig,
+    rule: WeightRule,
+    curr_ts: i64,
+    prices: &OraclePrices,
+) -> Result<u64> {
+    let power = deposit.voting_power_with_rule(config, rule, curr_ts)?;
+    Ok(match prices[deposit.voting_mint_config_idx as usize] {
+        Some(price) => {
+            u64::try_from(price.apply(power as u128)?).map_err(|_| ErrorCode::Overflow)?
+        }
+        None => power,
+    })
+}
+
+/// The weight `update_voter_weight_record` writes for `voter` at `now`
+/// without a weight action or target, computed without any syscalls.
+///
+/// Clients can pass future timestamps to show how the weight decays with
+/// the lockups. Deposits of mints with an oracle count without their price,
+/// and the NFT boost and upstream addin weights are not included since they
+/// depend on other accounts. Gives zero where the program would fail with
+/// an overflow.
+pub fn compute_voter_weight(voter: &Voter, registrar: &Registrar, now: i64) -> u64 {
+    let weight = voter.weight(registrar, now).unwrap_or(0);
+    registrar.weight_for_action(weight, None, None)
+}
+
+mod native_mint {
+    anchor_lang::declare_id!("So11111111111111111111111111111111111111112");
+}
+
+/// The wrapped SOL mint.
+pub use native_mint::ID as NATIVE_MINT;
+
+/// Seconds in one day.
+pub const SECS_PER_DAY: i64 = 86_400;
+
+/// Number of deposit entries per voter.
+pub const MAX_DEPOSIT_ENTRIES: usize = 32;
+
+/// Denominator of `Registrar::max_extra_lockup_vote_weight_factor`.
+pub const LOCKUP_FACTOR_SCALE: u64 = 1_000_000_000;
+
+/// Denominator of `Registrar::weight_action_bps`.
+pub const BPS_SCALE: u16 = 10_000;
+
+/// Default `Registrar::lockup_saturation_secs`.
+pub const DEFAULT_LOCKUP_SATURATION_SECS: u64 = 5 * 365 * SECS_PER_DAY as u64;
+
+/// Default `Registrar::max_extra_lockup_vote_weight_factor`, which doubles
+/// the weight of tokens with a saturated lockup.
+pub const DEFAULT_MAX_EXTRA_LOCKUP_VOTE_WEIGHT_FACTOR: u64 = LOCKUP_FACTOR_SCALE;
+
+/// Scale of `Registrar::reward_per_share`.
+pub const REWARD_PER_SHARE_SCALE: u128 = 1_000_000_000_000;
+
+/// Largest `Registrar::reward_rate_per_sec`. Ten years of emissions still
+/// fit a voter's `reward_pending`.
+pub const MAX_REWARD_RATE_PER_SEC: u64 = u64::MAX / (10 * 365 * SECS_PER_DAY as u64);
+
 /// User account for minting voting rights.
 #[account(zero_copy)]
 pub struct Voter {
@@ -24,14 +1160,1199 @@ pub struct Voter {
     pub registrar: Pubkey,
     pub voter_bump: u8,
     pub voter_weight_record_bump: u8,
-    pub amount_deposited: u64,
+    /// Index of the vault shard this voter's tokens are held in.
+    pub vault_shard: u8,
+    /// The voter's deposits. Entry 0 is created with the voter and never
+    /// locked.
+    pub deposits: [DepositEntry; MAX_DEPOSIT_ENTRIES],
     // Would like to use solana_program::clock::Slot here, but Anchor's IDL
     // does not know the type.
     pub last_deposit_slot: u64,
+    /// Nonce of the last deposit that supplied one.
+    pub last_deposit_nonce: u64,
+    /// Average weight over the `Registrar::twab_window_secs` before
+    /// `twab_updated_ts`.
+    pub twab_weight: u64,
+    pub twab_updated_ts: i64,
+    /// Set by the registrar authority with `freeze_voter`. A frozen voter
+    /// has no weight and can't deposit, but can still withdraw.
+    pub frozen: bool,
+    /// The voter's deposits, converted at the rate and digit shift of their
+    /// mints, when rewards were last synced. Rewards accrue to these.
+    pub reward_shares: u64,
+    /// `reward_shares * Registrar::reward_per_share` at the last sync.
+    pub reward_debt: u128,
+    /// Accrued rewards not claimed yet, in native reward tokens.
+    pub reward_pending: u64,
+    /// Whether the voter has a `VoterCheckpoints` account that balance
+    /// changes must be recorded in.
+    pub checkpoints_enabled: bool,
+    /// Layout version, see `migrate_account`.
+    pub version: u8,
+    /// Copy of the registrar's `Registrar::weight_config`, see
+    /// `cache_weight_config`.
+    pub weight_config: WeightConfig,
+    /// Whether `weight_config` was complete when it was copied, see
+    /// `Registrar::weight_config_complete`.
+    pub weight_config_cached: bool,
+    /// Who paid the rent of the voter's `VoterWeightRecord`, and gets it
+    /// back when the voter is closed. The default pubkey for voters created
+    /// before it was recorded.
+    pub voter_weight_record_payer: Pubkey,
+    /// Whether the rent of the voter and its record was paid by the
+    /// registrar's rent sponsor, see `create_voter_sponsored`. Both are
+    /// refunded to it.
+    pub rent_sponsored: bool,
+    /// Native community tokens escrowed for proposals, see
+    /// `ProposalDepositEscrow`. They can't be withdrawn until released.
+    pub proposal_escrowed_native: u64,
+    /// Bit `i` is set if deposit entry `i` is a `Position`.
+    pub position_entries: u32,
+    /// Number of used delegations in the voter's `VoterDelegations`, which
+    /// its own weight then depends on.
+    pub delegations_count: u8,
+    /// Number of voters delegating to this voter, which is the number of
+    /// `DelegationRecord`s with it as the delegate.
+    pub delegators_count: u8,
+    /// Native community tokens locked for proposals, see `ProposalLock`.
+    /// They can't be withdrawn until released.
+    pub proposal_locked_native: u64,
+    /// Number of `ProposalLock`s each deposit entry is locked in. Tokens
+    /// can't leave an entry while it is locked.
+    pub proposal_locked_entries: [u8; MAX_DEPOSIT_ENTRIES],
+    /// `Registrar::config_generation` when `weight_config` was copied.
+    /// `update_voter_weight_record_cached` rejects outdated copies.
+    pub weight_config_generation: u64,
+    /// Space for future fields, zero until used.
+    ///
+    /// Accounts can't be resized on the Solana 1.8 runtime, so voter
+    /// accounts can't grow: new fields must fit into these bytes, with
+    /// `migrate` initializing them where zero is not the right value.
+    pub reserved: [u8; 80],
+}
+
+/// Current `Voter::version`.
+pub const VOTER_VERSION: u8 = 1;
+
+/// Maximum number of voters that can delegate to one voter. All of them
+/// are passed to `update_voter_weight_record` for the delegate.
+pub const MAX_DELEGATORS: u8 = 8;
+
+/// Return data of `get_version`, borsh-serialized.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ProgramVersion {
+    /// Semver of the program crate, like "0.1.0".
+    pub version: String,
+    /// `REGISTRAR_VERSION` of the deployment.
+    pub registrar_version: u8,
+    /// `VOTER_VERSION` of the deployment.
+    pub voter_version: u8,
+}
+
+impl ProgramVersion {
+    /// The version of this build.
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            registrar_version: REGISTRAR_VERSION,
+            voter_version: VOTER_VERSION,
+        }
+    }
 }
 
 impl Voter {
-    pub fn weight(&self) -> Result<u64> {
-        Ok(self.amount_deposited)
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 2030;
+
+    /// Copies the registrar's weight settings into `weight_config`. Must be
+    /// called again after they change, before
+    /// `update_voter_weight_record_cached` works again.
+    pub fn cache_weight_config(&mut self, registrar: &Registrar) {
+        self.weight_config = registrar.weight_config();
+        self.weight_config_cached = registrar.weight_config_complete();
+        self.weight_config_generation = registrar.config_generation;
+    }
+
+    /// Initializes a new voter of `authority` in the registrar at
+    /// `registrar_key`, with entry 0 for its unlocked tokens.
+    pub fn init(
+        &mut self,
+        registrar: &Registrar,
+        registrar_key: Pubkey,
+        authority: Pubkey,
+        voter_bump: u8,
+        voter_weight_record_bump: u8,
+        voter_weight_record_payer: Pubkey,
+    ) -> Result<()> {
+        let now_ts = registrar.clock_unix_timestamp()?;
+        self.voter_bump = voter_bump;
+        self.voter_weight_record_bump = voter_weight_record_bump;
+        self.version = VOTER_VERSION;
+        self.authority = authority;
+        self.registrar = registrar_key;
+        self.vault_shard = registrar.vault_shard_for(&authority);
+        self.voter_weight_record_payer = voter_weight_record_payer;
+
+        // Entry 0 holds the voter's unlocked tokens.
+        self.deposits[0] =
+            DepositEntry::new(Lockup::new_from_periods(LockupKind::None, now_ts, 0)?, 0);
+        self.twab_updated_ts = now_ts;
+        self.cache_weight_config(registrar);
+        Ok(())
+    }
+
+    /// Where the rent of the voter's `VoterWeightRecord` goes when the voter
+    /// is closed: its payer, or `sol_destination` if that isn't known.
+    pub fn voter_weight_record_rent_destination(&self, sol_destination: Pubkey) -> Pubkey {
+        if self.voter_weight_record_payer == Pubkey::default() {
+            sol_destination
+        } else {
+            self.voter_weight_record_payer
+        }
+    }
+
+    /// Upgrades the voter to `VOTER_VERSION`, one version at a time.
+    pub fn migrate(&mut self) {
+        while self.version < VOTER_VERSION {
+            match self.version {
+                // Voters from before versioning have the same layout as
+                // version 1.
+                0 => {}
+                _ => unreachable!(),
+            }
+            self.version += 1;
+        }
+    }
+
+    /// Reads a voter from an account owned by this program.
+    ///
+    /// Intended for other programs that want to inspect voter state without
+    /// going through CPI. Callers still need to check that the account is
+    /// at the expected address, see `seeds::find_voter_address`.
+    pub fn try_from_account_info(account: &AccountInfo) -> Result<Voter> {
+        require!(account.owner == &crate::ID, InvalidAccountOwner);
+        let data = account.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        Ok(Voter::try_deserialize(&mut data_slice)?)
+    }
+
+    /// Total voting weight of all deposits at `curr_ts`.
+    ///
+    /// With `Registrar::twab_window_secs` it is the smaller of the current
+    /// and the time-weighted average weight. With
+    /// `Registrar::quadratic_weight` it is the square root of that. It is
+    /// limited to `Registrar::max_voter_weight_per_voter`. Frozen voters and
+    /// voters that delegate have no weight.
+    ///
+    /// Deposits of mints with an oracle count without their price, see
+    /// `weight_at_prices`.
+    pub fn weight(&self, registrar: &Registrar, curr_ts: i64) -> Result<u64> {
+        self.weight_at_prices(registrar, curr_ts, &[None; MAX_VOTING_MINTS])
+    }
+
+    /// Like `weight`, with the weight of each deposit multiplied by the price
+    /// of its mint in `prices`. The time-weighted average is tracked
+    /// without prices.
+    pub fn weight_at_prices(
+        &self,
+        registrar: &Registrar,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<u64> {
+        self.weight_with_config(&registrar.weight_config(), curr_ts, prices)
+    }
+
+    /// Like `weight_at_prices`, with the registrar's weight rule for
+    /// `action`.
+    pub fn weight_for_action(
+        &self,
+        registrar: &Registrar,
+        action: Option<VoterWeightAction>,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<u64> {
+        self.weight_with_rule(
+            &registrar.weight_config(),
+            registrar.weight_rule(action),
+            curr_ts,
+            prices,
+        )
+    }
+
+    /// Like `weight_at_prices`, with the registrar's settings in `config`.
+    pub fn weight_with_config(
+        &self,
+        config: &WeightConfig,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<u64> {
+        self.weight_with_rule(config, WeightRule::Full, curr_ts, prices)
+    }
+
+    /// Like `weight_with_config`, counting the deposits `rule` selects.
+    /// Voters that delegate have no weight here, see `own_weight_for_action`.
+    pub fn weight_with_rule(
+        &self,
+        config: &WeightConfig,
+        rule: WeightRule,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<u64> {
+        if self.is_delegating() {
+            return Ok(0);
+        }
+        self.undelegated_weight_with_rule(config, rule, curr_ts, prices)
+    }
+
+    /// The voter's weight for `action`, without what it delegates. A voter
+    /// that delegates needs its `VoterDelegations` in `accounts`.
+    pub fn own_weight_for_action<'info>(
+        &self,
+        voter_key: &Pubkey,
+        registrar: &Registrar,
+        action: Option<VoterWeightAction>,
+        curr_ts: i64,
+        prices: &OraclePrices,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<u64> {
+        if !self.is_delegating() {
+            return self.weight_for_action(registrar, action, curr_ts, prices);
+        }
+        let loader = VoterDelegations::find(voter_key, accounts)?;
+        let delegations = loader.load()?;
+        let (own, _) =
+            self.split_weight_for_action(&delegations, registrar, action, curr_ts, prices)?;
+        Ok(own)
+    }
+
+    /// The voter's weight for `action`, split into its own part and the
+    /// parts of each of its `delegations`. A delegation gets the share of
+    /// the weight that its tokens have of the voting power of all deposits.
+    pub fn split_weight_for_action(
+        &self,
+        delegations: &VoterDelegations,
+        registrar: &Registrar,
+        action: Option<VoterWeightAction>,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<(u64, [u64; MAX_DELEGATIONS])> {
+        let config = registrar.weight_config();
+        let rule = registrar.weight_rule(action);
+        let weight = self.undelegated_weight_with_rule(&config, rule, curr_ts, prices)?;
+        let total_power = self.deposits_weight(&config, rule, curr_ts, prices)?;
+        let native = delegations.delegated_native(self);
+
+        let mut delegated = [0u64; MAX_DELEGATIONS];
+        for (i, delegation) in delegations.delegations.iter().enumerate() {
+            if native[i] == 0 || total_power == 0 {
+                continue;
+            }
+            let deposit = &self.deposits[delegation.deposit_entry_index as usize];
+            let power = deposit_power(deposit, &config, rule, curr_ts, prices)? as u128
+                * native[i] as u128
+                / deposit.amount_deposited_native as u128;
+            delegated[i] = (weight as u128 * power / total_power as u128) as u64;
+        }
+        let own = delegated
+            .iter()
+            .fold(weight, |own, &part| own.saturating_sub(part));
+        Ok((own, delegated))
+    }
+
+    /// Sum of the weight for `action` delegated to this voter at `voter_key`
+    /// by the voters in `accounts`. Each of them must delegate to this voter,
+    /// appear only once and have its `VoterDelegations` in `accounts` too.
+    /// Frozen voters get no delegated weight.
+    pub fn delegators_weight<'info>(
+        &self,
+        voter_key: &Pubkey,
+        registrar: &Registrar,
+        action: Option<VoterWeightAction>,
+        curr_ts: i64,
+        prices: &OraclePrices,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<u64> {
+        if self.frozen {
+            return Ok(0);
+        }
+        let delegators = accounts
+            .iter()
+            .filter(|info| {
+                info.owner == &crate::ID
+                    && info
+                        .try_borrow_data()
+                        .map_or(false, |data| data.starts_with(&Voter::discriminator()))
+            })
+            .collect::<Vec<_>>();
+        let mut weight = 0u64;
+        for (i, info) in delegators.iter().enumerate() {
+            require!(
+                !delegators[..i].iter().any(|other| other.key == info.key),
+                InvalidDelegator
+            );
+            let loader = AccountLoader::<Voter>::try_from(info)?;
+            let delegator = loader.load()?;
+            let delegations_loader = VoterDelegations::find(info.key, accounts)?;
+            let delegations = delegations_loader.load()?;
+            let (_, delegated) = delegator.split_weight_for_action(
+                &delegations,
+                registrar,
+                action,
+                curr_ts,
+                prices,
+            )?;
+            let mut delegates_to_voter = false;
+            for (delegation, part) in delegations.delegations.iter().zip(delegated.iter()) {
+                if delegation.delegate == *voter_key {
+                    delegates_to_voter = true;
+                    weight = weight.checked_add(*part).ok_or(ErrorCode::Overflow)?;
+                }
+            }
+            require!(delegates_to_voter, InvalidDelegator);
+        }
+        Ok(weight)
+    }
+
+    fn undelegated_weight_with_rule(
+        &self,
+        config: &WeightConfig,
+        rule: WeightRule,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<u64> {
+        if self.frozen {
+            return Ok(0);
+        }
+        let mut weight = self.deposits_weight(config, rule, curr_ts, prices)?;
+        if config.twab_window_secs > 0 {
+            weight = weight.min(self.twab_weight_at(config, weight, curr_ts));
+        }
+        if config.quadratic_weight {
+            weight = isqrt(weight);
+        }
+        if config.max_voter_weight_per_voter > 0 {
+            weight = weight.min(config.max_voter_weight_per_voter);
+        }
+        Ok(weight)
+    }
+
+    /// Sum of the voting power of all deposits at `curr_ts`, at `prices`.
+    fn deposits_weight(
+        &self,
+        config: &WeightConfig,
+        rule: WeightRule,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<u64> {
+        self.deposits
+            .iter()
+            .filter(|d| d.is_used)
+            .try_fold(0u64, |sum, d| {
+                let power = deposit_power(d, config, rule, curr_ts, prices)?;
+                sum.checked_add(power).ok_or(ErrorCode::Overflow.into())
+            })
+    }
+
+    /// The time-weighted average weight over the window before `curr_ts`,
+    /// assuming the weight was `weight` since `twab_updated_ts`.
+    fn twab_weight_at(&self, config: &WeightConfig, weight: u64, curr_ts: i64) -> u64 {
+        let window = config.twab_window_secs;
+        let elapsed = curr_ts.saturating_sub(self.twab_updated_ts).max(0) as u64;
+        if elapsed >= window {
+            return weight;
+        }
+        let sum = self.twab_weight as u128 * (window - elapsed) as u128
+            + weight as u128 * elapsed as u128;
+        (sum / window as u128) as u64
+    }
+
+    /// Appends the voter's weight at `curr_ts` to its `VoterCheckpoints`, if
+    /// it has them. The checkpoints account must then be in `accounts`.
+    pub fn write_checkpoint<'info>(
+        &self,
+        voter_key: &Pubkey,
+        registrar: &Registrar,
+        curr_ts: i64,
+        slot: u64,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if !self.checkpoints_enabled {
+            return Ok(());
+        }
+        let (address, _) = find_voter_checkpoints_address(voter_key);
+        let info = accounts
+            .iter()
+            .find(|info| info.key() == address)
+            .ok_or(ErrorCode::MissingCheckpointsAccount)?;
+        let loader = AccountLoader::<VoterCheckpoints>::try_from(info)?;
+        let mut checkpoints = loader.load_mut()?;
+        checkpoints.push(slot, self.weight(registrar, curr_ts)?);
+        Ok(())
+    }
+
+    /// Settles the rewards accrued to the voter's reward shares and updates
+    /// the shares to the current deposits. Must be called after every
+    /// change to the deposited amounts.
+    pub fn sync_rewards(&mut self, registrar: &mut Registrar, curr_ts: i64) -> Result<()> {
+        registrar.update_rewards(curr_ts)?;
+        let accrued = (self.reward_shares as u128 * registrar.reward_per_share
+            / REWARD_PER_SHARE_SCALE)
+            .saturating_sub(self.reward_debt);
+        self.reward_pending = u64::try_from(self.reward_pending as u128 + accrued)
+            .map_err(|_| ErrorCode::Overflow)?;
+
+        let shares = self.deposits.iter().filter(|d| d.is_used).try_fold(
+            0u128,
+            |sum, d| -> Result<u128> {
+                let config = &registrar.voting_mints[d.voting_mint_config_idx as usize];
+                let shares = config.weight(d.amount_deposited_native as u128)?;
+                Ok(sum.checked_add(shares).ok_or(ErrorCode::Overflow)?)
+            },
+        )?;
+        let shares = u64::try_from(shares).map_err(|_| ErrorCode::Overflow)?;
+        registrar.reward_total_shares = registrar
+            .reward_total_shares
+            .saturating_sub(self.reward_shares)
+            .checked_add(shares)
+            .ok_or(ErrorCode::Overflow)?;
+        self.reward_shares = shares;
+        self.reward_debt = shares as u128 * registrar.reward_per_share / REWARD_PER_SHARE_SCALE;
+        Ok(())
+    }
+
+    /// Folds the weight since the last update into the time-weighted
+    /// average. Must be called before every change to the deposits.
+    pub fn update_twab(&mut self, registrar: &Registrar, curr_ts: i64) -> Result<()> {
+        let config = registrar.weight_config();
+        let weight = self.deposits_weight(
+            &config,
+            WeightRule::Full,
+            curr_ts,
+            &[None; MAX_VOTING_MINTS],
+        )?;
+        self.twab_weight = self.twab_weight_at(&config, weight, curr_ts);
+        self.twab_updated_ts = curr_ts;
+        Ok(())
+    }
+
+    /// Adds `amount` native tokens of `mint` to the deposit entry at `index`
+    /// and returns the entry's balance afterwards.
+    pub fn deposit(
+        &mut self,
+        registrar: &Registrar,
+        index: u8,
+        mint: Pubkey,
+        amount: u64,
+        curr_ts: i64,
+        slot: u64,
+    ) -> Result<u64> {
+        require!(!registrar.deposits_paused, DepositsPaused);
+        require!(!self.frozen, VoterFrozen);
+        self.update_twab(registrar, curr_ts)?;
+        let voting_mint_config_idx = self.active_deposit_mut(index)?.voting_mint_config_idx;
+        let mint_config = &registrar.voting_mints[voting_mint_config_idx as usize];
+        require!(mint_config.mint == mint, InvalidMint);
+        mint_config.check_deposit(amount)?;
+        if mint_config.max_voter_deposit_native > 0 {
+            let deposited = self
+                .amount_deposited_native_for(voting_mint_config_idx)?
+                .checked_add(amount)
+                .ok_or(ErrorCode::Overflow)?;
+            require!(
+                deposited <= mint_config.max_voter_deposit_native,
+                VoterDepositCapExceeded
+            );
+        }
+        let deposit = self.active_deposit_mut(index)?;
+        deposit.deposit(amount, curr_ts)?;
+        let amount_deposited_native = deposit.amount_deposited_native;
+        self.last_deposit_slot = slot;
+        Ok(amount_deposited_native)
+    }
+
+    /// Native tokens deposited of the voting mint at `voting_mint_config_idx`,
+    /// including locked tokens.
+    pub fn amount_deposited_native_for(&self, voting_mint_config_idx: u8) -> Result<u64> {
+        self.deposits
+            .iter()
+            .filter(|d| d.is_used && d.voting_mint_config_idx == voting_mint_config_idx)
+            .try_fold(0u64, |sum, d| {
+                sum.checked_add(d.amount_deposited_native)
+                    .ok_or(ErrorCode::Overflow.into())
+            })
+    }
+
+    /// Whether the voter gives weight to delegates.
+    pub fn is_delegating(&self) -> bool {
+        self.delegations_count > 0
+    }
+
+    /// Checks that governance allows the delegates of the deposit entry at
+    /// `deposit_entry_index` to give up its weight, like the voter's own
+    /// token owner record must allow withdrawing. Otherwise tokens leaving
+    /// the entry could vote again after a delegate voted with them.
+    ///
+    /// A voter that delegates needs its `VoterDelegations` in `accounts`,
+    /// and for each delegate of the entry the delegate's `Voter` and token
+    /// owner record.
+    pub fn check_entry_delegates_can_withdraw<'info>(
+        &self,
+        voter_key: &Pubkey,
+        registrar: &Registrar,
+        deposit_entry_index: u8,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        if !self.is_delegating() {
+            return Ok(());
+        }
+        let loader = VoterDelegations::find(voter_key, accounts)?;
+        let delegations = loader.load()?;
+        for delegation in delegations
+            .delegations
+            .iter()
+            .filter(|d| d.is_used() && d.deposit_entry_index == deposit_entry_index)
+        {
+            let delegate_info = accounts
+                .iter()
+                .find(|info| info.key() == delegation.delegate)
+                .ok_or(ErrorCode::MissingDelegateAccounts)?;
+            let delegate = AccountLoader::<Voter>::try_from(delegate_info)?;
+            let delegate_authority = delegate.load()?.authority;
+            TokenOwnerRecordHeader::find(
+                accounts,
+                &registrar.governance_program_id,
+                &registrar.realm,
+                &registrar.realm_community_mint,
+                &delegate_authority,
+            )
+            .ok_or(ErrorCode::MissingDelegateAccounts)?
+            .assert_can_withdraw_governing_tokens()?;
+        }
+        Ok(())
+    }
+
+    /// Whether the deposit entry at `index` is a `Position`.
+    pub fn has_position(&self, index: u8) -> bool {
+        self.position_entries & (1 << index) != 0
+    }
+
+    pub fn set_position(&mut self, index: u8, position: bool) {
+        if position {
+            self.position_entries |= 1 << index;
+        } else {
+            self.position_entries &= !(1 << index);
+        }
+    }
+
+    /// Native community tokens that are deposited and not escrowed or
+    /// locked for proposals yet.
+    pub fn proposal_deposit_available(&self) -> Result<u64> {
+        self.amount_deposited_native_for(0)?
+            .checked_sub(self.proposal_escrowed_native)
+            .and_then(|available| available.checked_sub(self.proposal_locked_native))
+            .ok_or(ErrorCode::ProposalDepositEscrowed.into())
+    }
+
+    /// Checks that the deposit entry at `index` isn't locked for a
+    /// proposal, before tokens leave it.
+    pub fn check_entry_not_locked(&self, index: u8) -> Result<()> {
+        let locks = self
+            .proposal_locked_entries
+            .get(index as usize)
+            .ok_or(ErrorCode::InvalidDepositId)?;
+        require!(*locks == 0, DepositEntryLockedForProposal);
+        Ok(())
+    }
+
+    /// Adds a proposal lock to each used deposit entry with a bit set in
+    /// `entries`, or removes it if `lock` is false.
+    pub fn lock_entries(&mut self, entries: u32, lock: bool) -> Result<()> {
+        require!(entries != 0, DepositEntryNotFound);
+        for i in 0..MAX_DEPOSIT_ENTRIES {
+            if entries & (1 << i) == 0 {
+                continue;
+            }
+            require!(!lock || self.deposits[i].is_used, DepositEntryNotFound);
+            let locks = &mut self.proposal_locked_entries[i];
+            *locks = if lock {
+                locks.checked_add(1).ok_or(ErrorCode::Overflow)?
+            } else {
+                locks.checked_sub(1).ok_or(ErrorCode::Underflow)?
+            };
+        }
+        Ok(())
+    }
+
+    /// Voting power of the deposit entries with a bit set in `entries`,
+    /// with the registrar's weight rule for `action`. Unlike
+    /// `weight_for_action`, nothing voter-wide like caps or quadratic
+    /// weight is applied.
+    pub fn entries_power(
+        &self,
+        registrar: &Registrar,
+        entries: u32,
+        action: Option<VoterWeightAction>,
+        curr_ts: i64,
+        prices: &OraclePrices,
+    ) -> Result<u64> {
+        let config = registrar.weight_config();
+        let rule = registrar.weight_rule(action);
+        self.deposits
+            .iter()
+            .enumerate()
+            .filter(|(i, d)| d.is_used && entries & (1 << i) != 0)
+            .try_fold(0u64, |sum, (_, d)| {
+                let power = deposit_power(d, &config, rule, curr_ts, prices)?;
+                sum.checked_add(power).ok_or(ErrorCode::Overflow.into())
+            })
+    }
+
+    /// Checks that the community tokens escrowed or locked for proposals
+    /// are still deposited, after withdrawing.
+    pub fn check_proposal_escrow(&self) -> Result<()> {
+        self.proposal_deposit_available().map(|_| ())
+    }
+
+    /// Total native tokens deposited, including locked tokens.
+    pub fn amount_deposited_native(&self) -> Result<u64> {
+        self.deposits
+            .iter()
+            .filter(|d| d.is_used)
+            .try_fold(0u64, |sum, d| {
+                sum.checked_add(d.amount_deposited_native)
+                    .ok_or(ErrorCode::Overflow.into())
+            })
+    }
+
+    /// The deposit entry at `index`, which must not be in use yet.
+    pub fn unused_deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
+        let index = index as usize;
+        require!(index < self.deposits.len(), InvalidDepositId);
+        let deposit = &mut self.deposits[index];
+        require!(!deposit.is_used, DepositEntryFull);
+        Ok(deposit)
+    }
+
+    pub fn active_deposit_mut(&mut self, index: u8) -> Result<&mut DepositEntry> {
+        let index = index as usize;
+        require!(index < self.deposits.len(), InvalidDepositId);
+        let deposit = &mut self.deposits[index];
+        require!(deposit.is_used, DepositEntryNotFound);
+        Ok(deposit)
+    }
+}
+
+/// Bytes of a deposit entry label.
+pub const DEPOSIT_LABEL_LEN: usize = 32;
+
+/// Labels of a voter's deposit entries, like "team-grant-2024", for wallets
+/// to show. At address `seeds::find_deposit_labels_address`.
+///
+/// Labels are UTF-8, padded with zero bytes. They aren't cleared when an
+/// entry is closed, so a reused entry keeps the old label until it is set
+/// again.
+#[account(zero_copy)]
+pub struct DepositLabels {
+    pub voter: Pubkey,
+    /// The label of each entry of `Voter::deposits`.
+    pub labels: [[u8; DEPOSIT_LABEL_LEN]; MAX_DEPOSIT_ENTRIES],
+}
+
+impl DepositLabels {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 32 + DEPOSIT_LABEL_LEN * MAX_DEPOSIT_ENTRIES;
+
+    /// The label of the deposit entry at `index`, without the padding.
+    /// `None` if it isn't valid UTF-8.
+    pub fn label(&self, index: usize) -> Option<&str> {
+        let label = &self.labels[index];
+        let len = label.iter().position(|&b| b == 0).unwrap_or(label.len());
+        std::str::from_utf8(&label[..len]).ok()
+    }
+}
+
+/// Maximum number of delegations of a voter, see `VoterDelegations`.
+pub const MAX_DELEGATIONS: usize = 8;
+
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum DelegationShareKind {
+    /// Basis points of the deposit entry.
+    Bps,
+    /// Native tokens of the deposit entry.
+    Amount,
+}
+
+/// A part of a deposit entry whose weight goes to `delegate`.
+#[zero_copy]
+pub struct Delegation {
+    /// The voter receiving the weight. The default pubkey if the slot is
+    /// unused.
+    pub delegate: Pubkey,
+    /// Index into `Voter::deposits`.
+    pub deposit_entry_index: u8,
+    pub share_kind: DelegationShareKind,
+    /// Basis points or native tokens of the entry, see `share_kind`.
+    pub share: u64,
+    /// When `request_undelegate` was called for the delegate, zero if it
+    /// wasn't.
+    pub undelegate_requested_ts: i64,
+}
+
+impl Delegation {
+    pub fn is_used(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    /// Native tokens of `deposit` the delegation stands for.
+    fn native(&self, deposit: &DepositEntry) -> u64 {
+        match self.share_kind {
+            DelegationShareKind::Bps => {
+                (deposit.amount_deposited_native as u128 * self.share as u128 / BPS_SCALE as u128)
+                    as u64
+            }
+            DelegationShareKind::Amount => self.share,
+        }
+    }
+}
+
+/// How `voter` splits its weight between delegates, see `delegate`.
+#[account(zero_copy)]
+pub struct VoterDelegations {
+    pub voter: Pubkey,
+    pub delegations: [Delegation; MAX_DELEGATIONS],
+}
+
+impl VoterDelegations {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 32 + MAX_DELEGATIONS * (32 + 1 + 1 + 8 + 8);
+
+    /// The `VoterDelegations` of the voter at `voter_key` among `accounts`.
+    pub fn find<'info>(
+        voter_key: &Pubkey,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<AccountLoader<'info, VoterDelegations>> {
+        let (address, _) = find_voter_delegations_address(voter_key);
+        let info = accounts
+            .iter()
+            .find(|info| info.key() == address)
+            .ok_or(ErrorCode::MissingDelegationsAccount)?;
+        Ok(AccountLoader::<VoterDelegations>::try_from(info)?)
+    }
+
+    /// Native tokens each delegation stands for. The delegations of an entry
+    /// are limited, in slot order, to its deposited amount, which may have
+    /// shrunk since delegating.
+    pub fn delegated_native(&self, voter: &Voter) -> [u64; MAX_DELEGATIONS] {
+        let mut left = [0u64; MAX_DEPOSIT_ENTRIES];
+        for (left, deposit) in left.iter_mut().zip(voter.deposits.iter()) {
+            if deposit.is_used {
+                *left = deposit.amount_deposited_native;
+            }
+        }
+        let mut native = [0u64; MAX_DELEGATIONS];
+        for (native, delegation) in native.iter_mut().zip(self.delegations.iter()) {
+            if !delegation.is_used() {
+                continue;
+            }
+            let index = delegation.deposit_entry_index as usize;
+            *native = delegation.native(&voter.deposits[index]).min(left[index]);
+            left[index] -= *native;
+        }
+        native
+    }
+
+    /// Checks that the delegations of each deposit entry of `voter` add up
+    /// to at most its deposited amount and 100%.
+    pub fn check_shares(&self, voter: &Voter) -> Result<()> {
+        let mut native = [0u128; MAX_DEPOSIT_ENTRIES];
+        let mut bps = [0u64; MAX_DEPOSIT_ENTRIES];
+        for delegation in self.delegations.iter().filter(|d| d.is_used()) {
+            let index = delegation.deposit_entry_index as usize;
+            native[index] += delegation.native(&voter.deposits[index]) as u128;
+            if delegation.share_kind == DelegationShareKind::Bps {
+                bps[index] += delegation.share;
+            }
+        }
+        for (i, deposit) in voter.deposits.iter().enumerate() {
+            require!(
+                native[i] <= deposit.amount_deposited_native as u128 && bps[i] <= BPS_SCALE as u64,
+                DelegationExceedsDeposit
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Marks that `delegator` delegates to `delegate`, while the shares are in
+/// the delegator's `VoterDelegations`. At address
+/// `seeds::find_delegation_record_address`, so indexers can list the
+/// delegators of a voter by filtering on `delegate`.
+#[account]
+pub struct DelegationRecord {
+    pub registrar: Pubkey,
+    /// The delegating voter.
+    pub delegator: Pubkey,
+    /// The voter receiving the weight.
+    pub delegate: Pubkey,
+}
+
+impl DelegationRecord {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 8 + 3 * 32;
+}
+
+/// Number of checkpoints a `VoterCheckpoints` account retains.
+pub const MAX_CHECKPOINTS: usize = 256;
+
+/// A voter's weight as of a slot.
+#[zero_copy]
+pub struct Checkpoint {
+    pub slot: u64,
+    pub weight: u64,
+}
+
+/// History of a voter's weight, at address
+/// `seeds::find_voter_checkpoints_address`.
+///
+/// A checkpoint is appended whenever the voter's deposits change. The weight
+/// recorded is `Voter::weight` at that time; the decay of lockups between
+/// checkpoints isn't recorded. Once `MAX_CHECKPOINTS` are written, each new
+/// one overwrites the oldest, so the full history is only available from the
+/// program's transaction logs.
+#[account(zero_copy)]
+pub struct VoterCheckpoints {
+    pub voter: Pubkey,
+    /// Number of checkpoints ever written. The latest is at index
+    /// `(count - 1) % MAX_CHECKPOINTS`.
+    pub count: u64,
+    pub checkpoints: [Checkpoint; MAX_CHECKPOINTS],
+}
+
+impl VoterCheckpoints {
+    /// Size of the account data, including the 8 byte discriminator.
+    pub const LEN: usize = 4144;
+
+    /// Appends a checkpoint. A checkpoint in the same slot as the latest one
+    /// replaces it.
+    pub fn push(&mut self, slot: u64, weight: u64) {
+        if self.count > 0 {
+            let latest = &mut self.checkpoints[(self.count - 1) as usize % MAX_CHECKPOINTS];
+            if latest.slot == slot {
+                latest.weight = weight;
+                return;
+            }
+        }
+        self.checkpoints[self.count as usize % MAX_CHECKPOINTS] = Checkpoint { slot, weight };
+        self.count += 1;
     }
+
+    /// The voter's weight as of `slot`: that of the latest checkpoint at or
+    /// before it. `None` if `slot` is before the oldest retained checkpoint.
+    pub fn weight_at(&self, slot: u64) -> Option<u64> {
+        let retained = self.count.min(MAX_CHECKPOINTS as u64);
+        (0..retained)
+            .map(|back| &self.checkpoints[(self.count - 1 - back) as usize % MAX_CHECKPOINTS])
+            .find(|checkpoint| checkpoint.slot <= slot)
+            .map(|checkpoint| checkpoint.weight)
+    }
+}
+
+/// Bookkeeping for a single deposit with a given lockup schedule.
+#[zero_copy]
+pub struct DepositEntry {
+    pub lockup: Lockup,
+    /// Native tokens deposited, including still locked tokens.
+    pub amount_deposited_native: u64,
+    /// Native tokens that were locked when the lockup started.
+    pub amount_initially_locked_native: u64,
+    pub is_used: bool,
+    /// Index into `Registrar::voting_mints` of the deposited mint.
+    pub voting_mint_config_idx: u8,
+    /// Native tokens requested for withdrawal. They no longer count towards
+    /// the voting weight.
+    pub amount_pending_withdrawal: u64,
+    /// When the last withdrawal was requested.
+    pub withdrawal_requested_ts: i64,
+}
+
+impl DepositEntry {
+    /// An empty deposit entry in use.
+    pub fn new(lockup: Lockup, voting_mint_config_idx: u8) -> Self {
+        Self {
+            lockup,
+            amount_deposited_native: 0,
+            amount_initially_locked_native: 0,
+            is_used: true,
+            voting_mint_config_idx,
+            amount_pending_withdrawal: 0,
+            withdrawal_requested_ts: 0,
+        }
+    }
+
+    /// Native tokens still locked at `curr_ts`.
+    pub fn amount_locked(&self, curr_ts: i64) -> u64 {
+        match self.lockup.kind {
+            LockupKind::None => 0,
+            LockupKind::Cliff | LockupKind::Constant => {
+                if self.lockup.expired(curr_ts) {
+                    0
+                } else {
+                    self.amount_initially_locked_native
+                }
+            }
+            LockupKind::Daily => {
+                let periods_total = self.lockup.periods_total();
+                if periods_total == 0 {
+                    return 0;
+                }
+                let periods_left = self.lockup.periods_left(curr_ts);
+                (self.amount_initially_locked_native as u128 * periods_left as u128
+                    / periods_total as u128) as u64
+            }
+        }
+    }
+
+    /// Adds `amount` native tokens to the deposit, locked under its lockup.
+    ///
+    /// For daily vesting, the schedule is restarted at the beginning of the
+    /// current period: the tokens that are still locked plus the new ones
+    /// then vest over the remaining periods.
+    pub fn deposit(&mut self, amount: u64, curr_ts: i64) -> Result<()> {
+        self.amount_deposited_native = self
+            .amount_deposited_native
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        if self.lockup.kind == LockupKind::None {
+            return Ok(());
+        }
+        if self.lockup.kind == LockupKind::Daily && curr_ts > self.lockup.start_ts {
+            let locked = self.amount_locked(curr_ts);
+            // periods_left <= periods_total, and both are at most a few
+            // billion days, so this can't overflow an i64.
+            let periods_elapsed = self.lockup.periods_total() - self.lockup.periods_left(curr_ts);
+            self.lockup.start_ts += periods_elapsed as i64 * SECS_PER_DAY;
+            self.amount_initially_locked_native = locked;
+        }
+        self.amount_initially_locked_native = self
+            .amount_initially_locked_native
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// Unlocks all tokens that are locked at `curr_ts`, minus a penalty of
+    /// `penalty_bps` basis points of them, rounded up. The penalty is removed
+    /// from the deposit and returned.
+    ///
+    /// The lockup itself is kept: tokens deposited into the entry later are
+    /// locked under it again.
+    pub fn unlock_early(&mut self, penalty_bps: u16, curr_ts: i64) -> Result<u64> {
+        let locked = self.amount_locked(curr_ts) as u128;
+        let scale = BPS_SCALE as u128;
+        let penalty = ((locked * penalty_bps as u128 + scale - 1) / scale) as u64;
+        self.amount_deposited_native = self
+            .amount_deposited_native
+            .checked_sub(penalty)
+            .ok_or(ErrorCode::Underflow)?;
+        self.amount_initially_locked_native = 0;
+        Ok(penalty)
+    }
+
+    /// Native tokens that may be withdrawn at `curr_ts`.
+    pub fn amount_unlocked(&self, curr_ts: i64) -> u64 {
+        self.amount_deposited_native
+            .saturating_sub(self.amount_locked(curr_ts))
+    }
+
+    /// Unlocked native tokens that are not yet requested for withdrawal.
+    pub fn amount_available(&self, curr_ts: i64) -> u64 {
+        self.amount_unlocked(curr_ts)
+            .saturating_sub(self.amount_pending_withdrawal)
+    }
+
+    /// Voting weight of the deposit: the deposited amount plus extra weight
+    /// for the locked amount, scaled by the remaining lockup time, converted
+    /// at the rate and digit shift of the deposited mint.
+    ///
+    /// With `Registrar::vote_escrow_weight`, only locked tokens have weight,
+    /// like in Curve's vote escrow: the locked amount times the remaining
+    /// lockup time over `lockup_saturation_secs`. The weight decays as the
+    /// lockup runs down and is recomputed on every
+    /// `update_voter_weight_record`.
+    pub fn voting_power(&self, registrar: &Registrar, curr_ts: i64) -> Result<u64> {
+        self.voting_power_with_config(&registrar.weight_config(), curr_ts)
+    }
+
+    /// Like `voting_power`, with the registrar's settings in `config`.
+    pub fn voting_power_with_config(&self, config: &WeightConfig, curr_ts: i64) -> Result<u64> {
+        self.voting_power_with_rule(config, WeightRule::Full, curr_ts)
+    }
+
+    /// Like `voting_power_with_config`, counting only locked tokens with
+    /// `WeightRule::LockedOnly`. Vote escrow weight only counts locked
+    /// tokens anyway.
+    pub fn voting_power_with_rule(
+        &self,
+        config: &WeightConfig,
+        rule: WeightRule,
+        curr_ts: i64,
+    ) -> Result<u64> {
+        let counted = match rule {
+            WeightRule::Full => self
+                .amount_deposited_native
+                .checked_sub(self.amount_pending_withdrawal)
+                .ok_or(ErrorCode::Underflow)?,
+            WeightRule::LockedOnly => self.amount_locked(curr_ts),
+        };
+        let amount = if config.vote_escrow_weight {
+            self.voting_power_locked(config, curr_ts, LOCKUP_FACTOR_SCALE)? as u128
+        } else {
+            counted as u128
+                + self.voting_power_locked(
+                    config,
+                    curr_ts,
+                    config.max_extra_lockup_vote_weight_factor,
+                )? as u128
+        };
+        let weight = config.mint_weight(self.voting_mint_config_idx, amount)?;
+        u64::try_from(weight).map_err(|_| ErrorCode::Overflow.into())
+    }
+
+    /// Weight of the locked amount: `factor / LOCKUP_FACTOR_SCALE` of it,
+    /// scaled by the remaining lockup time up to the saturation.
+    fn voting_power_locked(&self, config: &WeightConfig, curr_ts: i64, factor: u64) -> Result<u64> {
+        let locked = self.amount_locked(curr_ts) as u128;
+        let saturation_secs = config.lockup_saturation_secs;
+        let saturation = saturation_secs as u128;
+        let factor = factor as u128;
+        let scale = LOCKUP_FACTOR_SCALE as u128;
+        if saturation == 0 {
+            return Ok(0);
+        }
+        let denominator = saturation * scale;
+        let extra = match self.lockup.kind {
+            LockupKind::None => 0,
+            LockupKind::Cliff | LockupKind::Constant => {
+                let secs = self.lockup.seconds_left(curr_ts).min(saturation_secs) as u128;
+                (locked * secs)
+                    .checked_mul(factor)
+                    .ok_or(ErrorCode::Overflow)?
+                    / denominator
+            }
+            LockupKind::Daily => {
+                // Each remaining period unlocks an equal share of the locked
+                // amount and gets extra weight for the time until it unlocks.
+                let periods_left = self.lockup.periods_left(curr_ts) as u128;
+                if periods_left == 0 {
+                    return Ok(0);
+                }
+                let secs_sum = self
+                    .lockup
+                    .daily_unlock_seconds_sum(curr_ts, saturation_secs);
+                (locked.checked_mul(secs_sum).ok_or(ErrorCode::Overflow)? / periods_left)
+                    .checked_mul(factor)
+                    .ok_or(ErrorCode::Overflow)?
+                    / denominator
+            }
+        };
+        u64::try_from(extra).map_err(|_| ErrorCode::Overflow.into())
+    }
+}
+
+#[zero_copy]
+pub struct Lockup {
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub kind: LockupKind,
+}
+
+impl Lockup {
+    /// A lockup of `kind` that starts at `start_ts` and lasts `periods` days.
+    pub fn new_from_periods(kind: LockupKind, start_ts: i64, periods: u32) -> Result<Self> {
+        match kind {
+            LockupKind::None => require!(periods == 0, InvalidDays),
+            LockupKind::Cliff | LockupKind::Daily | LockupKind::Constant => {
+                require!(periods > 0, InvalidDays)
+            }
+        }
+        let end_ts = start_ts
+            .checked_add(i64::from(periods) * SECS_PER_DAY)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(Self {
+            start_ts,
+            end_ts,
+            kind,
+        })
+    }
+
+    pub fn expired(&self, curr_ts: i64) -> bool {
+        match self.kind {
+            // Constant lockups don't run down until unlocking is started.
+            LockupKind::Constant => false,
+            _ => curr_ts >= self.end_ts,
+        }
+    }
+
+    pub fn seconds_left(&self, curr_ts: i64) -> u64 {
+        if self.kind == LockupKind::Constant {
+            (self.end_ts - self.start_ts) as u64
+        } else if self.expired(curr_ts) {
+            0
+        } else {
+            (self.end_ts - curr_ts) as u64
+        }
+    }
+
+    /// Number of days the lockup lasts.
+    pub fn periods_total(&self) -> u64 {
+        ((self.end_ts - self.start_ts) / SECS_PER_DAY) as u64
+    }
+
+    /// Number of days that have not fully passed yet.
+    pub fn periods_left(&self, curr_ts: i64) -> u64 {
+        let elapsed = ((curr_ts - self.start_ts).max(0) / SECS_PER_DAY) as u64;
+        self.periods_total().saturating_sub(elapsed)
+    }
+
+    /// Sum over the remaining periods of the seconds until each period
+    /// unlocks, each capped at `saturation`.
+    ///
+    /// Computed in u128: the periods and seconds of a lockup fit into 64
+    /// bits, so the products here can't overflow.
+    fn daily_unlock_seconds_sum(&self, curr_ts: i64, saturation: u64) -> u128 {
+        let periods_left = self.periods_left(curr_ts) as u128;
+        if periods_left == 0 {
+            return 0;
+        }
+        let saturation = saturation as u128;
+        let day = SECS_PER_DAY as u128;
+        // Seconds until the next period unlocks; the later ones follow at
+        // one day intervals.
+        let first = self.seconds_left(curr_ts) as u128 - (periods_left - 1) * day;
+        // Number of periods that unlock before saturation is reached.
+        let unsaturated = if first >= saturation {
+            0
+        } else {
+            periods_left.min((saturation - first + day - 1) / day)
+        };
+        unsaturated * first
+            + day * unsaturated * unsaturated.saturating_sub(1) / 2
+            + (periods_left - unsaturated) * saturation
+    }
+}
+
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum LockupKind {
+    /// Not locked, the full amount can be withdrawn at any time.
+    None,
+    /// Locked until the lockup end, then everything unlocks at once.
+    Cliff,
+    /// Unlocks linearly, an equal share at the end of every day.
+    Daily,
+    /// Stays locked for the full lockup duration until the voter calls
+    /// `start_unlock`, which turns it into a cliff lockup ending one
+    /// duration later.
+    Constant,
 }
@@ -0,0 +1,263 @@
+use crate::account::{LockupKind, VoterWeightAction};
+use anchor_lang::prelude::*;
+
+// Instruction arguments are versioned so that new parameters can be added
+// without breaking existing clients: a new version becomes a new variant and
+// the program keeps accepting all older ones.
+
+/// Arguments for the `create_deposit_entry` instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum CreateDepositEntryArgs {
+    /// Creates the entry with a lockup of `kind` that starts now and lasts
+    /// `periods` days, for the realm's community mint.
+    V1 {
+        deposit_entry_index: u8,
+        kind: LockupKind,
+        periods: u32,
+    },
+    /// Like V1, for the voting mint at `voting_mint_config_idx`.
+    V2 {
+        deposit_entry_index: u8,
+        kind: LockupKind,
+        periods: u32,
+        voting_mint_config_idx: u8,
+    },
+}
+
+impl CreateDepositEntryArgs {
+    pub fn deposit_entry_index(&self) -> u8 {
+        match *self {
+            CreateDepositEntryArgs::V1 {
+                deposit_entry_index,
+                ..
+            } => deposit_entry_index,
+            CreateDepositEntryArgs::V2 {
+                deposit_entry_index,
+                ..
+            } => deposit_entry_index,
+        }
+    }
+
+    pub fn kind(&self) -> LockupKind {
+        match *self {
+            CreateDepositEntryArgs::V1 { kind, .. } => kind,
+            CreateDepositEntryArgs::V2 { kind, .. } => kind,
+        }
+    }
+
+    /// Lockup duration in days.
+    pub fn periods(&self) -> u32 {
+        match *self {
+            CreateDepositEntryArgs::V1 { periods, .. } => periods,
+            CreateDepositEntryArgs::V2 { periods, .. } => periods,
+        }
+    }
+
+    /// Index into `Registrar::voting_mints` of the mint to deposit.
+    pub fn voting_mint_config_idx(&self) -> u8 {
+        match *self {
+            CreateDepositEntryArgs::V1 { .. } => 0,
+            CreateDepositEntryArgs::V2 {
+                voting_mint_config_idx,
+                ..
+            } => voting_mint_config_idx,
+        }
+    }
+}
+
+/// Arguments for the `grant` instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum GrantArgs {
+    /// Creates the deposit entry with a lockup of `kind` that lasts
+    /// `periods` days from `start_ts` (default: now), holding `amount`
+    /// native tokens.
+    V1 {
+        deposit_entry_index: u8,
+        kind: LockupKind,
+        start_ts: Option<i64>,
+        periods: u32,
+        amount: u64,
+    },
+}
+
+/// Arguments for the `claim_from_merkle` instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum ClaimFromMerkleArgs {
+    /// Claims the `amount` native tokens at `index` of the distribution into
+    /// a new deposit entry at `deposit_entry_index`. `proof` are the sibling
+    /// nodes from the leaf up to the root.
+    V1 {
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        deposit_entry_index: u8,
+    },
+}
+
+impl ClaimFromMerkleArgs {
+    /// The position of the claim in the distribution.
+    pub fn index(&self) -> u64 {
+        match *self {
+            ClaimFromMerkleArgs::V1 { index, .. } => index,
+        }
+    }
+}
+
+/// Arguments for the `deposit` instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum DepositArgs {
+    V1 {
+        amount: u64,
+    },
+    /// Like V1, but with a client-chosen nonce that must be larger than the
+    /// nonce of the voter's previous deposit. Retried transactions then fail
+    /// instead of depositing twice.
+    V2 {
+        amount: u64,
+        nonce: u64,
+    },
+    /// Deposits into the given deposit entry. Older versions always deposit
+    /// into entry 0.
+    V3 {
+        deposit_entry_index: u8,
+        amount: u64,
+        nonce: Option<u64>,
+    },
+}
+
+impl DepositArgs {
+    /// The index of the deposit entry to deposit into.
+    pub fn deposit_entry_index(&self) -> u8 {
+        match *self {
+            DepositArgs::V1 { .. } | DepositArgs::V2 { .. } => 0,
+            DepositArgs::V3 {
+                deposit_entry_index,
+                ..
+            } => deposit_entry_index,
+        }
+    }
+
+    /// The number of native tokens to deposit.
+    pub fn amount(&self) -> u64 {
+        match *self {
+            DepositArgs::V1 { amount } => amount,
+            DepositArgs::V2 { amount, .. } => amount,
+            DepositArgs::V3 { amount, .. } => amount,
+        }
+    }
+
+    /// The deposit nonce, if the client supplied one.
+    pub fn nonce(&self) -> Option<u64> {
+        match *self {
+            DepositArgs::V1 { .. } => None,
+            DepositArgs::V2 { nonce, .. } => Some(nonce),
+            DepositArgs::V3 { nonce, .. } => nonce,
+        }
+    }
+}
+
+/// A new deposit entry created by `deposit_tranches`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct DepositTranche {
+    pub deposit_entry_index: u8,
+    /// Lockup of the entry, starting now and lasting `periods` days.
+    pub kind: LockupKind,
+    pub periods: u32,
+    /// Native tokens deposited into the entry.
+    pub amount: u64,
+}
+
+/// Arguments for the `deposit_tranches` instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum DepositTranchesArgs {
+    V1 { tranches: Vec<DepositTranche> },
+}
+
+/// Arguments for the `withdraw` instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum WithdrawArgs {
+    V1 {
+        amount: u64,
+    },
+    /// Withdraws from the given deposit entry. V1 always withdraws from
+    /// entry 0.
+    V2 {
+        deposit_entry_index: u8,
+        amount: u64,
+    },
+    /// Like V2. With `unwrap_sol`, withdraws wrapped SOL as SOL by closing
+    /// the destination token account into the voter authority.
+    V3 {
+        deposit_entry_index: u8,
+        amount: u64,
+        unwrap_sol: bool,
+    },
+}
+
+impl WithdrawArgs {
+    /// The index of the deposit entry to withdraw from.
+    pub fn deposit_entry_index(&self) -> u8 {
+        match *self {
+            WithdrawArgs::V1 { .. } => 0,
+            WithdrawArgs::V2 {
+                deposit_entry_index,
+                ..
+            } => deposit_entry_index,
+            WithdrawArgs::V3 {
+                deposit_entry_index,
+                ..
+            } => deposit_entry_index,
+        }
+    }
+
+    /// The number of native tokens to withdraw.
+    pub fn amount(&self) -> u64 {
+        match *self {
+            WithdrawArgs::V1 { amount } => amount,
+            WithdrawArgs::V2 { amount, .. } => amount,
+            WithdrawArgs::V3 { amount, .. } => amount,
+        }
+    }
+
+    /// Whether to unwrap withdrawn wrapped SOL.
+    pub fn unwrap_sol(&self) -> bool {
+        match *self {
+            WithdrawArgs::V1 { .. } | WithdrawArgs::V2 { .. } => false,
+            WithdrawArgs::V3 { unwrap_sol, .. } => unwrap_sol,
+        }
+    }
+}
+
+/// Arguments for the `update_voter_weight_record` instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum UpdateVoterWeightRecordArgs {
+    /// Writes the weight for `weight_action`, or for any action if `None`.
+    V1 {
+        weight_action: Option<VoterWeightAction>,
+    },
+    /// Like V1, also restricting the record to the proposal or governance
+    /// `weight_action_target`, or to any if `None`.
+    V2 {
+        weight_action: Option<VoterWeightAction>,
+        weight_action_target: Option<Pubkey>,
+    },
+}
+
+impl UpdateVoterWeightRecordArgs {
+    pub fn weight_action(&self) -> Option<VoterWeightAction> {
+        match *self {
+            UpdateVoterWeightRecordArgs::V1 { weight_action } => weight_action,
+            UpdateVoterWeightRecordArgs::V2 { weight_action, .. } => weight_action,
+        }
+    }
+
+    pub fn weight_action_target(&self) -> Option<Pubkey> {
+        match *self {
+            UpdateVoterWeightRecordArgs::V1 { .. } => None,
+            UpdateVoterWeightRecordArgs::V2 {
+                weight_action_target,
+                ..
+            } => weight_action_target,
+        }
+    }
+}
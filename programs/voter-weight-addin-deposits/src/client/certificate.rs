@@ -0,0 +1,113 @@
+//! Signed statements about a wallet's voter weight.
+//!
+//! Off-chain tools (forums, chat gating, snapshot votes) can consume a
+//! certificate instead of running their own indexer: it states the weight
+//! computed from the voter account at a slot, carries a hash of that account
+//! data so anyone with access to the account at that slot can re-check it,
+//! and is signed by the issuer.
+
+use crate::account::{Registrar, Voter};
+use crate::error::*;
+use crate::seeds::find_registrar_address;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+
+/// Prefix of signed messages, so certificates can't be confused with other
+/// signed data.
+const MESSAGE_DOMAIN: &[u8] = b"voter-weight-addin-deposits:certificate:v1";
+
+/// The content of a certificate.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct VoterWeightStatement {
+    pub program_id: Pubkey,
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub voter_authority: Pubkey,
+    /// Slot at which the voter account data was read.
+    pub slot: u64,
+    /// Unix timestamp of `slot`, used for computing lockup weights.
+    pub unix_timestamp: i64,
+    /// Hash of the voter account data at `slot`.
+    pub voter_account_hash: [u8; 32],
+    pub voter_weight: u64,
+}
+
+impl VoterWeightStatement {
+    /// Builds the statement from the voter account's data, as fetched from
+    /// an RPC node at `slot`, together with the voter's registrar.
+    pub fn from_voter_account_data(
+        voter_address: Pubkey,
+        voter_account_data: &[u8],
+        registrar: &Registrar,
+        slot: u64,
+        unix_timestamp: i64,
+    ) -> Result<Self> {
+        let mut data_slice: &[u8] = voter_account_data;
+        let voter = Voter::try_deserialize(&mut data_slice)?;
+        require!(
+            voter.registrar == find_registrar_address(&registrar.realm).0,
+            VoterRegistrarMismatch
+        );
+        Ok(Self {
+            program_id: crate::ID,
+            registrar: voter.registrar,
+            voter: voter_address,
+            voter_authority: voter.authority,
+            slot,
+            unix_timestamp,
+            voter_account_hash: hashv(&[voter_account_data]).to_bytes(),
+            voter_weight: voter.weight(registrar, unix_timestamp)?,
+        })
+    }
+
+    /// Checks that the statement matches the given voter account data.
+    pub fn matches_voter_account_data(
+        &self,
+        voter_account_data: &[u8],
+        registrar: &Registrar,
+    ) -> bool {
+        Self::from_voter_account_data(
+            self.voter,
+            voter_account_data,
+            registrar,
+            self.slot,
+            self.unix_timestamp,
+        )
+        .map(|statement| statement == *self)
+        .unwrap_or(false)
+    }
+
+    /// The bytes that get signed.
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = MESSAGE_DOMAIN.to_vec();
+        message.extend(self.try_to_vec().unwrap());
+        message
+    }
+}
+
+/// A statement signed by an issuer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoterWeightCertificate {
+    pub statement: VoterWeightStatement,
+    pub issuer: Pubkey,
+    pub signature: Signature,
+}
+
+impl VoterWeightCertificate {
+    pub fn sign(statement: VoterWeightStatement, issuer: &Keypair) -> Self {
+        let signature = issuer.sign_message(&statement.message());
+        Self {
+            statement,
+            issuer: issuer.pubkey(),
+            signature,
+        }
+    }
+
+    /// Checks the issuer's signature. Whether the issuer is trusted is up to
+    /// the caller.
+    pub fn verify_signature(&self) -> bool {
+        self.signature
+            .verify(self.issuer.as_ref(), &self.statement.message())
+    }
+}
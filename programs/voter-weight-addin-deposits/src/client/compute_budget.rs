@@ -0,0 +1,85 @@
+use anchor_lang::solana_program::instruction::Instruction;
+
+mod compute_budget_program {
+    anchor_lang::declare_id!("ComputeBudget111111111111111111111111111111");
+}
+
+pub use compute_budget_program::ID as COMPUTE_BUDGET_PROGRAM_ID;
+
+/// Compute units spl-governance's `CastVote` is assumed to need when bundled
+/// with `update_voter_weight_record`.
+pub const CAST_VOTE_COMPUTE_UNITS: u32 = 80_000;
+
+/// The instructions of this program, for picking compute unit limits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddinInstruction {
+    CreateRegistrar,
+    CreateVaultShard,
+    CreateVoter,
+    Deposit,
+    Withdraw,
+    UpdateVoterWeightRecord,
+    CloseVoter,
+}
+
+impl AddinInstruction {
+    /// Compute unit limit to request for the instruction.
+    ///
+    /// These are conservative estimates with headroom above what the program
+    /// tests consume. Re-measure when changing an instruction;
+    /// `tests/test_compute.rs` fails with test-bpf when an instruction
+    /// exceeds its budget there.
+    pub fn default_compute_units(self) -> u32 {
+        match self {
+            AddinInstruction::CreateRegistrar => 60_000,
+            AddinInstruction::CreateVaultShard => 40_000,
+            AddinInstruction::CreateVoter => 50_000,
+            AddinInstruction::Deposit => 30_000,
+            AddinInstruction::Withdraw => 50_000,
+            AddinInstruction::UpdateVoterWeightRecord => 15_000,
+            AddinInstruction::CloseVoter => 10_000,
+        }
+    }
+}
+
+/// Instruction setting the transaction's compute unit limit.
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Instruction setting the transaction's priority fee, in micro-lamports
+/// per compute unit.
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Prepends compute budget instructions to `instructions`.
+///
+/// `units` is usually the sum of `default_compute_units()` of the addin
+/// instructions in the transaction, plus e.g. `CAST_VOTE_COMPUTE_UNITS`.
+/// A `micro_lamports_per_unit` of zero adds no priority fee instruction.
+pub fn with_compute_budget(
+    instructions: Vec<Instruction>,
+    units: u32,
+    micro_lamports_per_unit: u64,
+) -> Vec<Instruction> {
+    let mut result = Vec::with_capacity(instructions.len() + 2);
+    result.push(set_compute_unit_limit(units));
+    if micro_lamports_per_unit > 0 {
+        result.push(set_compute_unit_price(micro_lamports_per_unit));
+    }
+    result.extend(instructions);
+    result
+}
@@ -0,0 +1,25 @@
+//! Translation of this program's error codes for user interfaces.
+//!
+//! The codes of this program start at 300 and stay below 500, where the
+//! codes of spl-governance's `GovernanceError` begin. A UI can therefore
+//! translate a custom program error from either program by its value.
+
+use crate::error::ErrorCode;
+
+/// Human readable message for an error code returned by this program.
+pub fn error_message(code: u32) -> Option<String> {
+    ErrorCode::from_code(code).map(|e| e.to_string())
+}
+
+/// Name of the error variant for an error code, e.g. "ForbiddenCpi".
+pub fn error_name(code: u32) -> Option<String> {
+    ErrorCode::from_code(code).map(|e| format!("{:?}", e))
+}
+
+/// All errors as (code, name, message), for generating translation tables.
+pub fn error_table() -> Vec<(u32, String, String)> {
+    ErrorCode::ALL
+        .iter()
+        .map(|e| (u32::from(*e), format!("{:?}", e), e.to_string()))
+        .collect()
+}
@@ -0,0 +1,736 @@
+//! Builders for the instructions of this program.
+//!
+//! Each builder derives the program addresses it needs and returns a
+//! ready-to-sign `Instruction`. Signers are the accounts named `authority`
+//! and `payer`, and `deposit_authority` for deposits.
+//!
+//! The address derivations are in `crate::seeds`, re-exported here.
+
+use crate::account::{DelegationShareKind, Registrar, DEPOSIT_LABEL_LEN};
+use crate::args::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+pub use crate::seeds::*;
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Address of the spl-governance token owner record of `wallet` in the
+/// realm of `registrar`, as needed by `withdraw_ix`.
+pub fn find_token_owner_record_address(registrar: &Registrar, wallet: &Pubkey) -> Pubkey {
+    spl_governance::state::token_owner_record::get_token_owner_record_address(
+        &registrar.governance_program_id,
+        &registrar.realm,
+        &registrar.realm_community_mint,
+        wallet,
+    )
+}
+
+/// Creates the registrar of `realm`, with `authority` as its authority.
+pub fn create_registrar_ix(
+    governance_program_id: &Pubkey,
+    realm: &Pubkey,
+    realm_community_mint: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (registrar, registrar_bump) = find_registrar_address(realm);
+    let (vault, _) = find_vault_address(&registrar, realm_community_mint, 0);
+    instruction(
+        crate::accounts::CreateRegistrar {
+            registrar,
+            governance_program_id: *governance_program_id,
+            realm: *realm,
+            realm_community_mint: *realm_community_mint,
+            authority: *authority,
+            vault,
+            payer: *payer,
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        },
+        crate::instruction::CreateRegistrar { registrar_bump },
+    )
+}
+
+/// Adds `mint` as voting mint at `idx` of the registrar, or changes its
+/// rate.
+pub fn configure_voting_mint_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    idx: u8,
+    rate: u64,
+) -> Instruction {
+    instruction(
+        crate::accounts::ConfigureVotingMint {
+            registrar: *registrar,
+            authority: *authority,
+            mint: *mint,
+        },
+        crate::instruction::ConfigureVotingMint { idx, rate },
+    )
+}
+
+/// Sets the registrar's lockup saturation and maximum extra lockup weight.
+pub fn configure_lockup_params_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    lockup_saturation_secs: u64,
+    max_extra_lockup_vote_weight_factor: u64,
+) -> Instruction {
+    instruction(
+        crate::accounts::ConfigureLockupParams {
+            registrar: *registrar,
+            authority: *authority,
+        },
+        crate::instruction::ConfigureLockupParams {
+            lockup_saturation_secs,
+            max_extra_lockup_vote_weight_factor,
+        },
+    )
+}
+
+/// Grants locked `deposit_mint` tokens from `deposit_token` to the voter of
+/// `voter_authority`. `vault_shard` is the voter's `Voter::vault_shard`.
+#[allow(clippy::too_many_arguments)]
+pub fn grant_ix(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    vault_shard: u8,
+    deposit_mint: &Pubkey,
+    deposit_token: &Pubkey,
+    deposit_authority: &Pubkey,
+    grant_authority: &Pubkey,
+    args: GrantArgs,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, voter_authority);
+    let (vault, _) = find_vault_address(registrar, deposit_mint, vault_shard);
+    instruction(
+        crate::accounts::Grant {
+            registrar: *registrar,
+            voter,
+            vault,
+            deposit_mint: *deposit_mint,
+            deposit_token: *deposit_token,
+            deposit_authority: *deposit_authority,
+            grant_authority: *grant_authority,
+            token_program: anchor_spl::token::ID,
+        },
+        crate::instruction::Grant { args },
+    )
+}
+
+/// Creates the voter and `VoterWeightRecord` of `authority`.
+pub fn create_voter_ix(registrar: &Pubkey, authority: &Pubkey, payer: &Pubkey) -> Instruction {
+    let (voter, voter_bump) = find_voter_address(registrar, authority);
+    let (voter_weight_record, voter_weight_record_bump) =
+        find_voter_weight_record_address(registrar, authority);
+    instruction(
+        crate::accounts::CreateVoter {
+            voter,
+            voter_weight_record,
+            registrar: *registrar,
+            authority: *authority,
+            payer: *payer,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            instructions: sysvar::instructions::ID,
+        },
+        crate::instruction::CreateVoter {
+            voter_bump,
+            voter_weight_record_bump,
+        },
+    )
+}
+
+/// Creates the voter and `VoterWeightRecord` of `authority`, with the rent
+/// paid by the registrar's rent sponsor.
+pub fn create_voter_sponsored_ix(registrar: &Pubkey, authority: &Pubkey) -> Instruction {
+    let (voter, voter_bump) = find_voter_address(registrar, authority);
+    let (voter_weight_record, voter_weight_record_bump) =
+        find_voter_weight_record_address(registrar, authority);
+    let (rent_sponsor, _) = find_rent_sponsor_address(registrar);
+    instruction(
+        crate::accounts::CreateVoterSponsored {
+            voter,
+            voter_weight_record,
+            registrar: *registrar,
+            rent_sponsor,
+            authority: *authority,
+            system_program: system_program::ID,
+            instructions: sysvar::instructions::ID,
+        },
+        crate::instruction::CreateVoterSponsored {
+            voter_bump,
+            voter_weight_record_bump,
+        },
+    )
+}
+
+/// Moves `lamports` from `payer` into the rent sponsor of `registrar`.
+pub fn fund_rent_sponsor_ix(registrar: &Pubkey, payer: &Pubkey, lamports: u64) -> Instruction {
+    let (rent_sponsor, _) = find_rent_sponsor_address(registrar);
+    instruction(
+        crate::accounts::FundRentSponsor {
+            registrar: *registrar,
+            rent_sponsor,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        crate::instruction::FundRentSponsor { lamports },
+    )
+}
+
+/// Approves withdrawing `amount` for the voter of `voter_authority`, see
+/// `approve_withdrawal`. `authority` is the registrar authority, usually a
+/// governance executing this in a proposal.
+pub fn approve_withdrawal_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    voter_authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, voter_authority);
+    let (withdrawal_approval, _) = find_withdrawal_approval_address(registrar, &voter);
+    let (rent_sponsor, _) = find_rent_sponsor_address(registrar);
+    instruction(
+        crate::accounts::ApproveWithdrawal {
+            registrar: *registrar,
+            authority: *authority,
+            voter,
+            withdrawal_approval,
+            rent_sponsor,
+            system_program: system_program::ID,
+        },
+        crate::instruction::ApproveWithdrawal { amount },
+    )
+}
+
+/// Creates a deposit entry with a lockup in the voter of `authority`.
+pub fn create_deposit_entry_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    args: CreateDepositEntryArgs,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    instruction(
+        crate::accounts::CreateDepositEntry {
+            registrar: *registrar,
+            voter,
+            authority: *authority,
+        },
+        crate::instruction::CreateDepositEntry { args },
+    )
+}
+
+/// Deposits `deposit_mint` tokens from `deposit_token` into the voter of
+/// `voter_authority`. `vault_shard` is the voter's `Voter::vault_shard`.
+pub fn deposit_ix(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    vault_shard: u8,
+    deposit_mint: &Pubkey,
+    deposit_token: &Pubkey,
+    deposit_authority: &Pubkey,
+    args: DepositArgs,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, voter_authority);
+    let (vault, _) = find_vault_address(registrar, deposit_mint, vault_shard);
+    instruction(
+        crate::accounts::Deposit {
+            registrar: *registrar,
+            voter,
+            vault,
+            deposit_mint: *deposit_mint,
+            deposit_token: *deposit_token,
+            deposit_authority: *deposit_authority,
+            token_program: anchor_spl::token::ID,
+        },
+        crate::instruction::Deposit { args },
+    )
+}
+
+/// Withdraws `withdraw_mint` tokens from the voter of `authority` into
+/// `destination`. `vault_shard` is the voter's `Voter::vault_shard`, see
+/// `find_token_owner_record_address` for `token_owner_record`.
+pub fn withdraw_ix(
+    registrar: &Pubkey,
+    token_owner_record: &Pubkey,
+    authority: &Pubkey,
+    vault_shard: u8,
+    withdraw_mint: &Pubkey,
+    destination: &Pubkey,
+    args: WithdrawArgs,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (vault, _) = find_vault_address(registrar, withdraw_mint, vault_shard);
+    let (voter_weight_record, _) = find_voter_weight_record_address(registrar, authority);
+    let (withdrawal_approval, _) = find_withdrawal_approval_address(registrar, &voter);
+    instruction(
+        crate::accounts::Withdraw {
+            registrar: *registrar,
+            voter,
+            token_owner_record: *token_owner_record,
+            vault,
+            withdraw_mint: *withdraw_mint,
+            destination: *destination,
+            voter_weight_record,
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+            instructions: sysvar::instructions::ID,
+            withdrawal_approval,
+        },
+        crate::instruction::Withdraw { args },
+    )
+}
+
+/// Additional accounts for `withdraw_ix`, `transfer_position_ix` and the
+/// other instructions moving tokens out of a delegated deposit entry of
+/// `voter`: its `VoterDelegations`, then each delegate voter of the entry
+/// with the delegate's token owner record.
+pub fn delegate_accounts(voter: &Pubkey, delegates: &[(Pubkey, Pubkey)]) -> Vec<AccountMeta> {
+    let (delegations, _) = find_voter_delegations_address(voter);
+    let mut accounts = vec![AccountMeta::new_readonly(delegations, false)];
+    for (delegate_voter, token_owner_record) in delegates {
+        accounts.push(AccountMeta::new_readonly(*delegate_voter, false));
+        accounts.push(AccountMeta::new_readonly(*token_owner_record, false));
+    }
+    accounts
+}
+
+/// Sweeps the `withdraw_mint` dust of the voter of `authority` into
+/// `destination`, see `sweep_dust`.
+pub fn sweep_dust_ix(
+    registrar: &Pubkey,
+    token_owner_record: &Pubkey,
+    authority: &Pubkey,
+    vault_shard: u8,
+    withdraw_mint: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (vault, _) = find_vault_address(registrar, withdraw_mint, vault_shard);
+    let (voter_weight_record, _) = find_voter_weight_record_address(registrar, authority);
+    let (withdrawal_approval, _) = find_withdrawal_approval_address(registrar, &voter);
+    instruction(
+        crate::accounts::Withdraw {
+            registrar: *registrar,
+            voter,
+            token_owner_record: *token_owner_record,
+            vault,
+            withdraw_mint: *withdraw_mint,
+            destination: *destination,
+            voter_weight_record,
+            authority: *authority,
+            token_program: anchor_spl::token::ID,
+            instructions: sysvar::instructions::ID,
+            withdrawal_approval,
+        },
+        crate::instruction::SweepDust {},
+    )
+}
+
+/// Writes the current weight of the voter of `voter_authority` to its
+/// `VoterWeightRecord`. Registrars with oracles, an NFT boost or an upstream
+/// addin need extra accounts appended, see `update_voter_weight_record`.
+pub fn update_voter_weight_record_ix(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    args: UpdateVoterWeightRecordArgs,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, voter_authority);
+    let (voter_weight_record, _) = find_voter_weight_record_address(registrar, voter_authority);
+    instruction(
+        crate::accounts::UpdateVoterWeightRecord {
+            registrar: *registrar,
+            voter,
+            voter_weight_record,
+            system_program: system_program::ID,
+        },
+        crate::instruction::UpdateVoterWeightRecord { args },
+    )
+}
+
+/// Like `update_voter_weight_record_ix`, using the registrar settings cached
+/// in the voter.
+pub fn update_voter_weight_record_cached_ix(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    args: UpdateVoterWeightRecordArgs,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, voter_authority);
+    let (voter_weight_record, _) = find_voter_weight_record_address(registrar, voter_authority);
+    instruction(
+        crate::accounts::UpdateVoterWeightRecordCached {
+            registrar: *registrar,
+            voter,
+            voter_weight_record,
+        },
+        crate::instruction::UpdateVoterWeightRecordCached { args },
+    )
+}
+
+/// Copies the registrar's weight settings into the voter of
+/// `voter_authority`.
+pub fn refresh_weight_config_ix(registrar: &Pubkey, voter_authority: &Pubkey) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, voter_authority);
+    instruction(
+        crate::accounts::RefreshWeightConfig {
+            registrar: *registrar,
+            voter,
+        },
+        crate::instruction::RefreshWeightConfig {},
+    )
+}
+
+/// Creates the `DepositLabels` of the voter of `authority`.
+pub fn create_deposit_labels_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (deposit_labels, _) = find_deposit_labels_address(&voter);
+    instruction(
+        crate::accounts::CreateDepositLabels {
+            voter,
+            authority: *authority,
+            deposit_labels,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        crate::instruction::CreateDepositLabels {},
+    )
+}
+
+/// Labels a deposit entry of the voter of `authority`. Fails for labels
+/// longer than `DEPOSIT_LABEL_LEN` bytes.
+pub fn set_deposit_label_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    deposit_entry_index: u8,
+    label: &str,
+) -> Option<Instruction> {
+    let bytes = label.as_bytes();
+    if bytes.len() > DEPOSIT_LABEL_LEN {
+        return None;
+    }
+    let mut padded = [0u8; DEPOSIT_LABEL_LEN];
+    padded[..bytes.len()].copy_from_slice(bytes);
+
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (deposit_labels, _) = find_deposit_labels_address(&voter);
+    Some(instruction(
+        crate::accounts::SetDepositLabel {
+            voter,
+            authority: *authority,
+            deposit_labels,
+        },
+        crate::instruction::SetDepositLabel {
+            deposit_entry_index,
+            label: padded,
+        },
+    ))
+}
+
+/// Tokenizes a locked deposit entry of the voter of `authority`, minting
+/// the NFT into `holder`. The `position_mint` and `holder` must already
+/// exist, see `create_position`.
+pub fn create_position_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    position_mint: &Pubkey,
+    holder: &Pubkey,
+    payer: &Pubkey,
+    deposit_entry_index: u8,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (position, _) = find_position_address(registrar, position_mint);
+    instruction(
+        crate::accounts::CreatePosition {
+            registrar: *registrar,
+            voter,
+            authority: *authority,
+            position,
+            position_mint: *position_mint,
+            holder: *holder,
+            payer: *payer,
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+        },
+        crate::instruction::CreatePosition {
+            deposit_entry_index,
+        },
+    )
+}
+
+/// Moves the position of `position_mint` from the voter of `authority` to
+/// the voter of `target_authority`, whose `target_holder` receives the NFT.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_position_ix(
+    registrar: &Pubkey,
+    token_owner_record: &Pubkey,
+    authority: &Pubkey,
+    position_mint: &Pubkey,
+    holder: &Pubkey,
+    target_authority: &Pubkey,
+    target_holder: &Pubkey,
+    target_deposit_entry_index: u8,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (target_voter, _) = find_voter_address(registrar, target_authority);
+    let (position, _) = find_position_address(registrar, position_mint);
+    instruction(
+        crate::accounts::TransferPosition {
+            registrar: *registrar,
+            position,
+            voter,
+            authority: *authority,
+            token_owner_record: *token_owner_record,
+            holder: *holder,
+            target_voter,
+            target_holder: *target_holder,
+            position_mint: *position_mint,
+            token_program: anchor_spl::token::ID,
+        },
+        crate::instruction::TransferPosition {
+            target_deposit_entry_index,
+        },
+    )
+}
+
+/// Burns the position NFT in `holder` of the voter of `authority`. The
+/// `Position` rent goes to `sol_destination`.
+pub fn close_position_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    position_mint: &Pubkey,
+    holder: &Pubkey,
+    sol_destination: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (position, _) = find_position_address(registrar, position_mint);
+    instruction(
+        crate::accounts::ClosePosition {
+            registrar: *registrar,
+            position,
+            voter,
+            authority: *authority,
+            holder: *holder,
+            position_mint: *position_mint,
+            sol_destination: *sol_destination,
+            token_program: anchor_spl::token::ID,
+        },
+        crate::instruction::ClosePosition {},
+    )
+}
+
+/// Creates the `VoterDelegations` of the voter of `authority`.
+pub fn create_voter_delegations_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (delegations, _) = find_voter_delegations_address(&voter);
+    instruction(
+        crate::accounts::CreateVoterDelegations {
+            voter,
+            authority: *authority,
+            delegations,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        crate::instruction::CreateVoterDelegations {},
+    )
+}
+
+/// Creates the `DelegationRecord` of the voter of `authority` delegating to
+/// the voter of `delegate_authority`.
+pub fn create_delegation_record_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    delegate_authority: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (delegate_voter, _) = find_voter_address(registrar, delegate_authority);
+    let (delegation_record, _) = find_delegation_record_address(&voter, &delegate_voter);
+    instruction(
+        crate::accounts::CreateDelegationRecord {
+            registrar: *registrar,
+            voter,
+            authority: *authority,
+            delegate_voter,
+            delegation_record,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        crate::instruction::CreateDelegationRecord {},
+    )
+}
+
+/// Delegates a share of a deposit entry of the voter of `authority` to the
+/// voter of `delegate_authority`.
+pub fn delegate_ix(
+    registrar: &Pubkey,
+    token_owner_record: &Pubkey,
+    authority: &Pubkey,
+    delegate_authority: &Pubkey,
+    deposit_entry_index: u8,
+    share_kind: DelegationShareKind,
+    share: u64,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (delegations, _) = find_voter_delegations_address(&voter);
+    let (delegate_voter, _) = find_voter_address(registrar, delegate_authority);
+    let (delegation_record, _) = find_delegation_record_address(&voter, &delegate_voter);
+    instruction(
+        crate::accounts::Delegate {
+            registrar: *registrar,
+            voter,
+            authority: *authority,
+            token_owner_record: *token_owner_record,
+            delegations,
+            delegate_voter,
+            delegation_record,
+        },
+        crate::instruction::Delegate {
+            deposit_entry_index,
+            share_kind,
+            share,
+        },
+    )
+}
+
+/// Starts the undelegate cooldown of the delegations of the voter of
+/// `authority` to `delegate_voter`.
+pub fn request_undelegate_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    delegate_voter: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (delegations, _) = find_voter_delegations_address(&voter);
+    instruction(
+        crate::accounts::RequestUndelegate {
+            registrar: *registrar,
+            voter,
+            authority: *authority,
+            delegations,
+            delegate_voter: *delegate_voter,
+        },
+        crate::instruction::RequestUndelegate {},
+    )
+}
+
+/// Ends the delegations of the voter of `authority` to `delegate_voter`,
+/// returning the rent of the `DelegationRecord` to `sol_destination`. See
+/// `find_token_owner_record_address` for `delegate_token_owner_record`.
+pub fn undelegate_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    delegate_voter: &Pubkey,
+    delegate_token_owner_record: &Pubkey,
+    sol_destination: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (delegations, _) = find_voter_delegations_address(&voter);
+    let (delegation_record, _) = find_delegation_record_address(&voter, delegate_voter);
+    instruction(
+        crate::accounts::Undelegate {
+            registrar: *registrar,
+            voter,
+            authority: *authority,
+            delegations,
+            delegate_voter: *delegate_voter,
+            delegate_token_owner_record: *delegate_token_owner_record,
+            delegation_record,
+            sol_destination: *sol_destination,
+        },
+        crate::instruction::Undelegate {},
+    )
+}
+
+/// Closes an empty deposit entry of the voter of `authority`.
+pub fn close_deposit_entry_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    deposit_entry_index: u8,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    instruction(
+        crate::accounts::CloseDepositEntry {
+            voter,
+            authority: *authority,
+        },
+        crate::instruction::CloseDepositEntry {
+            deposit_entry_index,
+        },
+    )
+}
+
+/// Snapshots the deposits of `registrar` for `epoch`, which must be the
+/// current epoch.
+pub fn snapshot_epoch_ix(registrar: &Pubkey, epoch: u64, payer: &Pubkey) -> Instruction {
+    let (epoch_snapshot, _) = find_epoch_snapshot_address(registrar, epoch);
+    instruction(
+        crate::accounts::SnapshotEpoch {
+            registrar: *registrar,
+            epoch_snapshot,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        crate::instruction::SnapshotEpoch { epoch },
+    )
+}
+
+/// Closes the voter of `authority`, sending its rent to `sol_destination`.
+/// The rent of its voter weight record goes to
+/// `voter_weight_record_rent_destination`, which must be
+/// `Voter::voter_weight_record_rent_destination`.
+pub fn close_voter_ix(
+    registrar: &Pubkey,
+    authority: &Pubkey,
+    sol_destination: &Pubkey,
+    voter_weight_record_rent_destination: &Pubkey,
+) -> Instruction {
+    let (voter, _) = find_voter_address(registrar, authority);
+    let (voter_weight_record, _) = find_voter_weight_record_address(registrar, authority);
+    instruction(
+        crate::accounts::CloseVoter {
+            registrar: *registrar,
+            voter,
+            voter_weight_record,
+            authority: *authority,
+            sol_destination: *sol_destination,
+            voter_weight_record_rent_destination: *voter_weight_record_rent_destination,
+        },
+        crate::instruction::CloseVoter {},
+    )
+}
+
+/// Upgrades the registrar or voter at `account` to the current layout.
+pub fn migrate_account_ix(account: &Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::MigrateAccount { account: *account },
+        crate::instruction::MigrateAccount {},
+    )
+}
+
+/// Returns the program's `ProgramVersion` as return data, when simulated.
+pub fn get_version_ix() -> Instruction {
+    instruction(
+        crate::accounts::GetVersion {},
+        crate::instruction::GetVersion {},
+    )
+}
@@ -0,0 +1,8 @@
+//! Helpers for off-chain clients of this program.
+//!
+//! Only compiled with the `client` feature.
+
+pub mod certificate;
+pub mod compute_budget;
+pub mod errors;
+pub mod instructions;
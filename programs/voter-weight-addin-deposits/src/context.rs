@@ -1,11 +1,13 @@
 use crate::account::*;
+use crate::args::ClaimFromMerkleArgs;
+use crate::error::*;
+use crate::seeds::*;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::system_instruction;
 use anchor_lang::solana_program::sysvar::instructions as tx_instructions;
-use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
-use std::mem::size_of;
-
-pub const VOTER_WEIGHT_RECORD: [u8; 19] = *b"voter-weight-record";
 
 #[derive(Accounts)]
 #[instruction(registrar_bump: u8)]
@@ -15,7 +17,7 @@ pub struct CreateRegistrar<'info> {
         seeds = [realm.key().as_ref()],
         bump = registrar_bump,
         payer = payer,
-        space = 8 + size_of::<Registrar>()
+        space = Registrar::LEN
     )]
     pub registrar: AccountLoader<'info, Registrar>,
     pub governance_program_id: AccountInfo<'info>,
@@ -25,11 +27,15 @@ pub struct CreateRegistrar<'info> {
     pub realm_community_mint: Account<'info, Mint>,
     pub authority: UncheckedAccount<'info>,
 
+    // The vault is a token account at a PDA of this program, so it can only
+    // ever be created through this program. This is vault shard 0.
     #[account(
         init,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), realm_community_mint.key().as_ref(), &[0]],
+        bump,
         payer = payer,
-        associated_token::authority = registrar,
-        associated_token::mint = realm_community_mint,
+        token::authority = registrar,
+        token::mint = realm_community_mint,
     )]
     pub vault: Account<'info, TokenAccount>,
 
@@ -38,148 +44,1681 @@ pub struct CreateRegistrar<'info> {
 
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-#[instruction(voter_bump: u8, voter_weight_record_bump: u8)]
-pub struct CreateVoter<'info> {
+pub struct VerifyRegistrar<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub realm: UncheckedAccount<'info>,
+    pub realm_config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(idx: u8, rate: u64)]
+pub struct ConfigureVotingMint<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+}
+
+#[derive(Accounts)]
+#[instruction(shard: u8)]
+pub struct CreateVaultShard<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub mint: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+
     #[account(
         init,
-        seeds = [registrar.key().as_ref(), authority.key().as_ref()],
-        bump = voter_bump,
-        payer = authority,
-        space = 8 + size_of::<Voter>(),
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), mint.key().as_ref(), &[shard]],
+        bump,
+        payer = payer,
+        token::authority = registrar,
+        token::mint = mint,
     )]
-    pub voter: AccountLoader<'info, Voter>,
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureAllowlistRequired<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
 
     #[account(
         init,
-        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
-        bump = voter_weight_record_bump,
+        seeds = [registrar.key().as_ref(), ALLOWLIST.as_ref(), wallet.as_ref()],
+        bump,
         payer = payer,
-        space = 150,
+        space = AllowlistEntry::LEN,
     )]
-    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(has_one = authority)]
     pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
 
-    // TODO: Why is authority and payer different? Is it necessary that Voter and VoterWeightRecord are paid for differently?
-    #[account(mut)]
+    #[account(mut, has_one = registrar, close = sol_destination)]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    pub sol_destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToBlocklist<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
     pub authority: Signer<'info>,
 
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), BLOCKLIST.as_ref(), wallet.as_ref()],
+        bump,
+        payer = payer,
+        space = BlocklistEntry::LEN,
+    )]
+    pub blocklist_entry: Account<'info, BlocklistEntry>,
+
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+}
 
-    #[account(address = tx_instructions::ID)]
-    pub instructions: UncheckedAccount<'info>,
+#[derive(Accounts)]
+pub struct RemoveFromBlocklist<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = registrar, close = sol_destination)]
+    pub blocklist_entry: Account<'info, BlocklistEntry>,
+
+    pub sol_destination: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct Deposit<'info> {
+pub struct ConfigureGatekeeper<'info> {
+    #[account(mut, has_one = authority)]
     pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
 
-    #[account(mut, has_one = authority, has_one = registrar)]
+#[derive(Accounts)]
+pub struct ConfigureNftBoost<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureEarlyUnlock<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureUpstreamAddin<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureWithdrawalCooldown<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureWithdrawalApproval<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePositionTransfers<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureUndelegateCooldown<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWithdrawal<'info> {
+    #[account(has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+
+    #[account(has_one = registrar)]
     pub voter: AccountLoader<'info, Voter>,
 
+    /// Created by the first approval for the voter.
     #[account(
         mut,
-        associated_token::authority = registrar,
-        associated_token::mint = deposit_mint,
+        seeds = [registrar.key().as_ref(), WITHDRAWAL_APPROVAL.as_ref(), voter.key().as_ref()],
+        bump,
     )]
-    pub vault: Account<'info, TokenAccount>,
-    pub deposit_mint: Account<'info, Mint>,
+    pub withdrawal_approval: UncheckedAccount<'info>,
 
+    /// Pays the rent of a new approval, since a governance executing the
+    /// approval has no SOL to pay with.
     #[account(
         mut,
-        constraint = deposit_token.mint == deposit_mint.key(),
+        seeds = [registrar.key().as_ref(), RENT_SPONSOR.as_ref()],
+        bump,
     )]
-    pub deposit_token: Account<'info, TokenAccount>,
+    pub rent_sponsor: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct ConfigureDepositsPaused<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
     pub authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
 }
 
-impl<'info> Deposit<'info> {
-    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
-        let program = self.token_program.to_account_info();
-        let accounts = token::Transfer {
-            from: self.deposit_token.to_account_info(),
-            to: self.vault.to_account_info(),
-            authority: self.authority.to_account_info(),
-        };
-        CpiContext::new(program, accounts)
-    }
+#[derive(Accounts)]
+pub struct ConfigureWeightAction<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct Withdraw<'info> {
+pub struct ConfigureWeightTargetCap<'info> {
+    #[account(mut, has_one = authority)]
     pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
 
-    #[account(mut, has_one = registrar, has_one = authority)]
-    pub voter: AccountLoader<'info, Voter>,
+#[derive(Accounts)]
+pub struct ConfigureVoteEscrowWeight<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
 
-    pub token_owner_record: AccountInfo<'info>,
+#[derive(Accounts)]
+pub struct ConfigureQuadraticWeight<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
 
-    #[account(
-        mut,
-        associated_token::authority = registrar,
-        associated_token::mint = withdraw_mint,
-    )]
-    pub vault: Account<'info, TokenAccount>,
-    pub withdraw_mint: Account<'info, Mint>,
+#[derive(Accounts)]
+pub struct ConfigureWeightScale<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
 
-    #[account(mut)]
-    pub destination: Account<'info, TokenAccount>,
+#[derive(Accounts)]
+pub struct ConfigureProposalDeposit<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
 
+#[derive(Accounts)]
+pub struct ConfigureProposalLocking<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
     pub authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
 }
 
-impl<'info> Withdraw<'info> {
-    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
-        let program = self.token_program.to_account_info();
-        let accounts = token::Transfer {
-            from: self.vault.to_account_info(),
-            to: self.destination.to_account_info(),
-            authority: self.registrar.to_account_info(),
-        };
-        CpiContext::new(program, accounts)
-    }
+#[derive(Accounts)]
+pub struct ConfigureMaxVoterWeightPerVoter<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateVoterWeightRecord<'info> {
+pub struct ConfigureMaxVoterDeposit<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureMaxTotalDeposit<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureDigitShift<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureOracle<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureMinAmounts<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTwabWindow<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureLockupParams<'info> {
+    #[account(mut, has_one = authority)]
     pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
 
+#[derive(Accounts)]
+#[instruction(voter_bump: u8, voter_weight_record_bump: u8)]
+pub struct CreateVoter<'info> {
     #[account(
-        has_one = registrar,
-        has_one = authority,
+        init,
+        seeds = [registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter_bump,
+        payer = authority,
+        space = Voter::LEN,
     )]
     pub voter: AccountLoader<'info, Voter>,
 
     #[account(
-        mut,
+        init,
         seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
-        bump = voter.load()?.voter_weight_record_bump,
-        constraint = voter_weight_record.realm == registrar.load()?.realm,
-        constraint = voter_weight_record.governing_token_owner == voter.load()?.authority,
+        bump = voter_weight_record_bump,
+        payer = payer,
+        space = VoterWeightRecord::LEN,
     )]
     pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    // TODO: Why is authority and payer different? Is it necessary that Voter and VoterWeightRecord are paid for differently?
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(address = tx_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CloseVoter<'info> {
-    #[account(mut, has_one = authority, close = sol_destination)]
-    pub voter: AccountLoader<'info, Voter>,
-    pub authority: Signer<'info>,
-    pub sol_destination: UncheckedAccount<'info>,
+#[instruction(voter_bump: u8, voter_weight_record_bump: u8)]
+pub struct CreateVoterSponsored<'info> {
+    /// Created by the instruction, see `CreateVoter::voter`.
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter_bump,
+    )]
+    pub voter: UncheckedAccount<'info>,
+
+    /// Created by the instruction, see `CreateVoter::voter_weight_record`.
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter_weight_record_bump,
+    )]
+    pub voter_weight_record: UncheckedAccount<'info>,
+
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), RENT_SPONSOR.as_ref()],
+        bump,
+    )]
+    pub rent_sponsor: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(address = tx_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundRentSponsor<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), RENT_SPONSOR.as_ref()],
+        bump,
+    )]
+    pub rent_sponsor: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRentSponsor<'info> {
+    #[account(has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), RENT_SPONSOR.as_ref()],
+        bump,
+    )]
+    pub rent_sponsor: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDepositEntry<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Grant<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), deposit_mint.key().as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub deposit_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = deposit_token.mint == deposit_mint.key(),
+    )]
+    pub deposit_token: Account<'info, TokenAccount>,
+    pub deposit_authority: Signer<'info>,
+
+    pub grant_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Grant<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.deposit_token.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.deposit_authority.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32])]
+pub struct CreateMerkleDistributor<'info> {
+    #[account(has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), MERKLE_DISTRIBUTOR.as_ref(), root.as_ref()],
+        bump,
+        payer = payer,
+        space = MerkleDistributor::LEN,
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    #[account(
+        init,
+        seeds = [distributor.key().as_ref(), DISTRIBUTOR_VAULT.as_ref()],
+        bump,
+        payer = payer,
+        token::authority = registrar,
+        token::mint = mint,
+    )]
+    pub distributor_vault: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ClaimFromMerkleArgs)]
+pub struct ClaimFromMerkle<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub distributor: Account<'info, MerkleDistributor>,
+    #[account(
+        mut,
+        seeds = [distributor.key().as_ref(), DISTRIBUTOR_VAULT.as_ref()],
+        bump,
+    )]
+    pub distributor_vault: Account<'info, TokenAccount>,
+
+    /// Created by the claim, so each index can only be claimed once.
+    #[account(
+        init,
+        seeds = [distributor.key().as_ref(), CLAIM_STATUS.as_ref(), args.index().to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = ClaimStatus::LEN,
+    )]
+    pub claim_status: Account<'info, ClaimStatus>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), distributor.mint.as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimFromMerkle<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.distributor_vault.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateRewardVault<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), REWARD_VAULT.as_ref()],
+        bump,
+        payer = payer,
+        token::authority = registrar,
+        token::mint = reward_mint,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardRate<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), REWARD_VAULT.as_ref()],
+        bump,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimRewards<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.reward_vault.to_account_info(),
+            to: self.destination.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateCheckpoints<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [voter.key().as_ref(), CHECKPOINTS.as_ref()],
+        bump,
+        payer = payer,
+        space = VoterCheckpoints::LEN,
+    )]
+    pub checkpoints: AccountLoader<'info, VoterCheckpoints>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDepositLabels<'info> {
+    #[account(has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [voter.key().as_ref(), DEPOSIT_LABELS.as_ref()],
+        bump,
+        payer = payer,
+        space = DepositLabels::LEN,
+    )]
+    pub deposit_labels: AccountLoader<'info, DepositLabels>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDepositLabel<'info> {
+    #[account(has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = voter)]
+    pub deposit_labels: AccountLoader<'info, DepositLabels>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePosition<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), POSITION.as_ref(), position_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = Position::LEN,
+    )]
+    pub position: Account<'info, Position>,
+
+    /// A new mint, see `create_position`.
+    #[account(mut)]
+    pub position_mint: Account<'info, Mint>,
+
+    /// Receives the NFT.
+    #[account(
+        mut,
+        constraint = holder.mint == position_mint.key(),
+        constraint = holder.owner == authority.key(),
+    )]
+    pub holder: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CreatePosition<'info> {
+    pub fn mint_to_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::MintTo<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::MintTo {
+            mint: self.position_mint.to_account_info(),
+            to: self.holder.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+
+    pub fn freeze_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::FreezeAccount<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::FreezeAccount {
+            account: self.holder.to_account_info(),
+            mint: self.position_mint.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = voter, has_one = position_mint)]
+    pub position: Account<'info, Position>,
+
+    /// The voter giving up the position.
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    /// Like for withdrawing, governance may forbid moving the voting power
+    /// away, for example while the voter has votes that aren't relinquished.
+    pub token_owner_record: AccountInfo<'info>,
+
+    #[account(mut, constraint = holder.mint == position_mint.key())]
+    pub holder: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = registrar,
+        constraint = target_voter.key() != voter.key(),
+    )]
+    pub target_voter: AccountLoader<'info, Voter>,
+
+    /// Receives the NFT.
+    #[account(
+        mut,
+        constraint = target_holder.mint == position_mint.key(),
+        constraint = target_holder.owner == target_voter.load()?.authority,
+    )]
+    pub target_holder: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub position_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> TransferPosition<'info> {
+    pub fn thaw_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::ThawAccount<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::ThawAccount {
+            account: self.holder.to_account_info(),
+            mint: self.position_mint.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+
+    pub fn burn_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Burn<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Burn {
+            mint: self.position_mint.to_account_info(),
+            to: self.holder.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+
+    pub fn mint_to_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::MintTo<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::MintTo {
+            mint: self.position_mint.to_account_info(),
+            to: self.target_holder.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+
+    pub fn freeze_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::FreezeAccount<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::FreezeAccount {
+            account: self.target_holder.to_account_info(),
+            mint: self.position_mint.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        mut,
+        has_one = registrar,
+        has_one = voter,
+        has_one = position_mint,
+        close = sol_destination,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(mut, constraint = holder.mint == position_mint.key())]
+    pub holder: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub position_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub sol_destination: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClosePosition<'info> {
+    pub fn thaw_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::ThawAccount<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::ThawAccount {
+            account: self.holder.to_account_info(),
+            mint: self.position_mint.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+
+    pub fn burn_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Burn<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Burn {
+            mint: self.position_mint.to_account_info(),
+            to: self.holder.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateVoterDelegations<'info> {
+    #[account(has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [voter.key().as_ref(), VOTER_DELEGATIONS.as_ref()],
+        bump,
+        payer = payer,
+        space = VoterDelegations::LEN,
+    )]
+    pub delegations: AccountLoader<'info, VoterDelegations>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDelegationRecord<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = registrar,
+        constraint = delegate_voter.key() != voter.key(),
+    )]
+    pub delegate_voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        init,
+        seeds = [voter.key().as_ref(), DELEGATION_RECORD.as_ref(), delegate_voter.key().as_ref()],
+        bump,
+        payer = payer,
+        space = DelegationRecord::LEN,
+    )]
+    pub delegation_record: Account<'info, DelegationRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Delegate<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    /// Like for withdrawing, governance may forbid moving the voting power
+    /// away, for example while the voter has votes that aren't relinquished.
+    pub token_owner_record: AccountInfo<'info>,
+
+    #[account(mut, has_one = voter)]
+    pub delegations: AccountLoader<'info, VoterDelegations>,
+
+    #[account(
+        has_one = registrar,
+        constraint = delegate_voter.key() != voter.key(),
+    )]
+    pub delegate_voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        seeds = [voter.key().as_ref(), DELEGATION_RECORD.as_ref(), delegate_voter.key().as_ref()],
+        bump,
+    )]
+    pub delegation_record: Account<'info, DelegationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUndelegate<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = voter)]
+    pub delegations: AccountLoader<'info, VoterDelegations>,
+
+    pub delegate_voter: AccountLoader<'info, Voter>,
+}
+
+#[derive(Accounts)]
+pub struct Undelegate<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = voter)]
+    pub delegations: AccountLoader<'info, VoterDelegations>,
+
+    #[account(mut)]
+    pub delegate_voter: AccountLoader<'info, Voter>,
+
+    /// The delegate's record: the weight can't come back while the delegate
+    /// may have voted with it.
+    pub delegate_token_owner_record: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [voter.key().as_ref(), DELEGATION_RECORD.as_ref(), delegate_voter.key().as_ref()],
+        bump,
+        close = sol_destination,
+    )]
+    pub delegation_record: Account<'info, DelegationRecord>,
+
+    #[account(mut)]
+    pub sol_destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCpiAllowlist<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureExcludedSupplyAccount<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRegistrarAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureGrantAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeVoter<'info> {
+    #[account(has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StartUnlock<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetLockup<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InternalTransfer<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDepositEntry<'info> {
+    #[account(mut, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), deposit_mint.key().as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub deposit_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = deposit_token.mint == deposit_mint.key(),
+    )]
+    pub deposit_token: Account<'info, TokenAccount>,
+
+    /// The owner of `deposit_token`, or its delegate.
+    pub deposit_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> Deposit<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.deposit_token.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.deposit_authority.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+/// Checks that no one but the owner and `deposit_authority` can move or
+/// close `deposit_token` while tokens are deposited from it: it must not
+/// have a close authority, and its delegate, if any, must be the
+/// `deposit_authority` itself.
+pub fn check_deposit_source(
+    deposit_token: &TokenAccount,
+    deposit_authority: &Pubkey,
+) -> Result<()> {
+    require!(
+        deposit_token.close_authority.is_none(),
+        InvalidDepositSource
+    );
+    require!(
+        deposit_token.delegate.is_none()
+            || deposit_token.delegate == COption::Some(*deposit_authority),
+        InvalidDepositSource
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositTranches<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), deposit_mint.key().as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub deposit_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = deposit_token.mint == deposit_mint.key(),
+    )]
+    pub deposit_token: Account<'info, TokenAccount>,
+
+    /// The owner of `deposit_token`, or its delegate.
+    pub deposit_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> DepositTranches<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.deposit_token.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.deposit_authority.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct MigrateGovernanceDeposit<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), deposit_mint.key().as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub deposit_mint: Account<'info, Mint>,
+
+    #[account(constraint = realm.key() == registrar.load()?.realm)]
+    pub realm: UncheckedAccount<'info>,
+    /// spl-governance's token holding account of the realm for
+    /// `deposit_mint`. Checked by spl-governance.
+    #[account(mut)]
+    pub governing_token_holding: UncheckedAccount<'info>,
+    /// The authority's token owner record for `deposit_mint`.
+    #[account(mut)]
+    pub token_owner_record: UncheckedAccount<'info>,
+    #[account(constraint = governance_program.key() == registrar.load()?.governance_program_id)]
+    pub governance_program: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), NATIVE_MINT.as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> DepositSol<'info> {
+    pub fn transfer_ix(&self, lamports: u64) -> Instruction {
+        system_instruction::transfer(&self.authority.key(), &self.vault.key(), lamports)
+    }
+
+    /// spl-token's SyncNative, which credits lamports sent to a wrapped SOL
+    /// account to its token balance.
+    pub fn sync_native_ix(&self) -> Instruction {
+        Instruction {
+            program_id: token::ID,
+            accounts: vec![AccountMeta::new(self.vault.key(), false)],
+            data: vec![17],
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = authority, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub token_owner_record: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), withdraw_mint.key().as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub withdraw_mint: Account<'info, Mint>,
+
+    /// Must belong to the voter authority, so a transaction it is tricked
+    /// into signing can't send its tokens elsewhere.
+    #[account(
+        mut,
+        constraint = destination.owner == authority.key(),
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.authority,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    /// Mutable, to receive the SOL when unwrapping.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    #[account(address = tx_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// Only used with `Registrar::withdrawals_require_approval`, and may not
+    /// exist otherwise.
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), WITHDRAWAL_APPROVAL.as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub withdrawal_approval: UncheckedAccount<'info>,
+}
+
+impl<'info> Withdraw<'info> {
+    pub fn close_destination_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, token::CloseAccount<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::CloseAccount {
+            account: self.destination.to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.vault.to_account_info(),
+            to: self.destination.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct UnlockEarly<'info> {
+    #[account(mut)]
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    pub token_owner_record: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [registrar.key().as_ref(), VAULT.as_ref(), deposit_mint.key().as_ref(), &[voter.load()?.vault_shard]],
+        bump,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub deposit_mint: Account<'info, Mint>,
+
+    /// Receives the penalty.
+    #[account(
+        mut,
+        constraint = treasury.owner == registrar.load()?.early_unlock_treasury,
+        constraint = treasury.mint == deposit_mint.key(),
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.authority,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> UnlockEarly<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.vault.to_account_info(),
+            to: self.treasury.to_account_info(),
+            authority: self.registrar.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateVoterWeightRecord<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), voter.load()?.authority.as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        constraint = voter_weight_record.realm == registrar.load()?.realm,
+        constraint = voter_weight_record.governing_token_owner == voter.load()?.authority,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_index: u32)]
+pub struct EscrowProposalDeposit<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    /// The spl-governance governance the proposal is created in.
+    pub governance: UncheckedAccount<'info>,
+
+    /// The proposal to be created, which doesn't exist yet.
+    #[account(address = registrar.load()?.proposal_address(&governance.key(), proposal_index))]
+    pub proposal: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), PROPOSAL_DEPOSIT.as_ref(), proposal.key().as_ref()],
+        bump,
+        payer = payer,
+        space = ProposalDepositEscrow::LEN,
+    )]
+    pub escrow: Account<'info, ProposalDepositEscrow>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseProposalDeposit<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        has_one = registrar,
+        has_one = voter,
+        has_one = proposal,
+        has_one = payer,
+        close = payer,
+    )]
+    pub escrow: Account<'info, ProposalDepositEscrow>,
+
+    pub proposal: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LockForProposal<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority)]
+    pub voter: AccountLoader<'info, Voter>,
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    /// The spl-governance proposal to vote on.
+    pub proposal: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [voter.key().as_ref(), PROPOSAL_LOCK.as_ref(), proposal.key().as_ref()],
+        bump,
+        payer = payer,
+        space = ProposalLock::LEN,
+    )]
+    pub lock: Account<'info, ProposalLock>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseProposalLock<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        has_one = registrar,
+        has_one = voter,
+        has_one = proposal,
+        has_one = payer,
+        close = payer,
+    )]
+    pub lock: Account<'info, ProposalLock>,
+
+    pub proposal: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVoterWeightRecordCached<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), voter.load()?.registrar.as_ref(), voter.load()?.authority.as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshWeightConfig<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVoterWeightRecords<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+}
+
+#[derive(Accounts)]
+pub struct SetTimeOffset<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetVersion {}
+
+#[derive(Accounts)]
+pub struct GetVoterWeight<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+}
+
+#[derive(Accounts)]
+pub struct LogVoterInfo<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(has_one = registrar)]
+    pub voter: AccountLoader<'info, Voter>,
+}
+
+#[derive(Accounts)]
+#[instruction(max_voter_weight_record_bump: u8)]
+pub struct CreateMaxVoterWeightRecord<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        init,
+        seeds = [MAX_VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref()],
+        bump = max_voter_weight_record_bump,
+        payer = payer,
+        space = MaxVoterWeightRecord::LEN,
+    )]
+    pub max_voter_weight_record: Account<'info, MaxVoterWeightRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMaxVoteWeight<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(
+        mut,
+        seeds = [MAX_VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref()],
+        bump,
+    )]
+    pub max_voter_weight_record: Account<'info, MaxVoterWeightRecord>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct SnapshotEpoch<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    /// Created by the snapshot, so each epoch is only recorded once.
+    #[account(
+        init,
+        seeds = [registrar.key().as_ref(), EPOCH_SNAPSHOT.as_ref(), epoch.to_le_bytes().as_ref()],
+        bump,
+        payer = payer,
+        space = EpochSnapshot::LEN,
+    )]
+    pub epoch_snapshot: Account<'info, EpochSnapshot>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVoter<'info> {
+    pub registrar: AccountLoader<'info, Registrar>,
+
+    #[account(mut, has_one = registrar, has_one = authority, close = sol_destination)]
+    pub voter: AccountLoader<'info, Voter>,
+
+    #[account(
+        mut,
+        seeds = [VOTER_WEIGHT_RECORD.as_ref(), registrar.key().as_ref(), authority.key().as_ref()],
+        bump = voter.load()?.voter_weight_record_bump,
+        close = voter_weight_record_rent_destination,
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub sol_destination: UncheckedAccount<'info>,
+
+    /// See `Voter::voter_weight_record_rent_destination`.
+    #[account(
+        mut,
+        constraint = voter_weight_record_rent_destination.key()
+            == voter.load()?.voter_weight_record_rent_destination(sol_destination.key()),
+    )]
+    pub voter_weight_record_rent_destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpRent<'info> {
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpRentFromRegistrar<'info> {
+    #[account(mut, has_one = authority)]
+    pub registrar: AccountLoader<'info, Registrar>,
+    pub authority: Signer<'info>,
+
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
 }
@@ -1,39 +1,323 @@
 use anchor_lang::prelude::*;
 
+// The numeric values of these errors are part of the program's interface:
+// UIs translate on-chain failures by code. On chain, an error is reported
+// as `ERROR_CODE_OFFSET + discriminant` (currently 300 + discriminant).
+//
+// Never renumber or remove a variant. New errors are appended with the next
+// free discriminant, and must also be added to `ErrorCode::ALL`.
 #[error]
 pub enum ErrorCode {
     #[msg("Exchange rate must be greater than zero")]
-    InvalidRate,
-    #[msg("")]
-    RatesFull,
-    #[msg("")]
-    ExchangeRateEntryNotFound,
-    #[msg("")]
-    DepositEntryNotFound,
-    #[msg("")]
-    DepositEntryFull,
-    #[msg("")]
-    VotingTokenNonZero,
-    #[msg("")]
-    InvalidDepositId,
-    #[msg("")]
-    InsufficientVestedTokens,
-    #[msg("")]
-    UnableToConvert,
-    #[msg("")]
-    InvalidLockupPeriod,
-    #[msg("")]
-    InvalidEndTs,
-    #[msg("")]
-    InvalidDays,
-    #[msg("")]
-    RateNotZero,
-    #[msg("")]
-    InvalidIndex,
-    #[msg("")]
-    InvalidTokenRecordOwner,
-    #[msg("")]
-    InvalidToDepositAndWithdrawInOneSlot,
-    #[msg("")]
-    ForbiddenCpi,
+    InvalidRate = 0,
+    #[msg("Exchange rate list is full")]
+    RatesFull = 1,
+    #[msg("Exchange rate entry not found")]
+    ExchangeRateEntryNotFound = 2,
+    #[msg("Deposit entry not found")]
+    DepositEntryNotFound = 3,
+    #[msg("Deposit entries are full")]
+    DepositEntryFull = 4,
+    #[msg("Voter still has deposited tokens")]
+    VotingTokenNonZero = 5,
+    #[msg("Invalid deposit entry index")]
+    InvalidDepositId = 6,
+    #[msg("Not enough withdrawable tokens")]
+    InsufficientVestedTokens = 7,
+    #[msg("Unable to convert between units")]
+    UnableToConvert = 8,
+    #[msg("Invalid lockup period")]
+    InvalidLockupPeriod = 9,
+    #[msg("Invalid lockup end timestamp")]
+    InvalidEndTs = 10,
+    #[msg("Invalid number of days")]
+    InvalidDays = 11,
+    #[msg("Exchange rate must be zero")]
+    RateNotZero = 12,
+    #[msg("Invalid index")]
+    InvalidIndex = 13,
+    #[msg("Token owner record does not belong to the voter")]
+    InvalidTokenRecordOwner = 14,
+    #[msg("Cannot deposit and withdraw in the same slot")]
+    InvalidToDepositAndWithdrawInOneSlot = 15,
+    #[msg("Instruction may not be invoked via CPI")]
+    ForbiddenCpi = 16,
+    #[msg("Account is not owned by this program")]
+    InvalidAccountOwner = 17,
+    #[msg("Maximum number of vault shards reached")]
+    VaultShardsFull = 18,
+    #[msg("Deposit nonce must be larger than the nonce of the previous deposit")]
+    DuplicateDepositNonce = 19,
+    #[msg("Payer does not have enough lamports to cover the rent")]
+    RentPayerUnderfunded = 20,
+    #[msg("Registrar does not have enough excess lamports to cover the rent")]
+    RegistrarRentReserveUnderfunded = 21,
+    #[msg("Registrar cannot top up its own rent")]
+    InvalidRentTopUpTarget = 22,
+    #[msg("Lockup saturation must be greater than zero")]
+    InvalidLockupSaturation = 23,
+    #[msg("Voter does not belong to the registrar")]
+    VoterRegistrarMismatch = 24,
+    #[msg("Mint does not match the deposit entry's voting mint")]
+    InvalidMint = 25,
+    #[msg("Mint accounts must match the registrar's voting mints")]
+    InvalidVotingMintAccounts = 26,
+    #[msg("Signer is not the registrar's authority or grant authority")]
+    InvalidGrantAuthority = 27,
+    #[msg("Amount exceeds the tokens requested for withdrawal")]
+    InsufficientPendingWithdrawal = 28,
+    #[msg("Withdrawal cooldown has not passed yet")]
+    WithdrawalCooldownActive = 29,
+    #[msg("Deposit amount is below the minimum")]
+    DepositBelowMinimum = 30,
+    #[msg("Balance left after the withdraw is below the minimum")]
+    RemainingBalanceBelowMinimum = 31,
+    #[msg("Arithmetic overflow")]
+    Overflow = 32,
+    #[msg("Arithmetic underflow")]
+    Underflow = 33,
+    #[msg("Time offsets require the localnet-test feature")]
+    TimeOffsetUnavailable = 34,
+    #[msg("Deposit would exceed the per-voter deposit cap")]
+    VoterDepositCapExceeded = 35,
+    #[msg("Deposit would exceed the registrar's total deposit cap")]
+    TotalDepositCapExceeded = 36,
+    #[msg("Realm account does not match the registrar")]
+    InvalidRealm = 37,
+    #[msg("Realm does not use this program as its community voter weight addin")]
+    RealmAddinMismatch = 38,
+    #[msg("Deposits into the registrar are paused")]
+    DepositsPaused = 39,
+    #[msg("Voter is frozen by the registrar authority")]
+    VoterFrozen = 40,
+    #[msg("Voter authority is not on the registrar's allowlist")]
+    NotAllowlisted = 41,
+    #[msg("Voter authority is on the registrar's blocklist")]
+    Blocklisted = 42,
+    #[msg("The voter authority's blocklist entry address must be passed")]
+    MissingBlocklistAccount = 43,
+    #[msg("Account is not a gateway token of the registrar's gatekeeper program")]
+    InvalidGatewayToken = 44,
+    #[msg("Voter authority has no valid gateway token of the registrar's gatekeeper network")]
+    MissingGatewayToken = 45,
+    #[msg("The voter's VoterWeightRecord of the upstream addin must be passed")]
+    MissingUpstreamVoterWeightRecord = 46,
+    #[msg("Upstream VoterWeightRecord is expired or for another voter or action")]
+    InvalidUpstreamVoterWeightRecord = 47,
+    #[msg("Digit shift must be between -18 and 18")]
+    InvalidDigitShift = 48,
+    #[msg("The oracle accounts of the registrar's priced voting mints must be passed")]
+    MissingOracleAccount = 49,
+    #[msg("Oracle account does not hold a valid price")]
+    InvalidOraclePrice = 50,
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice = 51,
+    #[msg("Registrar has no rewards")]
+    RewardsNotConfigured = 52,
+    #[msg("Registrar already has a reward vault")]
+    RewardsAlreadyConfigured = 53,
+    #[msg("Registrar does not allow unlocking early")]
+    EarlyUnlockDisabled = 54,
+    #[msg("Early unlock penalty must be at most 10000 basis points")]
+    InvalidEarlyUnlockPenalty = 55,
+    #[msg("The voter's checkpoints account must be passed")]
+    MissingCheckpointsAccount = 56,
+    #[msg("Merkle proof does not match the distributor's root")]
+    InvalidMerkleProof = 57,
+    #[msg("The realm, community mint and governance program must be passed to create the token owner record")]
+    MissingTokenOwnerRecordAccounts = 58,
+    #[msg("Registrars with an NFT boost or upstream addin need update_voter_weight_record")]
+    BatchUpdateUnsupported = 59,
+    #[msg("Accounts must be pairs of a voter of the registrar and its VoterWeightRecord")]
+    InvalidVoterAccounts = 60,
+    #[msg("Account is not a registrar or voter")]
+    InvalidMigrationAccount = 61,
+    #[msg("Voter has no complete copy of the registrar's weight settings")]
+    WeightConfigNotCached = 62,
+    #[msg("Withdraw amount is below the minimum and doesn't empty the deposit entry")]
+    WithdrawBelowMinimum = 63,
+    #[msg("Deposit token account has a close authority or a delegate other than the deposit authority")]
+    InvalidDepositSource = 64,
+    #[msg("The rent of a sponsored voter must go back to its sponsor")]
+    SponsoredRentNotReturned = 65,
+    #[msg("Weight scale numerator and denominator must be positive")]
+    InvalidWeightScale = 66,
+    #[msg("Tokens escrowed or locked for a proposal can't be withdrawn")]
+    ProposalDepositEscrowed = 67,
+    #[msg("Not enough community tokens deposited for the proposal deposit or lock")]
+    InsufficientProposalDeposit = 68,
+    #[msg("The proposal is still open")]
+    ProposalNotFinal = 69,
+    #[msg("The proposal is not the governance's next proposal")]
+    InvalidProposal = 70,
+    #[msg("The withdrawal was not approved by the registrar authority")]
+    WithdrawalNotApproved = 71,
+    #[msg("Deposit labels must be UTF-8")]
+    InvalidDepositLabel = 72,
+    #[msg("The registrar doesn't allow transferring positions")]
+    PositionTransfersDisabled = 73,
+    #[msg("Position mints need zero decimals and supply, with the registrar as mint and freeze authority")]
+    InvalidPositionMint = 74,
+    #[msg("The deposit entry is a position")]
+    DepositEntryHasPosition = 75,
+    #[msg("The deposit entry has no locked tokens")]
+    DepositNotLocked = 76,
+    #[msg("The voters use different vault shards")]
+    VaultShardMismatch = 77,
+    #[msg("The voter has the maximum number of delegations")]
+    TooManyDelegations = 78,
+    #[msg("Delegates can't delegate their weight")]
+    DelegationChain = 79,
+    #[msg("The delegate has the maximum number of delegators")]
+    TooManyDelegators = 80,
+    #[msg("Account is not a voter delegating to this voter")]
+    InvalidDelegator = 81,
+    #[msg("The voter delegates or has delegators")]
+    VoterHasDelegations = 82,
+    #[msg("Delegations of a deposit entry exceed its deposited amount")]
+    DelegationExceedsDeposit = 83,
+    #[msg("Delegation shares must be positive and at most 10000 basis points")]
+    InvalidDelegationShare = 84,
+    #[msg("The voter doesn't delegate to this delegate")]
+    DelegationNotFound = 85,
+    #[msg("The voter delegates and its VoterDelegations account is missing")]
+    MissingDelegationsAccount = 86,
+    #[msg("Undelegating needs request_undelegate and the cooldown to pass")]
+    UndelegateCooldownActive = 87,
+    #[msg("Delegation shares can only grow, use undelegate to reduce them")]
+    DelegationShareReduced = 88,
+    #[msg("Proposal locking is not enabled for the registrar")]
+    ProposalLockingDisabled = 89,
+    #[msg("The proposal doesn't exist or voting on it is over")]
+    ProposalNotOpen = 90,
+    #[msg("The deposit entry is locked for a proposal")]
+    DepositEntryLockedForProposal = 91,
+    #[msg("Token accounts must match the registrar's excluded supply accounts")]
+    InvalidExcludedSupplyAccounts = 92,
+    #[msg("Epoch must be the current epoch")]
+    InvalidEpoch = 93,
+    #[msg("Reward rate must be at most MAX_REWARD_RATE_PER_SEC")]
+    InvalidRewardRate = 94,
+    #[msg("A delegate of the deposit entry or its token owner record is missing")]
+    MissingDelegateAccounts = 95,
+    #[msg("Voters that delegate or have delegators need update_voter_weight_record")]
+    DelegatedWeightUnsupported = 96,
+    #[msg("Voter's copy of the weight settings is outdated, call refresh_weight_config")]
+    WeightConfigStale = 97,
+}
+
+impl ErrorCode {
+    /// All errors, in discriminant order.
+    pub const ALL: &'static [ErrorCode] = &[
+        ErrorCode::InvalidRate,
+        ErrorCode::RatesFull,
+        ErrorCode::ExchangeRateEntryNotFound,
+        ErrorCode::DepositEntryNotFound,
+        ErrorCode::DepositEntryFull,
+        ErrorCode::VotingTokenNonZero,
+        ErrorCode::InvalidDepositId,
+        ErrorCode::InsufficientVestedTokens,
+        ErrorCode::UnableToConvert,
+        ErrorCode::InvalidLockupPeriod,
+        ErrorCode::InvalidEndTs,
+        ErrorCode::InvalidDays,
+        ErrorCode::RateNotZero,
+        ErrorCode::InvalidIndex,
+        ErrorCode::InvalidTokenRecordOwner,
+        ErrorCode::InvalidToDepositAndWithdrawInOneSlot,
+        ErrorCode::ForbiddenCpi,
+        ErrorCode::InvalidAccountOwner,
+        ErrorCode::VaultShardsFull,
+        ErrorCode::DuplicateDepositNonce,
+        ErrorCode::RentPayerUnderfunded,
+        ErrorCode::RegistrarRentReserveUnderfunded,
+        ErrorCode::InvalidRentTopUpTarget,
+        ErrorCode::InvalidLockupSaturation,
+        ErrorCode::VoterRegistrarMismatch,
+        ErrorCode::InvalidMint,
+        ErrorCode::InvalidVotingMintAccounts,
+        ErrorCode::InvalidGrantAuthority,
+        ErrorCode::InsufficientPendingWithdrawal,
+        ErrorCode::WithdrawalCooldownActive,
+        ErrorCode::DepositBelowMinimum,
+        ErrorCode::RemainingBalanceBelowMinimum,
+        ErrorCode::Overflow,
+        ErrorCode::Underflow,
+        ErrorCode::TimeOffsetUnavailable,
+        ErrorCode::VoterDepositCapExceeded,
+        ErrorCode::TotalDepositCapExceeded,
+        ErrorCode::InvalidRealm,
+        ErrorCode::RealmAddinMismatch,
+        ErrorCode::DepositsPaused,
+        ErrorCode::VoterFrozen,
+        ErrorCode::NotAllowlisted,
+        ErrorCode::Blocklisted,
+        ErrorCode::MissingBlocklistAccount,
+        ErrorCode::InvalidGatewayToken,
+        ErrorCode::MissingGatewayToken,
+        ErrorCode::MissingUpstreamVoterWeightRecord,
+        ErrorCode::InvalidUpstreamVoterWeightRecord,
+        ErrorCode::InvalidDigitShift,
+        ErrorCode::MissingOracleAccount,
+        ErrorCode::InvalidOraclePrice,
+        ErrorCode::StaleOraclePrice,
+        ErrorCode::RewardsNotConfigured,
+        ErrorCode::RewardsAlreadyConfigured,
+        ErrorCode::EarlyUnlockDisabled,
+        ErrorCode::InvalidEarlyUnlockPenalty,
+        ErrorCode::MissingCheckpointsAccount,
+        ErrorCode::InvalidMerkleProof,
+        ErrorCode::MissingTokenOwnerRecordAccounts,
+        ErrorCode::BatchUpdateUnsupported,
+        ErrorCode::InvalidVoterAccounts,
+        ErrorCode::InvalidMigrationAccount,
+        ErrorCode::WeightConfigNotCached,
+        ErrorCode::WithdrawBelowMinimum,
+        ErrorCode::InvalidDepositSource,
+        ErrorCode::SponsoredRentNotReturned,
+        ErrorCode::InvalidWeightScale,
+        ErrorCode::ProposalDepositEscrowed,
+        ErrorCode::InsufficientProposalDeposit,
+        ErrorCode::ProposalNotFinal,
+        ErrorCode::InvalidProposal,
+        ErrorCode::WithdrawalNotApproved,
+        ErrorCode::InvalidDepositLabel,
+        ErrorCode::PositionTransfersDisabled,
+        ErrorCode::InvalidPositionMint,
+        ErrorCode::DepositEntryHasPosition,
+        ErrorCode::DepositNotLocked,
+        ErrorCode::VaultShardMismatch,
+        ErrorCode::TooManyDelegations,
+        ErrorCode::DelegationChain,
+        ErrorCode::TooManyDelegators,
+        ErrorCode::InvalidDelegator,
+        ErrorCode::VoterHasDelegations,
+        ErrorCode::DelegationExceedsDeposit,
+        ErrorCode::InvalidDelegationShare,
+        ErrorCode::DelegationNotFound,
+        ErrorCode::MissingDelegationsAccount,
+        ErrorCode::UndelegateCooldownActive,
+        ErrorCode::DelegationShareReduced,
+        ErrorCode::ProposalLockingDisabled,
+        ErrorCode::ProposalNotOpen,
+        ErrorCode::DepositEntryLockedForProposal,
+        ErrorCode::InvalidExcludedSupplyAccounts,
+        ErrorCode::InvalidEpoch,
+        ErrorCode::InvalidRewardRate,
+        ErrorCode::MissingDelegateAccounts,
+        ErrorCode::DelegatedWeightUnsupported,
+        ErrorCode::WeightConfigStale,
+    ];
+
+    /// The error for an on-chain error code, if it is one of this program's.
+    pub fn from_code(code: u32) -> Option<ErrorCode> {
+        Self::ALL.iter().copied().find(|e| u32::from(*e) == code)
+    }
+}
+
+impl From<ErrorCode> for u32 {
+    /// The on-chain error code, `ERROR_CODE_OFFSET + discriminant`.
+    fn from(e: ErrorCode) -> u32 {
+        anchor_lang::__private::ERROR_CODE_OFFSET + e as u32
+    }
 }
@@ -0,0 +1,99 @@
+use crate::account::{DelegationShareKind, VoterWeightAction};
+use anchor_lang::prelude::*;
+
+/// Tokens were added to a deposit entry, by `deposit` or `grant`.
+#[event]
+pub struct DepositEvent {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub mint: Pubkey,
+    pub deposit_entry_index: u8,
+    /// Native tokens added.
+    pub amount: u64,
+    /// Native tokens in the deposit entry afterwards.
+    pub deposit_entry_amount: u64,
+}
+
+/// Tokens were withdrawn from a deposit entry.
+#[event]
+pub struct WithdrawEvent {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub mint: Pubkey,
+    pub deposit_entry_index: u8,
+    /// Native tokens withdrawn.
+    pub amount: u64,
+    /// Native tokens in the deposit entry afterwards.
+    pub deposit_entry_amount: u64,
+}
+
+/// The locked tokens of a deposit entry were unlocked early.
+#[event]
+pub struct EarlyUnlockEvent {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub mint: Pubkey,
+    pub deposit_entry_index: u8,
+    /// Native tokens forfeited to the treasury.
+    pub penalty: u64,
+    /// Native tokens in the deposit entry afterwards, all unlocked.
+    pub deposit_entry_amount: u64,
+}
+
+/// A voter's `VoterWeightRecord` was written.
+#[event]
+pub struct VoterWeightUpdated {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub voter_weight: u64,
+    pub weight_action: Option<VoterWeightAction>,
+    pub weight_action_target: Option<Pubkey>,
+    pub slot: u64,
+}
+
+/// A voter claimed rewards.
+#[event]
+pub struct RewardClaimEvent {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    /// Native reward tokens paid out.
+    pub amount: u64,
+    /// Native reward tokens still owed to the voter afterwards.
+    pub reward_pending: u64,
+}
+
+/// The registrar authority changed the reward emissions.
+#[event]
+pub struct RewardRateEvent {
+    pub registrar: Pubkey,
+    /// Native reward tokens emitted per second before the change.
+    pub previous_reward_rate_per_sec: u64,
+    pub reward_rate_per_sec: u64,
+}
+
+/// A voter created the `DelegationRecord` for delegating to a delegate.
+#[event]
+pub struct DelegationCreatedEvent {
+    pub registrar: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+}
+
+/// A voter delegated a share of a deposit entry, or changed it.
+#[event]
+pub struct DelegateEvent {
+    pub registrar: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub deposit_entry_index: u8,
+    pub share_kind: DelegationShareKind,
+    pub share: u64,
+}
+
+/// A voter ended its delegations to a delegate.
+#[event]
+pub struct UndelegateEvent {
+    pub registrar: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+}
@@ -0,0 +1,49 @@
+use crate::error::*;
+use anchor_lang::prelude::*;
+
+/// A gateway token as issued by the Civic gateway program: an attestation
+/// by a gatekeeper of `gatekeeper_network` about `owner_wallet`.
+///
+/// Other attestation programs can be used as gatekeeper as long as their
+/// accounts start with this layout.
+#[derive(AnchorDeserialize, Clone, Debug)]
+pub struct GatewayToken {
+    pub features: u8,
+    pub parent_gateway_token: Option<Pubkey>,
+    pub owner_wallet: Pubkey,
+    pub owner_identity: Option<Pubkey>,
+    pub gatekeeper_network: Pubkey,
+    pub issuing_gatekeeper: Pubkey,
+    pub state: GatewayTokenState,
+    pub expire_time: Option<i64>,
+}
+
+#[derive(AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum GatewayTokenState {
+    Active,
+    Revoked,
+    Frozen,
+}
+
+impl GatewayToken {
+    /// Reads a gateway token from an account owned by `gatekeeper_program`.
+    pub fn try_from_account_info(
+        account: &AccountInfo,
+        gatekeeper_program: &Pubkey,
+    ) -> Result<GatewayToken> {
+        require!(account.owner == gatekeeper_program, InvalidGatewayToken);
+        let data = account.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        GatewayToken::deserialize(&mut data_slice)
+            .map_err(|_| ErrorCode::InvalidGatewayToken.into())
+    }
+
+    /// Whether the token attests `wallet` in `gatekeeper_network` at
+    /// `curr_ts`.
+    pub fn is_valid_for(&self, wallet: &Pubkey, gatekeeper_network: &Pubkey, curr_ts: i64) -> bool {
+        self.owner_wallet == *wallet
+            && self.gatekeeper_network == *gatekeeper_network
+            && self.state == GatewayTokenState::Active
+            && self.expire_time.map_or(true, |expiry| curr_ts < expiry)
+    }
+}
@@ -0,0 +1,199 @@
+use crate::error::*;
+use anchor_lang::prelude::*;
+use spl_governance::error::GovernanceError;
+use spl_governance::state::enums::{GovernanceAccountType, ProposalState};
+
+/// The leading fields of a spl-governance `TokenOwnerRecord`, enough to
+/// decide whether governing tokens may be withdrawn.
+#[derive(AnchorDeserialize, Clone, Debug)]
+pub struct TokenOwnerRecordHeader {
+    pub account_type: GovernanceAccountType,
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub governing_token_deposit_amount: u64,
+    pub unrelinquished_votes_count: u32,
+    pub total_votes_count: u32,
+    pub outstanding_proposal_count: u8,
+}
+
+impl TokenOwnerRecordHeader {
+    /// Reads the token owner record of `governing_token_owner` for
+    /// `governing_token_mint` in `realm`.
+    ///
+    /// Only the header is deserialized, and the record's address is not
+    /// derived, which would cost a lot of compute. spl-governance creates
+    /// token owner records only at their address, so a record owned by the
+    /// governance program with matching keys is the one.
+    pub fn load(
+        account: &AccountInfo,
+        governance_program_id: &Pubkey,
+        realm: &Pubkey,
+        governing_token_mint: &Pubkey,
+        governing_token_owner: &Pubkey,
+    ) -> Result<Self> {
+        require!(account.owner == governance_program_id, InvalidAccountOwner);
+        let data = account.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        let header = Self::deserialize(&mut data_slice).map_err(ProgramError::from)?;
+        let is_record = matches!(
+            header.account_type,
+            GovernanceAccountType::TokenOwnerRecordV1 | GovernanceAccountType::TokenOwnerRecordV2
+        );
+        if !is_record
+            || header.realm != *realm
+            || header.governing_token_mint != *governing_token_mint
+            || header.governing_token_owner != *governing_token_owner
+        {
+            return Err(
+                ProgramError::from(GovernanceError::InvalidTokenOwnerRecordAccountAddress).into(),
+            );
+        }
+        Ok(header)
+    }
+
+    /// Like `load`, for the record of `governing_token_owner` among
+    /// `accounts`. `None` if it isn't there.
+    pub fn find(
+        accounts: &[AccountInfo],
+        governance_program_id: &Pubkey,
+        realm: &Pubkey,
+        governing_token_mint: &Pubkey,
+        governing_token_owner: &Pubkey,
+    ) -> Option<Self> {
+        accounts
+            .iter()
+            .filter(|info| info.owner == governance_program_id)
+            .find_map(|info| {
+                Self::load(
+                    info,
+                    governance_program_id,
+                    realm,
+                    governing_token_mint,
+                    governing_token_owner,
+                )
+                .ok()
+            })
+    }
+
+    /// Same check as spl-governance's `TokenOwnerRecord` method of the same
+    /// name: no votes may be unrelinquished and no proposals outstanding.
+    pub fn assert_can_withdraw_governing_tokens(&self) -> Result<()> {
+        if self.unrelinquished_votes_count > 0 {
+            return Err(ProgramError::from(
+                GovernanceError::AllVotesMustBeRelinquishedToWithdrawGoverningTokens,
+            )
+            .into());
+        }
+        self.assert_no_outstanding_proposals()
+    }
+
+    /// Like `assert_can_withdraw_governing_tokens`, except that with
+    /// `proposal_locking` votes don't hold back any tokens: the ones they
+    /// were cast with are in `ProposalLock`s.
+    pub fn assert_can_withdraw_unlocked_tokens(&self, proposal_locking: bool) -> Result<()> {
+        if proposal_locking {
+            self.assert_no_outstanding_proposals()
+        } else {
+            self.assert_can_withdraw_governing_tokens()
+        }
+    }
+
+    fn assert_no_outstanding_proposals(&self) -> Result<()> {
+        if self.outstanding_proposal_count > 0 {
+            return Err(ProgramError::from(
+                GovernanceError::AllProposalsMustBeFinalisedToWithdrawGoverningTokens,
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// The leading fields of a spl-governance `Proposal`, enough to decide
+/// whether it is still open.
+#[derive(AnchorDeserialize, Clone, Debug)]
+pub struct ProposalHeader {
+    pub account_type: GovernanceAccountType,
+    pub governance: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub state: ProposalState,
+}
+
+impl ProposalHeader {
+    /// Reads the proposal at `account`, which must be one of
+    /// `governing_token_mint`. `None` if the account was never created.
+    ///
+    /// Like for token owner records, the address is checked by the caller.
+    pub fn load(
+        account: &AccountInfo,
+        governance_program_id: &Pubkey,
+        governing_token_mint: &Pubkey,
+    ) -> Result<Option<Self>> {
+        if account.data_is_empty() {
+            return Ok(None);
+        }
+        require!(account.owner == governance_program_id, InvalidAccountOwner);
+        let data = account.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        let header = Self::deserialize(&mut data_slice).map_err(ProgramError::from)?;
+        let is_proposal = matches!(
+            header.account_type,
+            GovernanceAccountType::ProposalV1 | GovernanceAccountType::ProposalV2
+        );
+        if !is_proposal || header.governing_token_mint != *governing_token_mint {
+            return Err(
+                ProgramError::from(GovernanceError::InvalidGoverningMintForProposal).into(),
+            );
+        }
+        Ok(Some(header))
+    }
+
+    /// Whether voting on the proposal is over: it was decided, executed or
+    /// cancelled.
+    pub fn is_final(&self) -> bool {
+        !matches!(
+            self.state,
+            ProposalState::Draft | ProposalState::SigningOff | ProposalState::Voting
+        )
+    }
+}
+
+/// The leading fields of a spl-governance governance account, enough to
+/// know the address of its next proposal.
+#[derive(AnchorDeserialize, Clone, Debug)]
+pub struct GovernanceHeader {
+    pub account_type: GovernanceAccountType,
+    pub realm: Pubkey,
+    pub governed_account: Pubkey,
+    pub proposals_count: u32,
+}
+
+impl GovernanceHeader {
+    /// Reads the governance at `account`, which must be one of `realm`.
+    pub fn load(
+        account: &AccountInfo,
+        governance_program_id: &Pubkey,
+        realm: &Pubkey,
+    ) -> Result<Self> {
+        require!(account.owner == governance_program_id, InvalidAccountOwner);
+        let data = account.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        let header = Self::deserialize(&mut data_slice).map_err(ProgramError::from)?;
+        let is_governance = matches!(
+            header.account_type,
+            GovernanceAccountType::GovernanceV1
+                | GovernanceAccountType::GovernanceV2
+                | GovernanceAccountType::ProgramGovernanceV1
+                | GovernanceAccountType::ProgramGovernanceV2
+                | GovernanceAccountType::MintGovernanceV1
+                | GovernanceAccountType::MintGovernanceV2
+                | GovernanceAccountType::TokenGovernanceV1
+                | GovernanceAccountType::TokenGovernanceV2
+        );
+        if !is_governance || header.realm != *realm {
+            return Err(ProgramError::from(GovernanceError::InvalidRealmForGovernance).into());
+        }
+        Ok(header)
+    }
+}
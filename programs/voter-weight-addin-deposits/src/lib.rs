@@ -1,16 +1,49 @@
+use account::*;
 use anchor_lang::prelude::*;
-use anchor_spl::token;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Mint, TokenAccount};
+use args::*;
 use context::*;
 use error::*;
-use spl_governance::addins::voter_weight::VoterWeightAccountType;
+use events::*;
+use governance::{GovernanceHeader, ProposalHeader, TokenOwnerRecordHeader};
 
 pub mod account;
+pub mod args;
+#[cfg(feature = "client")]
+pub mod client;
 mod context;
-mod error;
+pub mod error;
+pub mod events;
+pub mod gateway;
+pub mod governance;
+pub mod merkle;
+pub mod nft;
+pub mod oracle;
+#[cfg(feature = "no-entrypoint")]
+pub mod parse;
+mod rent;
+pub mod seeds;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // The program address.
 declare_id!("HoVX43xherfXV6RUoLmcoLA1XSyd9SbF4V1Edpi2QbLY");
 
+// Read by auditors and scanners from the deployed binary. Only in builds
+// with an entrypoint, so programs depending on this one don't embed it too.
+#[cfg(all(feature = "security-txt", not(feature = "no-entrypoint")))]
+solana_security_txt::security_txt! {
+    name: "voter-weight-addin-deposits",
+    project_url: "https://github.com/ckamm/voter-weight-addin-deposits",
+    contacts: "link:https://github.com/ckamm/voter-weight-addin-deposits/security/advisories/new",
+    policy: "https://github.com/ckamm/voter-weight-addin-deposits/blob/master/SECURITY.md",
+    source_code: "https://github.com/ckamm/voter-weight-addin-deposits"
+}
+
 /// # Introduction
 ///
 /// The voter-weight-addin-deposits is an "addin" to the SPL governance program
@@ -22,6 +55,7 @@ declare_id!("HoVX43xherfXV6RUoLmcoLA1XSyd9SbF4V1Edpi2QbLY");
 /// - Create a SPL governance realm.
 /// - Create a governance registry account.
 /// - Create a voter account.
+/// - Optionally, create a deposit entry with a lockup.
 /// - Deposit tokens into this program.
 /// - Vote.
 ///
@@ -38,6 +72,29 @@ declare_id!("HoVX43xherfXV6RUoLmcoLA1XSyd9SbF4V1Edpi2QbLY");
 /// format, which is then used by SPL governance as the voting power measurement
 /// for a given user.
 ///
+/// # Governance-controlled configuration
+///
+/// All `configure_*` instructions only require the signature of the
+/// registrar's authority. Setting the authority to a governance PDA of the
+/// realm, at creation or later with `configure_registrar_authority`, lets
+/// the realm change its own registrar: a proposal carrying the configure
+/// instruction is executed by spl-governance, which signs for the PDA.
+///
+/// # Token programs
+///
+/// Only mints of the original SPL token program can be deposited. Vaults,
+/// deposits and withdraws use Anchor 0.18's `TokenAccount` and `Mint`, which
+/// require accounts owned by that program with exactly its layout, so
+/// Token-2022 mints and accounts are rejected. Supporting them needs an
+/// Anchor version with token interface types.
+///
+/// # Using as a library
+///
+/// With the `no-entrypoint` feature this crate can be depended on by other
+/// on-chain programs. The `account` module exposes the account types and the
+/// voting weight computation, `seeds` the address derivations. That allows
+/// verifying a voter's state directly from account data, without CPI.
+///
 #[program]
 pub mod voter_weight_addin_deposits {
     use super::*;
@@ -47,48 +104,709 @@ pub mod voter_weight_addin_deposits {
     pub fn create_registrar(ctx: Context<CreateRegistrar>, registrar_bump: u8) -> Result<()> {
         let registrar = &mut ctx.accounts.registrar.load_init()?;
         registrar.bump = registrar_bump;
+        registrar.version = REGISTRAR_VERSION;
         registrar.governance_program_id = ctx.accounts.governance_program_id.key();
         registrar.realm = ctx.accounts.realm.key();
         registrar.realm_community_mint = ctx.accounts.realm_community_mint.key();
+        registrar.voting_mints[0] = VotingMintConfig {
+            mint: registrar.realm_community_mint,
+            rate: 1,
+            min_deposit_native: 0,
+            min_remaining_native: 0,
+            max_voter_deposit_native: 0,
+            total_deposited_native: 0,
+            max_total_deposit_native: 0,
+            digit_shift: 0,
+            oracle: Pubkey::default(),
+        };
         registrar.authority = ctx.accounts.authority.key();
+        registrar.vault_shard_count = 1;
+        registrar.lockup_saturation_secs = DEFAULT_LOCKUP_SATURATION_SECS;
+        registrar.max_extra_lockup_vote_weight_factor = DEFAULT_MAX_EXTRA_LOCKUP_VOTE_WEIGHT_FACTOR;
+        registrar.weight_action_bps = [BPS_SCALE; VOTER_WEIGHT_ACTION_COUNT];
+
+        Ok(())
+    }
+
+    /// Checks that the registrar's realm is set up to use this program: the
+    /// realm's community mint is the registrar's and its realm config names
+    /// this program as the community voter weight addin. Changes nothing.
+    ///
+    /// Meant to be called after setting up a realm and before users deposit,
+    /// since votes can't be cast with a misconfigured realm.
+    pub fn verify_registrar(ctx: Context<VerifyRegistrar>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let realm_info = ctx.accounts.realm.to_account_info();
+        require!(realm_info.key() == registrar.realm, InvalidRealm);
+
+        use spl_governance::state::{realm, realm_config};
+        let realm_data = realm::get_realm_data(&registrar.governance_program_id, &realm_info)?;
+        require!(
+            realm_data.community_mint == registrar.realm_community_mint,
+            InvalidRealm
+        );
+
+        let realm_config_data = realm_config::get_realm_config_data_for_realm(
+            &registrar.governance_program_id,
+            &ctx.accounts.realm_config.to_account_info(),
+            &registrar.realm,
+        )?;
+        require!(
+            realm_config_data.community_voter_weight_addin == Some(crate::id()),
+            RealmAddinMismatch
+        );
+        Ok(())
+    }
+
+    /// Adds a mint that can be deposited, or changes the rate of one.
+    ///
+    /// One native token of the mint counts as `rate` voting weight. Vaults
+    /// for the mint are created with `create_vault_shard`, one for each vault
+    /// shard of the registrar.
+    pub fn configure_voting_mint(
+        ctx: Context<ConfigureVotingMint>,
+        idx: u8,
+        rate: u64,
+    ) -> Result<()> {
+        require!(rate > 0, InvalidRate);
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let mint = ctx.accounts.mint.key();
+        let idx = idx as usize;
+        require!(idx < registrar.voting_mints.len(), RatesFull);
+
+        // A config slot can't be switched to another mint, since deposits
+        // of the old mint may refer to it.
+        let config = &registrar.voting_mints[idx];
+        require!(!config.in_use() || config.mint == mint, InvalidIndex);
+        if let Ok(existing_idx) = registrar.voting_mint_config_index(mint) {
+            require!(existing_idx == idx, InvalidIndex);
+        }
+
+        let config = &mut registrar.voting_mints[idx];
+        config.mint = mint;
+        config.rate = rate;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets the minimum deposit and the minimum balance a withdraw may leave
+    /// in a deposit entry, for the voting mint at `idx`. Both are in native
+    /// tokens of the mint and keep the vault free of dust deposits.
+    pub fn configure_min_amounts(
+        ctx: Context<ConfigureMinAmounts>,
+        idx: u8,
+        min_deposit_native: u64,
+        min_remaining_native: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let config = registrar
+            .voting_mints
+            .get_mut(idx as usize)
+            .ok_or(ErrorCode::InvalidIndex)?;
+        require!(config.in_use(), InvalidIndex);
+        config.min_deposit_native = min_deposit_native;
+        config.min_remaining_native = min_remaining_native;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets the smallest amount of the voting mint at `idx` that can be
+    /// withdrawn from a deposit entry, see `Registrar::min_withdraw_native`.
+    /// Balances below it can still be withdrawn in full, or with
+    /// `sweep_dust`.
+    pub fn configure_min_withdraw(
+        ctx: Context<ConfigureMinAmounts>,
+        idx: u8,
+        min_withdraw_native: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let config = registrar
+            .voting_mints
+            .get(idx as usize)
+            .ok_or(ErrorCode::InvalidIndex)?;
+        require!(config.in_use(), InvalidIndex);
+        registrar.min_withdraw_native[idx as usize] = min_withdraw_native;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets the digit shift of the voting mint at `idx`: its weight is
+    /// multiplied by `10^digit_shift` after applying the rate. For example,
+    /// with a 6-decimal mint at digit shift 0, a 9-decimal mint needs -3 so
+    /// whole tokens of both count the same.
+    pub fn configure_digit_shift(
+        ctx: Context<ConfigureDigitShift>,
+        idx: u8,
+        digit_shift: i8,
+    ) -> Result<()> {
+        require!(
+            digit_shift.unsigned_abs() <= MAX_DIGIT_SHIFT as u8,
+            InvalidDigitShift
+        );
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let config = registrar
+            .voting_mints
+            .get_mut(idx as usize)
+            .ok_or(ErrorCode::InvalidIndex)?;
+        require!(config.in_use(), InvalidIndex);
+        config.digit_shift = digit_shift;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Prices the voting mint at `idx` with the Pyth price account `oracle`,
+    /// so deposits of LP or derivative tokens count with the value of their
+    /// underlying. Passing the default pubkey removes the oracle.
+    ///
+    /// `update_voter_weight_record` then needs the oracle account as an
+    /// additional account. Other instructions, and the
+    /// `MaxVoterWeightRecord`, count the mint's deposits without the price.
+    pub fn configure_oracle(ctx: Context<ConfigureOracle>, idx: u8, oracle: Pubkey) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let config = registrar
+            .voting_mints
+            .get_mut(idx as usize)
+            .ok_or(ErrorCode::InvalidIndex)?;
+        require!(config.in_use(), InvalidIndex);
+        config.oracle = oracle;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Caps how many native tokens of the voting mint at `idx` all voters
+    /// together can deposit, for example during a staged rollout. Zero
+    /// removes the cap.
+    ///
+    /// Only tokens deposited since the total is tracked count towards it.
+    pub fn configure_max_total_deposit(
+        ctx: Context<ConfigureMaxTotalDeposit>,
+        idx: u8,
+        max_total_deposit_native: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let config = registrar
+            .voting_mints
+            .get_mut(idx as usize)
+            .ok_or(ErrorCode::InvalidIndex)?;
+        require!(config.in_use(), InvalidIndex);
+        config.max_total_deposit_native = max_total_deposit_native;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Caps how many native tokens of the voting mint at `idx` a single
+    /// voter can deposit, limiting the voting power one wallet can build up
+    /// through deposits. Zero removes the cap. Grants are not limited.
+    pub fn configure_max_voter_deposit(
+        ctx: Context<ConfigureMaxVoterDeposit>,
+        idx: u8,
+        max_voter_deposit_native: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let config = registrar
+            .voting_mints
+            .get_mut(idx as usize)
+            .ok_or(ErrorCode::InvalidIndex)?;
+        require!(config.in_use(), InvalidIndex);
+        config.max_voter_deposit_native = max_voter_deposit_native;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Creates the vault of a voting mint for the vault shard `shard`.
+    ///
+    /// Passing the next unused shard index adds a vault shard to the
+    /// registrar: voters created afterwards are spread over all vault shards.
+    /// Deposits and withdraws of voters on different shards write-lock
+    /// different vaults. They all still write-lock the registrar though,
+    /// which tracks the total deposits.
+    ///
+    /// Every voting mint needs a vault for each shard, so after adding a
+    /// shard it must be created for the other voting mints too.
+    pub fn create_vault_shard(ctx: Context<CreateVaultShard>, shard: u8) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.voting_mint_config_index(ctx.accounts.mint.key())?;
+        require!(shard <= registrar.vault_shard_count, InvalidIndex);
+        if shard == registrar.vault_shard_count {
+            require!(registrar.vault_shard_count < u8::MAX, VaultShardsFull);
+            registrar.vault_shard_count += 1;
+        }
+        Ok(())
+    }
+
+    /// Turns the allowlist on or off. While it is on, only wallets added
+    /// with `add_to_allowlist` can create voters and deposit, for
+    /// membership-style voting. Voters whose wallet is removed keep their
+    /// deposits and weight, but can't deposit more.
+    ///
+    /// `create_voter`, `deposit`, `deposit_for` and `deposit_sol` then need
+    /// the voter authority's `AllowlistEntry` as an additional account.
+    pub fn configure_allowlist_required(
+        ctx: Context<ConfigureAllowlistRequired>,
+        allowlist_required: bool,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.allowlist_required = allowlist_required;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Adds `wallet` to the registrar's allowlist.
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, wallet: Pubkey) -> Result<()> {
+        let entry = &mut ctx.accounts.allowlist_entry;
+        entry.registrar = ctx.accounts.registrar.key();
+        entry.wallet = wallet;
+        Ok(())
+    }
+
+    /// Removes a wallet from the registrar's allowlist.
+    pub fn remove_from_allowlist(_ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Adds `wallet` to the registrar's blocklist, for example an exchange
+    /// hot wallet that shouldn't gain governance power with customer funds.
+    /// The wallet can't create a voter or deposit anymore, but can still
+    /// withdraw.
+    ///
+    /// While the blocklist isn't empty, `create_voter`, `deposit`,
+    /// `deposit_for` and `deposit_sol` need the address of the voter
+    /// authority's `BlocklistEntry` as an additional account, whether or not
+    /// the entry exists.
+    pub fn add_to_blocklist(ctx: Context<AddToBlocklist>, wallet: Pubkey) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.blocklist_len = registrar
+            .blocklist_len
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let entry = &mut ctx.accounts.blocklist_entry;
+        entry.registrar = ctx.accounts.registrar.key();
+        entry.wallet = wallet;
+        Ok(())
+    }
+
+    /// Removes a wallet from the registrar's blocklist.
+    pub fn remove_from_blocklist(ctx: Context<RemoveFromBlocklist>) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.blocklist_len = registrar
+            .blocklist_len
+            .checked_sub(1)
+            .ok_or(ErrorCode::Underflow)?;
+        Ok(())
+    }
+
+    /// Requires voter authorities to hold a gateway token of
+    /// `gatekeeper_network`, issued by `gatekeeper_program`, to create a
+    /// voter. That limits voting to wallets that passed the network's
+    /// checks, such as a proof of personhood against sybils, or KYC.
+    ///
+    /// `create_voter` then needs the gateway token as an additional account.
+    /// Passing the default pubkey as `gatekeeper_network` turns this off.
+    pub fn configure_gatekeeper(
+        ctx: Context<ConfigureGatekeeper>,
+        gatekeeper_program: Pubkey,
+        gatekeeper_network: Pubkey,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.gatekeeper_program = gatekeeper_program;
+        registrar.gatekeeper_network = gatekeeper_network;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Boosts the weight of voters holding a verified NFT of the Metaplex
+    /// collection `nft_collection` by `nft_boost_bps` basis points. Passing
+    /// the default pubkey as `nft_collection` turns the boost off.
+    ///
+    /// The boost is applied by `update_voter_weight_record` when it is passed
+    /// the voter authority's token account holding the NFT and the NFT's
+    /// metadata account.
+    pub fn configure_nft_boost(
+        ctx: Context<ConfigureNftBoost>,
+        nft_collection: Pubkey,
+        nft_boost_bps: u16,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.nft_collection = nft_collection;
+        registrar.nft_boost_bps = nft_boost_bps;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Chains this addin after `upstream_addin`, as in a plugin stack: the
+    /// voter weight from the upstream addin's `VoterWeightRecord` is added
+    /// to the deposit weight, or the larger of both is used, depending on
+    /// `upstream_combination`. Passing the default pubkey as
+    /// `upstream_addin` turns this off.
+    ///
+    /// `update_voter_weight_record` then needs the voter's upstream record,
+    /// updated in the same slot, as an additional account. The
+    /// `MaxVoterWeightRecord` doesn't include upstream weight.
+    pub fn configure_upstream_addin(
+        ctx: Context<ConfigureUpstreamAddin>,
+        upstream_addin: Pubkey,
+        upstream_combination: WeightCombination,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.upstream_addin = upstream_addin;
+        registrar.upstream_combination = upstream_combination;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Lets voters unlock their locked tokens before the lockup ends with
+    /// `unlock_early`, forfeiting `early_unlock_penalty_bps` basis points of
+    /// them to the token accounts of `early_unlock_treasury`, for example the
+    /// DAO's treasury governance. Passing the default pubkey as treasury
+    /// turns early unlocks off.
+    pub fn configure_early_unlock(
+        ctx: Context<ConfigureEarlyUnlock>,
+        early_unlock_treasury: Pubkey,
+        early_unlock_penalty_bps: u16,
+    ) -> Result<()> {
+        require!(
+            early_unlock_penalty_bps <= BPS_SCALE,
+            InvalidEarlyUnlockPenalty
+        );
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.early_unlock_treasury = early_unlock_treasury;
+        registrar.early_unlock_penalty_bps = early_unlock_penalty_bps;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets the withdrawal cooldown. With a non-zero cooldown, tokens must be
+    /// requested with `request_withdraw` and can only be withdrawn after
+    /// `withdrawal_cooldown_secs` have passed.
+    pub fn configure_withdrawal_cooldown(
+        ctx: Context<ConfigureWithdrawalCooldown>,
+        withdrawal_cooldown_secs: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.withdrawal_cooldown_secs = withdrawal_cooldown_secs;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets the undelegate cooldown. With a non-zero cooldown, delegations
+    /// must be ended with `request_undelegate` and keep counting for the
+    /// delegate until `undelegate` after `undelegate_cooldown_secs`, so
+    /// delegates can rely on their weight for the duration of a vote.
+    pub fn configure_undelegate_cooldown(
+        ctx: Context<ConfigureUndelegateCooldown>,
+        undelegate_cooldown_secs: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.undelegate_cooldown_secs = undelegate_cooldown_secs;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Switches the membership mode on or off: with it, voters can only
+    /// withdraw what the registrar authority approved with
+    /// `approve_withdrawal`.
+    pub fn configure_withdrawal_approval(
+        ctx: Context<ConfigureWithdrawalApproval>,
+        withdrawals_require_approval: bool,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.withdrawals_require_approval = withdrawals_require_approval;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Allows the voter to withdraw `amount` native tokens in membership
+    /// mode, replacing any earlier approval. Zero revokes it.
+    ///
+    /// Meant to be executed by a governance proposal after handing the
+    /// registrar to the governance. The rent of the voter's
+    /// `WithdrawalApproval` is paid by the registrar's rent sponsor.
+    pub fn approve_withdrawal(ctx: Context<ApproveWithdrawal>, amount: u64) -> Result<()> {
+        let registrar_key = ctx.accounts.registrar.key();
+        let voter_key = ctx.accounts.voter.key();
+        let approval_info = ctx.accounts.withdrawal_approval.to_account_info();
+        if approval_info.data_is_empty() {
+            let (_, approval_bump) =
+                seeds::find_withdrawal_approval_address(&registrar_key, &voter_key);
+            let (_, sponsor_bump) = seeds::find_rent_sponsor_address(&registrar_key);
+            rent::create_account_from_sponsor(
+                &approval_info,
+                &ctx.accounts.rent_sponsor.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                WithdrawalApproval::LEN,
+                &[
+                    registrar_key.as_ref(),
+                    seeds::WITHDRAWAL_APPROVAL.as_ref(),
+                    voter_key.as_ref(),
+                    &[approval_bump],
+                ],
+                &[
+                    registrar_key.as_ref(),
+                    seeds::RENT_SPONSOR.as_ref(),
+                    &[sponsor_bump],
+                ],
+            )?;
+        }
+
+        let approval = WithdrawalApproval {
+            registrar: registrar_key,
+            voter: voter_key,
+            amount,
+        };
+        approval.try_serialize(&mut &mut approval_info.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /// Pauses or resumes deposits by voters, for incident response or when
+    /// the realm stops using the addin. Withdrawals are never paused, and
+    /// the registrar authority can still `grant`.
+    pub fn configure_deposits_paused(
+        ctx: Context<ConfigureDepositsPaused>,
+        deposits_paused: bool,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.deposits_paused = deposits_paused;
+        registrar.bump_config_generation();
+        Ok(())
+    }
 
+    /// Sets the share of the voting weight, in basis points, that counts for
+    /// `action`. For example, 5000 for `CreateProposal` halves the weight
+    /// that counts towards the proposal creation threshold.
+    pub fn configure_weight_action(
+        ctx: Context<ConfigureWeightAction>,
+        action: VoterWeightAction,
+        weight_bps: u16,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.weight_action_bps[action as usize] = weight_bps;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Limits the voting weight for the proposal or governance `target` to
+    /// `max_weight`. Passing the default pubkey as `target` clears the cap
+    /// at `idx`.
+    pub fn configure_weight_target_cap(
+        ctx: Context<ConfigureWeightTargetCap>,
+        idx: u8,
+        target: Pubkey,
+        max_weight: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let idx = idx as usize;
+        require!(idx < registrar.weight_target_caps.len(), InvalidIndex);
+        registrar.weight_target_caps[idx] = WeightTargetCap { target, max_weight };
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Switches to vote escrow weight, where only locked tokens count and
+    /// their weight decays linearly with the remaining lockup time, or back
+    /// to the deposited amount plus lockup bonus.
+    ///
+    /// Unlocked deposits have no weight with vote escrow weight, so voters
+    /// keep their tokens locked to keep voting power.
+    pub fn configure_vote_escrow_weight(
+        ctx: Context<ConfigureVoteEscrowWeight>,
+        vote_escrow_weight: bool,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.vote_escrow_weight = vote_escrow_weight;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets which deposits count towards the weight for `action`, for
+    /// example only locked tokens for `CreateProposal`. Applies to records
+    /// that `update_voter_weight_record` restricts to `action`.
+    pub fn configure_weight_action_rule(
+        ctx: Context<ConfigureWeightAction>,
+        action: VoterWeightAction,
+        rule: WeightRule,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.weight_action_rules[action as usize] = rule;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Switches between linear and quadratic voting weight.
+    ///
+    /// With quadratic weight, a voter's weight is the square root of what it
+    /// would otherwise be, which reduces the influence of large holders.
+    pub fn configure_quadratic_weight(
+        ctx: Context<ConfigureQuadraticWeight>,
+        quadratic_weight: bool,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.quadratic_weight = quadratic_weight;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Caps the voting weight of every voter at `max_voter_weight_per_voter`,
+    /// so no single wallet controls more than that share of the realm's
+    /// voting power. Zero removes the cap.
+    pub fn configure_max_voter_weight_per_voter(
+        ctx: Context<ConfigureMaxVoterWeightPerVoter>,
+        max_voter_weight_per_voter: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.max_voter_weight_per_voter = max_voter_weight_per_voter;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Multiplies all weights written by this addin by `numerator /
+    /// denominator`, to align them with legacy token owner record weights or
+    /// with another addin in the chain. `1 / 1` is no scaling.
+    ///
+    /// Voters using `update_voter_weight_record_cached` must have their
+    /// weight config refreshed afterwards.
+    pub fn configure_weight_scale(
+        ctx: Context<ConfigureWeightScale>,
+        numerator: u64,
+        denominator: u64,
+    ) -> Result<()> {
+        require!(numerator > 0 && denominator > 0, InvalidWeightScale);
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        if numerator == denominator {
+            registrar.weight_scale_numerator = 0;
+            registrar.weight_scale_denominator = 0;
+        } else {
+            registrar.weight_scale_numerator = numerator;
+            registrar.weight_scale_denominator = denominator;
+        }
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Requires voters to escrow `proposal_deposit_native` deposited
+    /// community tokens for every proposal they create, to discourage spam
+    /// proposals. Zero removes the requirement.
+    ///
+    /// With a deposit, `update_voter_weight_record` writes zero weight for
+    /// creating proposals, and for records not restricted to an action.
+    /// Proposals are created with the weight `escrow_proposal_deposit`
+    /// writes instead. Voters using `update_voter_weight_record_cached` must
+    /// have their weight config refreshed afterwards.
+    pub fn configure_proposal_deposit(
+        ctx: Context<ConfigureProposalDeposit>,
+        proposal_deposit_native: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.proposal_deposit_native = proposal_deposit_native;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Makes voters lock tokens for each proposal they vote on, with
+    /// `lock_for_proposal` or `lock_entries_for_proposal`. Only the locked
+    /// tokens count for the vote and only they are held back until the
+    /// proposal ends, so voting doesn't keep the voter's other deposits from
+    /// being withdrawn.
+    ///
+    /// With proposal locking, `update_voter_weight_record` writes zero
+    /// weight for casting votes, and for records not restricted to an
+    /// action. Voters using `update_voter_weight_record_cached` must have
+    /// their weight config refreshed afterwards.
+    pub fn configure_proposal_locking(
+        ctx: Context<ConfigureProposalLocking>,
+        proposal_locking: bool,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.proposal_locking = proposal_locking;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Makes voting weight the time-weighted average over the trailing
+    /// `twab_window_secs`, so tokens deposited shortly before a vote count
+    /// only with the share of the window they were held. Withdrawn tokens
+    /// stop counting immediately. Zero switches back to the current weight.
+    pub fn configure_twab_window(
+        ctx: Context<ConfigureTwabWindow>,
+        twab_window_secs: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.twab_window_secs = twab_window_secs;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Configures how much extra voting weight locked tokens earn.
+    ///
+    /// Tokens locked for `lockup_saturation_secs` or longer get
+    /// `max_extra_lockup_vote_weight_factor / LOCKUP_FACTOR_SCALE` times
+    /// their amount as extra weight; shorter lockups get proportionally less.
+    pub fn configure_lockup_params(
+        ctx: Context<ConfigureLockupParams>,
+        lockup_saturation_secs: u64,
+        max_extra_lockup_vote_weight_factor: u64,
+    ) -> Result<()> {
+        require!(lockup_saturation_secs > 0, InvalidLockupSaturation);
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.lockup_saturation_secs = lockup_saturation_secs;
+        registrar.max_extra_lockup_vote_weight_factor = max_extra_lockup_vote_weight_factor;
+        registrar.bump_config_generation();
         Ok(())
     }
 
     /// Creates a new voter account. There can only be a single voter per
     /// user wallet.
-    pub fn create_voter(
-        ctx: Context<CreateVoter>,
+    ///
+    /// If the authority's spl-governance token owner record address is
+    /// passed in the remaining accounts and the record doesn't exist yet, it
+    /// is created too, saving UIs an extra transaction before the first
+    /// withdraw. This needs the realm, the community mint and the governance
+    /// program among the remaining accounts.
+    pub fn create_voter<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateVoter<'info>>,
         voter_bump: u8,
         voter_weight_record_bump: u8,
     ) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+
         // Forbid creating voter accounts from CPI. The goal is to make automation
         // impossible that weakens some of the limitations intentionally imposed on
-        // locked tokens.
-        {
-            use anchor_lang::solana_program::sysvar::instructions as tx_instructions;
-            let ixns = ctx.accounts.instructions.to_account_info();
-            let current_index = tx_instructions::load_current_index_checked(&ixns)? as usize;
-            let current_ixn = tx_instructions::load_instruction_at_checked(current_index, &ixns)?;
-            require!(
-                current_ixn.program_id == *ctx.program_id,
-                ErrorCode::ForbiddenCpi
-            );
-        }
+        // locked tokens. Programs on the registrar's allowlist are trusted not to.
+        registrar.check_not_cpi(ctx.program_id, &ctx.accounts.instructions.to_account_info())?;
+
+        let now_ts = registrar.clock_unix_timestamp()?;
+        registrar.check_depositor(
+            &ctx.accounts.registrar.key(),
+            &ctx.accounts.authority.key(),
+            ctx.remaining_accounts,
+        )?;
+        registrar.check_gateway_token(
+            &ctx.accounts.authority.key(),
+            ctx.remaining_accounts,
+            now_ts,
+        )?;
+        registrar.create_token_owner_record_if_missing(
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.remaining_accounts,
+        )?;
 
         // Load accounts.
-        let registrar = &ctx.accounts.registrar.load()?;
         let voter = &mut ctx.accounts.voter.load_init()?;
         let voter_weight_record = &mut ctx.accounts.voter_weight_record;
 
         // Init the voter.
-        voter.voter_bump = voter_bump;
-        voter.voter_weight_record_bump = voter_weight_record_bump;
-        voter.authority = ctx.accounts.authority.key();
-        voter.registrar = ctx.accounts.registrar.key();
+        voter.init(
+            registrar,
+            ctx.accounts.registrar.key(),
+            ctx.accounts.authority.key(),
+            voter_bump,
+            voter_weight_record_bump,
+            ctx.accounts.payer.key(),
+        )?;
 
         // Init the voter weight record.
-        voter_weight_record.account_type = VoterWeightAccountType::VoterWeightRecord;
         voter_weight_record.realm = registrar.realm;
         voter_weight_record.governing_token_mint = registrar.realm_community_mint;
         voter_weight_record.governing_token_owner = ctx.accounts.authority.key();
@@ -96,91 +814,2495 @@ pub mod voter_weight_addin_deposits {
         Ok(())
     }
 
-    /// Creates a new deposit entry and updates it by transferring in tokens.
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        // Load accounts.
-        let voter = &mut ctx.accounts.voter.load_mut()?;
+    /// Like `create_voter`, but the rent of the voter and its
+    /// `VoterWeightRecord` is paid by the registrar's rent sponsor, a SOL
+    /// escrow funded with `fund_rent_sponsor`, so wallets without SOL can
+    /// be onboarded. The rent goes back to the sponsor when the voter is
+    /// closed.
+    ///
+    /// Anyone passing the registrar's allowlist and gateway checks can use
+    /// it, so registrars with a public sponsor should require one of them.
+    /// The token owner record is not created.
+    pub fn create_voter_sponsored(
+        ctx: Context<CreateVoterSponsored>,
+        voter_bump: u8,
+        voter_weight_record_bump: u8,
+    ) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        registrar.check_not_cpi(ctx.program_id, &ctx.accounts.instructions.to_account_info())?;
 
-        voter.amount_deposited += amount;
-        voter.last_deposit_slot = Clock::get()?.slot;
+        let now_ts = registrar.clock_unix_timestamp()?;
+        registrar.check_depositor(
+            &ctx.accounts.registrar.key(),
+            &ctx.accounts.authority.key(),
+            ctx.remaining_accounts,
+        )?;
+        registrar.check_gateway_token(
+            &ctx.accounts.authority.key(),
+            ctx.remaining_accounts,
+            now_ts,
+        )?;
 
-        // Deposit tokens into the registrar.
-        token::transfer(ctx.accounts.transfer_ctx(), amount)?;
+        // Create the accounts with the sponsor's lamports.
+        let registrar_key = ctx.accounts.registrar.key();
+        let authority_key = ctx.accounts.authority.key();
+        let (sponsor_key, sponsor_bump) = seeds::find_rent_sponsor_address(&registrar_key);
+        let sponsor_seeds: &[&[u8]] = &[
+            registrar_key.as_ref(),
+            seeds::RENT_SPONSOR.as_ref(),
+            &[sponsor_bump],
+        ];
+        let voter_info = ctx.accounts.voter.to_account_info();
+        let record_info = ctx.accounts.voter_weight_record.to_account_info();
+        rent::create_account_from_sponsor(
+            &voter_info,
+            &ctx.accounts.rent_sponsor.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Voter::LEN,
+            &[
+                registrar_key.as_ref(),
+                authority_key.as_ref(),
+                &[voter_bump],
+            ],
+            sponsor_seeds,
+        )?;
+        rent::create_account_from_sponsor(
+            &record_info,
+            &ctx.accounts.rent_sponsor.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            VoterWeightRecord::LEN,
+            &[
+                seeds::VOTER_WEIGHT_RECORD.as_ref(),
+                registrar_key.as_ref(),
+                authority_key.as_ref(),
+                &[voter_weight_record_bump],
+            ],
+            sponsor_seeds,
+        )?;
+
+        // Init the voter.
+        let voter_loader = AccountLoader::<Voter>::try_from_unchecked(ctx.program_id, &voter_info)?;
+        {
+            let voter = &mut voter_loader.load_init()?;
+            voter.init(
+                registrar,
+                registrar_key,
+                authority_key,
+                voter_bump,
+                voter_weight_record_bump,
+                sponsor_key,
+            )?;
+            voter.rent_sponsored = true;
+        }
+        voter_info.try_borrow_mut_data()?[..8].copy_from_slice(&Voter::discriminator());
+
+        // Init the voter weight record.
+        let record = VoterWeightRecord {
+            realm: registrar.realm,
+            governing_token_mint: registrar.realm_community_mint,
+            governing_token_owner: authority_key,
+            voter_weight: 0,
+            voter_weight_expiry: None,
+            weight_action: None,
+            weight_action_target: None,
+            reserved: [0; 8],
+        };
+        record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
 
         Ok(())
     }
 
-    /// Withdraws tokens from a deposit entry.
+    /// Moves `lamports` from the payer into the registrar's rent sponsor,
+    /// which pays for `create_voter_sponsored`.
+    pub fn fund_rent_sponsor(ctx: Context<FundRentSponsor>, lamports: u64) -> Result<()> {
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.payer.key(),
+                &ctx.accounts.rent_sponsor.key(),
+                lamports,
+            ),
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.rent_sponsor.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Moves `lamports` out of the registrar's rent sponsor into
+    /// `destination`. Only the registrar authority can do this.
+    pub fn withdraw_rent_sponsor(ctx: Context<WithdrawRentSponsor>, lamports: u64) -> Result<()> {
+        let registrar_key = ctx.accounts.registrar.key();
+        let (_, sponsor_bump) = seeds::find_rent_sponsor_address(&registrar_key);
+        invoke_signed(
+            &system_instruction::transfer(
+                &ctx.accounts.rent_sponsor.key(),
+                &ctx.accounts.destination.key(),
+                lamports,
+            ),
+            &[
+                ctx.accounts.rent_sponsor.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[
+                registrar_key.as_ref(),
+                seeds::RENT_SPONSOR.as_ref(),
+                &[sponsor_bump],
+            ]],
+        )?;
+        Ok(())
+    }
+
+    /// Creates a new, empty deposit entry with a lockup.
     ///
-    /// `amount` is in units of the native currency being withdrawn.
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-        // Load the accounts.
+    /// Tokens deposited into the entry stay locked according to the lockup
+    /// and earn extra voting weight while they are.
+    pub fn create_deposit_entry(
+        ctx: Context<CreateDepositEntry>,
+        args: CreateDepositEntryArgs,
+    ) -> Result<()> {
+        let deposit_entry_index = args.deposit_entry_index();
+        let kind = args.kind();
+        let periods = args.periods();
+        let voting_mint_config_idx = args.voting_mint_config_idx();
+
         let registrar = &ctx.accounts.registrar.load()?;
         let voter = &mut ctx.accounts.voter.load_mut()?;
+        require!(
+            registrar
+                .voting_mints
+                .get(voting_mint_config_idx as usize)
+                .map_or(false, |c| c.in_use()),
+            ExchangeRateEntryNotFound
+        );
+        let deposit = voter.unused_deposit_mut(deposit_entry_index)?;
+        let start_ts = registrar.clock_unix_timestamp()?;
+        *deposit = DepositEntry::new(
+            Lockup::new_from_periods(kind, start_ts, periods)?,
+            voting_mint_config_idx,
+        );
 
-        // Governance may forbid withdraws, for example when engaged in a vote.
-        let token_owner = ctx.accounts.authority.key();
-        use spl_governance::state::token_owner_record;
-        let token_owner_record_address_seeds =
-            token_owner_record::get_token_owner_record_address_seeds(
-                &registrar.realm,
-                &registrar.realm_community_mint,
-                &token_owner,
-            );
-        let token_owner_record_data = token_owner_record::get_token_owner_record_data_for_seeds(
-            &registrar.governance_program_id,
-            &ctx.accounts.token_owner_record.to_account_info(),
-            &token_owner_record_address_seeds,
-        )?;
-        token_owner_record_data.assert_can_withdraw_governing_tokens()?;
+        Ok(())
+    }
 
-        // Must not withdraw in the same slot as depositing, to prevent people
-        // depositing, having the vote weight updated, withdrawing and then
-        // voting.
+    /// Grants locked tokens to a voter, for example as contributor
+    /// compensation that carries voting power from day one.
+    ///
+    /// Creates a new deposit entry with the given lockup in the voter's
+    /// account and funds it from `deposit_token`. Must be signed by the
+    /// registrar's authority or grant authority.
+    pub fn grant(ctx: Context<Grant>, args: GrantArgs) -> Result<()> {
+        let GrantArgs::V1 {
+            deposit_entry_index,
+            kind,
+            start_ts,
+            periods,
+            amount,
+        } = args;
+
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
         require!(
-            voter.last_deposit_slot < Clock::get()?.slot,
-            ErrorCode::InvalidToDepositAndWithdrawInOneSlot
+            registrar.is_grant_authority(&ctx.accounts.grant_authority.key()),
+            InvalidGrantAuthority
         );
+        let voting_mint_config_idx =
+            registrar.voting_mint_config_index(ctx.accounts.deposit_mint.key())?;
+        registrar.voting_mints[voting_mint_config_idx].check_deposit(amount)?;
+        registrar.voting_mints[voting_mint_config_idx].track_deposit(amount)?;
 
-        require!(
-            amount <= voter.amount_deposited,
-            ErrorCode::InsufficientVestedTokens
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.update_twab(registrar, curr_ts)?;
+        let deposit = voter.unused_deposit_mut(deposit_entry_index)?;
+        let start_ts = start_ts.unwrap_or(curr_ts);
+        *deposit = DepositEntry::new(
+            Lockup::new_from_periods(kind, start_ts, periods)?,
+            voting_mint_config_idx as u8,
         );
+        deposit.amount_deposited_native = amount;
+        deposit.amount_initially_locked_native = amount;
+        voter.last_deposit_slot = clock.slot;
+        voter.sync_rewards(registrar, curr_ts)?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
 
-        // Update deposit book keeping.
-        voter.amount_deposited -= amount;
+        token::transfer(ctx.accounts.transfer_ctx(), amount)?;
 
-        // Transfer the tokens to withdraw.
-        token::transfer(
-            ctx.accounts
-                .transfer_ctx()
-                .with_signer(&[&[registrar.realm.as_ref(), &[registrar.bump]]]),
+        emit!(DepositEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            mint: ctx.accounts.deposit_mint.key(),
+            deposit_entry_index,
             amount,
-        )?;
+            deposit_entry_amount: amount,
+        });
 
         Ok(())
     }
 
-    /// Calculates the voting power for the given voter (exactly the number
-    /// of deposited tokens) and writes it into a `VoteWeightRecord` account
-    /// to be used by the SPL governance program.
+    /// Creates an airdrop of `mint` tokens for the merkle tree with `root`,
+    /// whose claims are deposited into deposit entries locked with
+    /// `lockup_periods` days of `lockup_kind`, see `claim_from_merkle`.
     ///
-    /// This "revise" instruction should be called in the same transaction,
-    /// immediately before voting.
-    pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
-        let voter = ctx.accounts.voter.load()?;
+    /// The tokens are held in the distributor vault, which anyone can fund
+    /// with a token transfer. `mint` must be a voting mint of the registrar.
+    pub fn create_merkle_distributor(
+        ctx: Context<CreateMerkleDistributor>,
+        root: [u8; 32],
+        lockup_kind: LockupKind,
+        lockup_periods: u32,
+    ) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        registrar.voting_mint_config_index(ctx.accounts.mint.key())?;
+        // Fail early on lockups that claims couldn't create.
+        Lockup::new_from_periods(lockup_kind, 0, lockup_periods)?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.registrar = ctx.accounts.registrar.key();
+        distributor.root = root;
+        distributor.mint = ctx.accounts.mint.key();
+        distributor.lockup_kind = lockup_kind;
+        distributor.lockup_periods = lockup_periods;
+        distributor.total_claimed = 0;
+        Ok(())
+    }
+
+    /// Claims the voter authority's share of a merkle distribution into a
+    /// new deposit entry of the voter, locked with the distributor's lockup
+    /// starting now. Airdropped tokens thereby arrive committed to voting.
+    ///
+    /// Like `grant`, this doesn't check the depositor restrictions or
+    /// whether deposits are paused; the distribution decides who gets
+    /// tokens.
+    pub fn claim_from_merkle(
+        ctx: Context<ClaimFromMerkle>,
+        args: ClaimFromMerkleArgs,
+    ) -> Result<()> {
+        let ClaimFromMerkleArgs::V1 {
+            index,
+            amount,
+            proof,
+            deposit_entry_index,
+        } = args;
+
+        let claimant = ctx.accounts.authority.key();
+        let distributor = &mut ctx.accounts.distributor;
+        require!(
+            merkle::verify(
+                &proof,
+                distributor.root,
+                merkle::leaf(index, &claimant, amount)
+            ),
+            InvalidMerkleProof
+        );
+        distributor.total_claimed = distributor
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        let mint = distributor.mint;
+        let lockup_kind = distributor.lockup_kind;
+        let lockup_periods = distributor.lockup_periods;
+        let claim_status = &mut ctx.accounts.claim_status;
+        claim_status.claimant = claimant;
+        claim_status.amount = amount;
+
+        let mut registrar_data = ctx.accounts.registrar.load_mut()?;
+        let registrar = &mut *registrar_data;
+        let voting_mint_config_idx = registrar.voting_mint_config_index(mint)?;
+        registrar.voting_mints[voting_mint_config_idx].check_deposit(amount)?;
+        registrar.voting_mints[voting_mint_config_idx].track_deposit(amount)?;
+
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.update_twab(registrar, curr_ts)?;
+        let deposit = voter.unused_deposit_mut(deposit_entry_index)?;
+        *deposit = DepositEntry::new(
+            Lockup::new_from_periods(lockup_kind, curr_ts, lockup_periods)?,
+            voting_mint_config_idx as u8,
+        );
+        deposit.amount_deposited_native = amount;
+        deposit.amount_initially_locked_native = amount;
+        voter.last_deposit_slot = clock.slot;
+        voter.sync_rewards(registrar, curr_ts)?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        let (realm, bump) = (registrar.realm, registrar.bump);
+        drop(registrar_data);
+        token::transfer(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[&[realm.as_ref(), &[bump]]]),
+            amount,
+        )?;
+
+        emit!(DepositEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            mint,
+            deposit_entry_index,
+            amount,
+            deposit_entry_amount: amount,
+        });
+
+        Ok(())
+    }
+
+    /// Sets up rewards for voters: creates the reward vault for
+    /// `reward_mint`, which anyone can fund with a token transfer, and starts
+    /// emitting `reward_rate_per_sec` native tokens per second.
+    ///
+    /// Emissions are shared by voters in proportion to their deposits,
+    /// converted at each mint's rate and digit shift, regardless of lockups.
+    /// A voter's share is updated by every deposit, withdraw and
+    /// `claim_rewards`, so voters with deposits from before should call
+    /// `claim_rewards` once to start earning.
+    pub fn create_reward_vault(
+        ctx: Context<CreateRewardVault>,
+        reward_rate_per_sec: u64,
+    ) -> Result<()> {
+        require!(
+            reward_rate_per_sec <= MAX_REWARD_RATE_PER_SEC,
+            InvalidRewardRate
+        );
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        require!(
+            registrar.reward_mint == Pubkey::default(),
+            RewardsAlreadyConfigured
+        );
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        registrar.update_rewards(curr_ts)?;
+        registrar.reward_mint = ctx.accounts.reward_mint.key();
+        registrar.reward_rate_per_sec = reward_rate_per_sec;
+        Ok(())
+    }
+
+    /// Changes the native reward tokens emitted per second, at most
+    /// `MAX_REWARD_RATE_PER_SEC`. Zero pauses emissions.
+    ///
+    /// Rewards emitted so far accrue at the previous rate, so voters keep
+    /// what they earned before the change.
+    pub fn set_reward_rate(ctx: Context<SetRewardRate>, reward_rate_per_sec: u64) -> Result<()> {
+        require!(
+            reward_rate_per_sec <= MAX_REWARD_RATE_PER_SEC,
+            InvalidRewardRate
+        );
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        require!(
+            registrar.reward_mint != Pubkey::default(),
+            RewardsNotConfigured
+        );
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        registrar.update_rewards(curr_ts)?;
+        let previous_reward_rate_per_sec = registrar.reward_rate_per_sec;
+        registrar.reward_rate_per_sec = reward_rate_per_sec;
+
+        emit!(RewardRateEvent {
+            registrar: ctx.accounts.registrar.key(),
+            previous_reward_rate_per_sec,
+            reward_rate_per_sec,
+        });
+        Ok(())
+    }
+
+    /// Pays the voter's accrued rewards from the reward vault to
+    /// `destination`. If the vault holds less, it pays what is there and the
+    /// rest stays owed.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let mut registrar_data = ctx.accounts.registrar.load_mut()?;
+        let registrar = &mut *registrar_data;
+        require!(
+            registrar.reward_mint != Pubkey::default(),
+            RewardsNotConfigured
+        );
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        voter.sync_rewards(registrar, curr_ts)?;
+
+        let amount = voter.reward_pending.min(ctx.accounts.reward_vault.amount);
+        voter.reward_pending -= amount;
+        // The registrar signs the transfer, which needs it unborrowed.
+        let (realm, bump) = (registrar.realm, registrar.bump);
+        drop(registrar_data);
+        token::transfer(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[&[realm.as_ref(), &[bump]]]),
+            amount,
+        )?;
+
+        emit!(RewardClaimEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            amount,
+            reward_pending: voter.reward_pending,
+        });
+        Ok(())
+    }
+
+    /// Creates the voter's `VoterCheckpoints` account, starting with a
+    /// checkpoint of the current weight.
+    ///
+    /// Afterwards every deposit, grant, withdraw and early unlock appends a
+    /// checkpoint and needs the account among its remaining accounts.
+    pub fn create_checkpoints(ctx: Context<CreateCheckpoints>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        let checkpoints = &mut ctx.accounts.checkpoints.load_init()?;
+        checkpoints.voter = ctx.accounts.voter.key();
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        checkpoints.push(Clock::get()?.slot, voter.weight(registrar, curr_ts)?);
+        voter.checkpoints_enabled = true;
+        Ok(())
+    }
+
+    /// Creates the voter's `DepositLabels` account, with all labels empty.
+    pub fn create_deposit_labels(ctx: Context<CreateDepositLabels>) -> Result<()> {
+        let deposit_labels = &mut ctx.accounts.deposit_labels.load_init()?;
+        deposit_labels.voter = ctx.accounts.voter.key();
+        Ok(())
+    }
+
+    /// Sets the label of the deposit entry at `deposit_entry_index`, which
+    /// must be in use. Shorter labels are padded with zero bytes, and an
+    /// all-zero label clears it.
+    pub fn set_deposit_label(
+        ctx: Context<SetDepositLabel>,
+        deposit_entry_index: u8,
+        label: [u8; DEPOSIT_LABEL_LEN],
+    ) -> Result<()> {
+        let voter = ctx.accounts.voter.load()?;
+        let index = deposit_entry_index as usize;
+        require!(index < voter.deposits.len(), InvalidDepositId);
+        require!(voter.deposits[index].is_used, DepositEntryNotFound);
+        require!(std::str::from_utf8(&label).is_ok(), InvalidDepositLabel);
+        let deposit_labels = &mut ctx.accounts.deposit_labels.load_mut()?;
+        deposit_labels.labels[index] = label;
+        Ok(())
+    }
+
+    /// Allows or forbids tokenizing locked deposit entries with
+    /// `create_position` and transferring them with `transfer_position`.
+    /// Existing positions can always be closed.
+    pub fn configure_position_transfers(
+        ctx: Context<ConfigurePositionTransfers>,
+        position_transfers_allowed: bool,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.position_transfers_allowed = position_transfers_allowed;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Tokenizes the locked deposit entry at `deposit_entry_index` by
+    /// minting an NFT into the voter authority's `holder` token account,
+    /// where it stays frozen.
+    ///
+    /// The `position_mint` must be new: no decimals, no supply and the
+    /// registrar as mint and freeze authority. Clients create it and the
+    /// holder token account in the same transaction.
+    pub fn create_position(ctx: Context<CreatePosition>, deposit_entry_index: u8) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        require!(
+            registrar.position_transfers_allowed,
+            PositionTransfersDisabled
+        );
+
+        let registrar_key = ctx.accounts.registrar.key();
+        let mint = &ctx.accounts.position_mint;
+        require!(
+            mint.decimals == 0
+                && mint.supply == 0
+                && mint.mint_authority == COption::Some(registrar_key)
+                && mint.freeze_authority == COption::Some(registrar_key),
+            InvalidPositionMint
+        );
+
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        require!(
+            !voter.has_position(deposit_entry_index),
+            DepositEntryHasPosition
+        );
+        let deposit = voter.active_deposit_mut(deposit_entry_index)?;
+        require!(deposit.amount_locked(curr_ts) > 0, DepositNotLocked);
+        voter.set_position(deposit_entry_index, true);
+
+        let position = &mut ctx.accounts.position;
+        position.registrar = registrar_key;
+        position.position_mint = mint.key();
+        position.voter = ctx.accounts.voter.key();
+        position.deposit_entry_index = deposit_entry_index;
+
+        let signer_seeds: &[&[u8]] = &[registrar.realm.as_ref(), &[registrar.bump]];
+        token::mint_to(ctx.accounts.mint_to_ctx().with_signer(&[signer_seeds]), 1)?;
+        token::freeze_account(ctx.accounts.freeze_ctx().with_signer(&[signer_seeds]))?;
+        Ok(())
+    }
+
+    /// Moves the deposit entry of a position, with its remaining lockup and
+    /// voting power, into the unused entry `target_deposit_entry_index` of
+    /// `target_voter`. The NFT is burned from the `holder` and minted to the
+    /// target voter authority's `target_holder`.
+    ///
+    /// Like withdrawing, this needs the voter's token owner record and is
+    /// forbidden while governance doesn't allow withdrawing, for the voter
+    /// or for delegates of the entry. Both voters must use the same vault
+    /// shard.
+    pub fn transfer_position(
+        ctx: Context<TransferPosition>,
+        target_deposit_entry_index: u8,
+    ) -> Result<()> {
+        let mut registrar_data = ctx.accounts.registrar.load_mut()?;
+        let registrar = &mut *registrar_data;
+        require!(
+            registrar.position_transfers_allowed,
+            PositionTransfersDisabled
+        );
+
+        TokenOwnerRecordHeader::load(
+            &ctx.accounts.token_owner_record.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm,
+            &registrar.realm_community_mint,
+            &ctx.accounts.authority.key(),
+        )?
+        .assert_can_withdraw_unlocked_tokens(registrar.proposal_locking)?;
+
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        let target_voter = &mut ctx.accounts.target_voter.load_mut()?;
+        require!(!voter.frozen && !target_voter.frozen, VoterFrozen);
+        require!(
+            voter.vault_shard == target_voter.vault_shard,
+            VaultShardMismatch
+        );
+        voter.update_twab(registrar, curr_ts)?;
+        target_voter.update_twab(registrar, curr_ts)?;
+
+        let position = &mut ctx.accounts.position;
+        let index = position.deposit_entry_index;
+        voter.check_entry_delegates_can_withdraw(
+            &ctx.accounts.voter.key(),
+            registrar,
+            index,
+            ctx.remaining_accounts,
+        )?;
+        voter.check_entry_not_locked(index)?;
+        let deposit = voter.active_deposit_mut(index)?;
+        *target_voter.unused_deposit_mut(target_deposit_entry_index)? = *deposit;
+        deposit.amount_deposited_native = 0;
+        deposit.amount_initially_locked_native = 0;
+        deposit.amount_pending_withdrawal = 0;
+        deposit.is_used = false;
+        voter.set_position(index, false);
+        target_voter.set_position(target_deposit_entry_index, true);
+        // Like a deposit, the moved tokens can't be withdrawn in this slot.
+        target_voter.last_deposit_slot = clock.slot;
+        voter.check_proposal_escrow()?;
+        voter.sync_rewards(registrar, curr_ts)?;
+        target_voter.sync_rewards(registrar, curr_ts)?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+        target_voter.write_checkpoint(
+            &ctx.accounts.target_voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        position.voter = ctx.accounts.target_voter.key();
+        position.deposit_entry_index = target_deposit_entry_index;
+
+        // The registrar signs the token instructions, which needs it unborrowed.
+        let (realm, bump) = (registrar.realm, registrar.bump);
+        drop(registrar_data);
+        let signer_seeds: &[&[u8]] = &[realm.as_ref(), &[bump]];
+        token::thaw_account(ctx.accounts.thaw_ctx().with_signer(&[signer_seeds]))?;
+        token::burn(ctx.accounts.burn_ctx(), 1)?;
+        token::mint_to(ctx.accounts.mint_to_ctx().with_signer(&[signer_seeds]), 1)?;
+        token::freeze_account(ctx.accounts.freeze_ctx().with_signer(&[signer_seeds]))?;
+        Ok(())
+    }
+
+    /// Turns a position back into a plain deposit entry of its voter by
+    /// burning the NFT. Needed before the entry can be closed.
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.set_position(ctx.accounts.position.deposit_entry_index, false);
+
+        let signer_seeds: &[&[u8]] = &[registrar.realm.as_ref(), &[registrar.bump]];
+        token::thaw_account(ctx.accounts.thaw_ctx().with_signer(&[signer_seeds]))?;
+        token::burn(ctx.accounts.burn_ctx(), 1)?;
+        Ok(())
+    }
+
+    /// Creates the voter's `VoterDelegations` account, needed to delegate.
+    pub fn create_voter_delegations(ctx: Context<CreateVoterDelegations>) -> Result<()> {
+        let delegations = &mut ctx.accounts.delegations.load_init()?;
+        delegations.voter = ctx.accounts.voter.key();
+        Ok(())
+    }
+
+    /// Creates the `DelegationRecord` the voter needs for delegating to
+    /// `delegate_voter`, which counts as one of the delegate's at most
+    /// `MAX_DELEGATORS` delegators until `undelegate` closes it.
+    pub fn create_delegation_record(ctx: Context<CreateDelegationRecord>) -> Result<()> {
+        let delegate_voter = &mut ctx.accounts.delegate_voter.load_mut()?;
+        require!(
+            delegate_voter.delegators_count < MAX_DELEGATORS,
+            TooManyDelegators
+        );
+        delegate_voter.delegators_count += 1;
+
+        let record = &mut ctx.accounts.delegation_record;
+        record.registrar = ctx.accounts.registrar.key();
+        record.delegator = ctx.accounts.voter.key();
+        record.delegate = ctx.accounts.delegate_voter.key();
+
+        emit!(DelegationCreatedEvent {
+            registrar: record.registrar,
+            delegator: record.delegator,
+            delegate: record.delegate,
+        });
+        Ok(())
+    }
+
+    /// Gives the weight of a share of the deposit entry at
+    /// `deposit_entry_index` to `delegate_voter`, whose
+    /// `update_voter_weight_record` adds it to its own. The share is basis
+    /// points or native tokens of the entry, and replaces an earlier share
+    /// of the same entry for the same delegate. A voter can split its
+    /// weight into up to `MAX_DELEGATIONS` such shares, and the shares of an
+    /// entry can't exceed its deposit.
+    ///
+    /// Like withdrawing, this needs the voter's token owner record and is
+    /// forbidden while governance doesn't allow withdrawing, so the weight
+    /// can't vote twice. Delegates can't delegate themselves. The voter's
+    /// `DelegationRecord` for the delegate must exist.
+    ///
+    /// An earlier share can only grow and keeps its kind. Reducing it goes
+    /// through `undelegate`, which checks the delegate's token owner record,
+    /// so the delegate can't have voted with the weight taken back.
+    pub fn delegate(
+        ctx: Context<Delegate>,
+        deposit_entry_index: u8,
+        share_kind: DelegationShareKind,
+        share: u64,
+    ) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        TokenOwnerRecordHeader::load(
+            &ctx.accounts.token_owner_record.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm,
+            &registrar.realm_community_mint,
+            &ctx.accounts.authority.key(),
+        )?
+        .assert_can_withdraw_unlocked_tokens(registrar.proposal_locking)?;
+        require!(
+            share > 0 && (share_kind == DelegationShareKind::Amount || share <= BPS_SCALE as u64),
+            InvalidDelegationShare
+        );
+
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        let delegate_voter = &ctx.accounts.delegate_voter.load()?;
+        let delegations = &mut ctx.accounts.delegations.load_mut()?;
+        require!(
+            voter.delegators_count == 0 && !delegate_voter.is_delegating(),
+            DelegationChain
+        );
+        voter.active_deposit_mut(deposit_entry_index)?;
+
+        let delegate_key = ctx.accounts.delegate_voter.key();
+        let existing = delegations.delegations.iter().position(|d| {
+            d.delegate == delegate_key && d.deposit_entry_index == deposit_entry_index
+        });
+        let (index, undelegate_requested_ts) = match existing {
+            Some(index) => {
+                let old = &delegations.delegations[index];
+                require!(
+                    old.share_kind == share_kind && old.share <= share,
+                    DelegationShareReduced
+                );
+                (index, old.undelegate_requested_ts)
+            }
+            None => {
+                let index = delegations
+                    .delegations
+                    .iter()
+                    .position(|d| !d.is_used())
+                    .ok_or(ErrorCode::TooManyDelegations)?;
+                voter.delegations_count += 1;
+                (index, 0)
+            }
+        };
+        delegations.delegations[index] = Delegation {
+            delegate: delegate_key,
+            deposit_entry_index,
+            share_kind,
+            share,
+            undelegate_requested_ts,
+        };
+        delegations.check_shares(voter)?;
+
+        emit!(DelegateEvent {
+            registrar: ctx.accounts.registrar.key(),
+            delegator: ctx.accounts.voter.key(),
+            delegate: delegate_key,
+            deposit_entry_index,
+            share_kind,
+            share,
+        });
+        Ok(())
+    }
+
+    /// Starts the undelegate cooldown for all delegations of the voter to
+    /// `delegate_voter`, if the registrar has one configured. They keep
+    /// counting for the delegate until `undelegate`.
+    ///
+    /// The cooldown only gives the delegate notice. What keeps the weight
+    /// from voting twice is the delegate's token owner record, which
+    /// `undelegate` checks.
+    pub fn request_undelegate(ctx: Context<RequestUndelegate>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let delegations = &mut ctx.accounts.delegations.load_mut()?;
+        let delegate_key = ctx.accounts.delegate_voter.key();
+
+        let mut requested = false;
+        for delegation in delegations.delegations.iter_mut() {
+            if delegation.delegate == delegate_key {
+                delegation.undelegate_requested_ts = curr_ts;
+                requested = true;
+            }
+        }
+        require!(requested, DelegationNotFound);
+        Ok(())
+    }
+
+    /// Ends all delegations of the voter to `delegate_voter`, so the voter's
+    /// own record gets that weight again, and closes the
+    /// `DelegationRecord`. With an undelegate cooldown, only once it has
+    /// passed since `request_undelegate`.
+    ///
+    /// Like withdrawing, this is forbidden while the delegate's token owner
+    /// record doesn't allow withdrawing, so the weight can't vote again
+    /// after the delegate voted with it. The record must exist; anyone can
+    /// create it in spl-governance.
+    pub fn undelegate(ctx: Context<Undelegate>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        let delegate_voter = &mut ctx.accounts.delegate_voter.load_mut()?;
+        TokenOwnerRecordHeader::load(
+            &ctx.accounts.delegate_token_owner_record,
+            &registrar.governance_program_id,
+            &registrar.realm,
+            &registrar.realm_community_mint,
+            &delegate_voter.authority,
+        )?
+        .assert_can_withdraw_governing_tokens()?;
+        let delegations = &mut ctx.accounts.delegations.load_mut()?;
+        let delegate_key = ctx.accounts.delegate_voter.key();
+
+        let mut removed = 0;
+        for delegation in delegations.delegations.iter_mut() {
+            if delegation.delegate != delegate_key {
+                continue;
+            }
+            if registrar.undelegate_cooldown_secs > 0 {
+                let cooldown_end_ts = delegation
+                    .undelegate_requested_ts
+                    .checked_add(registrar.undelegate_cooldown_secs as i64)
+                    .ok_or(ErrorCode::Overflow)?;
+                require!(
+                    delegation.undelegate_requested_ts > 0 && curr_ts >= cooldown_end_ts,
+                    UndelegateCooldownActive
+                );
+            }
+            delegation.delegate = Pubkey::default();
+            removed += 1;
+        }
+        voter.delegations_count -= removed;
+        delegate_voter.delegators_count = delegate_voter.delegators_count.saturating_sub(1);
+
+        emit!(UndelegateEvent {
+            registrar: ctx.accounts.registrar.key(),
+            delegator: ctx.accounts.voter.key(),
+            delegate: delegate_key,
+        });
+        Ok(())
+    }
+
+    /// Sets the program at `idx` of the registrar's CPI allowlist. Voters can
+    /// be created and withdraw through CPI from transactions whose top-level
+    /// instruction is for an allowlisted program. The default pubkey clears
+    /// the entry.
+    ///
+    /// The argument isn't called `program_id`, which would shadow the one of
+    /// the generated instruction dispatch.
+    pub fn configure_cpi_allowlist(
+        ctx: Context<ConfigureCpiAllowlist>,
+        idx: u8,
+        allowed_program: Pubkey,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let idx = idx as usize;
+        require!(idx < registrar.cpi_allowlist.len(), InvalidIndex);
+        registrar.cpi_allowlist[idx] = allowed_program;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets the token account at `idx` of the registrar's excluded supply
+    /// accounts. Its balance, usually locked treasury or team tokens, then
+    /// doesn't count towards the circulating supply in
+    /// `update_max_vote_weight`, which spl-governance uses for quorums. The
+    /// default pubkey clears the entry.
+    pub fn configure_excluded_supply_account(
+        ctx: Context<ConfigureExcludedSupplyAccount>,
+        idx: u8,
+        token_account: Pubkey,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let idx = idx as usize;
+        require!(idx < registrar.excluded_supply_accounts.len(), InvalidIndex);
+        registrar.excluded_supply_accounts[idx] = token_account;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Hands control over the registrar to `new_authority`, typically a
+    /// governance PDA of the realm. Every `configure_*` instruction then has
+    /// to be signed by it.
+    pub fn configure_registrar_authority(
+        ctx: Context<ConfigureRegistrarAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.authority = new_authority;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Sets the registrar's grant authority, which may call `grant` in
+    /// addition to the registrar authority.
+    pub fn configure_grant_authority(
+        ctx: Context<ConfigureGrantAuthority>,
+        grant_authority: Pubkey,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.grant_authority = grant_authority;
+        registrar.bump_config_generation();
+        Ok(())
+    }
+
+    /// Freezes a voter, for example a compromised wallet or one under a
+    /// compliance hold. The voter's weight counts as zero and it can't
+    /// deposit, but it can still withdraw unlocked tokens.
+    ///
+    /// The voter weight record keeps its old weight until it is updated
+    /// again, which spl-governance requires before every use.
+    pub fn freeze_voter(ctx: Context<FreezeVoter>) -> Result<()> {
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.frozen = true;
+        Ok(())
+    }
+
+    /// Lifts a freeze set by `freeze_voter`.
+    pub fn unfreeze_voter(ctx: Context<FreezeVoter>) -> Result<()> {
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.frozen = false;
+        Ok(())
+    }
+
+    /// Starts unlocking a deposit entry with a constant lockup.
+    ///
+    /// The entry becomes a cliff lockup that ends after the constant
+    /// lockup's duration, counted from now.
+    pub fn start_unlock(ctx: Context<StartUnlock>, deposit_entry_index: u8) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.update_twab(registrar, curr_ts)?;
+        let deposit = voter.active_deposit_mut(deposit_entry_index)?;
+        require!(
+            deposit.lockup.kind == LockupKind::Constant,
+            InvalidLockupPeriod
+        );
+
+        let duration = deposit.lockup.end_ts - deposit.lockup.start_ts;
+        deposit.lockup = Lockup {
+            start_ts: curr_ts,
+            end_ts: curr_ts.checked_add(duration).ok_or(ErrorCode::Overflow)?,
+            kind: LockupKind::Cliff,
+        };
+        Ok(())
+    }
+
+    /// Restarts the lockup of a deposit entry, to regain the full lockup
+    /// boost without withdrawing and depositing again.
+    ///
+    /// The new lockup starts now, lasts `periods` days and locks everything
+    /// in the entry except tokens requested for withdrawal, which stay
+    /// withdrawable. It may never end earlier than the current one: for
+    /// constant lockups the duration must grow, for others the end.
+    pub fn reset_lockup(
+        ctx: Context<ResetLockup>,
+        deposit_entry_index: u8,
+        periods: u32,
+    ) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.update_twab(registrar, curr_ts)?;
+        let deposit = voter.active_deposit_mut(deposit_entry_index)?;
+        let kind = deposit.lockup.kind;
+        require!(kind != LockupKind::None, InvalidLockupPeriod);
+
+        let lockup = Lockup::new_from_periods(kind, curr_ts, periods)?;
+        if kind == LockupKind::Constant {
+            require!(
+                lockup.end_ts - lockup.start_ts > deposit.lockup.end_ts - deposit.lockup.start_ts,
+                InvalidEndTs
+            );
+        } else {
+            require!(lockup.end_ts > deposit.lockup.end_ts, InvalidEndTs);
+        }
+
+        deposit.lockup = lockup;
+        deposit.amount_initially_locked_native = deposit
+            .amount_deposited_native
+            .checked_sub(deposit.amount_pending_withdrawal)
+            .ok_or(ErrorCode::Underflow)?;
+        Ok(())
+    }
+
+    /// Moves unlocked tokens from one deposit entry of a voter to another of
+    /// the same mint, for example to lock them, without touching the vault.
+    ///
+    /// Tokens moved into an entry with a lockup are locked like deposited
+    /// tokens would be. Tokens can only leave a delegated entry while its
+    /// delegates could withdraw, see `withdraw`.
+    pub fn internal_transfer(
+        ctx: Context<InternalTransfer>,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            source_deposit_entry_index != target_deposit_entry_index,
+            InvalidDepositId
+        );
+        let registrar = &ctx.accounts.registrar.load()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+
+        voter.check_entry_delegates_can_withdraw(
+            &ctx.accounts.voter.key(),
+            registrar,
+            source_deposit_entry_index,
+            ctx.remaining_accounts,
+        )?;
+        voter.check_entry_not_locked(source_deposit_entry_index)?;
+        voter.update_twab(registrar, curr_ts)?;
+        let source = voter.active_deposit_mut(source_deposit_entry_index)?;
+        let voting_mint_config_idx = source.voting_mint_config_idx;
+        require!(
+            amount <= source.amount_available(curr_ts),
+            InsufficientVestedTokens
+        );
+        source.amount_deposited_native = source
+            .amount_deposited_native
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Underflow)?;
+
+        let target = voter.active_deposit_mut(target_deposit_entry_index)?;
+        require!(
+            target.voting_mint_config_idx == voting_mint_config_idx,
+            InvalidMint
+        );
+        target.deposit(amount, curr_ts)?;
+        Ok(())
+    }
+
+    /// Closes an empty deposit entry, so its index can be reused.
+    ///
+    /// Entry 0 can't be closed.
+    pub fn close_deposit_entry(
+        ctx: Context<CloseDepositEntry>,
+        deposit_entry_index: u8,
+    ) -> Result<()> {
+        require!(deposit_entry_index != 0, InvalidDepositId);
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        require!(
+            !voter.has_position(deposit_entry_index),
+            DepositEntryHasPosition
+        );
+        voter.check_entry_not_locked(deposit_entry_index)?;
+        let deposit = voter.active_deposit_mut(deposit_entry_index)?;
+        require!(deposit.amount_deposited_native == 0, VotingTokenNonZero);
+        deposit.is_used = false;
+        Ok(())
+    }
+
+    /// Adds tokens to a deposit entry by transferring them into the vault.
+    ///
+    /// Tokens deposited into an entry with a lockup are locked along with
+    /// the tokens already in it.
+    ///
+    /// The tokens don't need to come from the voter authority: the
+    /// `deposit_authority` can be the owner of the token account or a
+    /// delegate approved on it, so vesting or payroll programs can top up
+    /// a voter's deposits. The token account must not have a close
+    /// authority or a delegate other than the `deposit_authority`.
+    pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
+        let amount = args.amount();
+        check_deposit_source(
+            &ctx.accounts.deposit_token,
+            &ctx.accounts.deposit_authority.key(),
+        )?;
+
+        // Load accounts.
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        registrar.check_depositor(
+            &ctx.accounts.registrar.key(),
+            &voter.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // Reject retries of deposits that already went through.
+        if let Some(nonce) = args.nonce() {
+            require!(nonce > voter.last_deposit_nonce, DuplicateDepositNonce);
+            voter.last_deposit_nonce = nonce;
+        }
+
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let mint = ctx.accounts.deposit_mint.key();
+        let deposit_entry_amount = voter.deposit(
+            registrar,
+            args.deposit_entry_index(),
+            mint,
+            amount,
+            curr_ts,
+            clock.slot,
+        )?;
+        let voting_mint_config_idx = registrar.voting_mint_config_index(mint)?;
+        registrar.voting_mints[voting_mint_config_idx].track_deposit(amount)?;
+        voter.sync_rewards(registrar, curr_ts)?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        // Deposit tokens into the registrar.
+        token::transfer(ctx.accounts.transfer_ctx(), amount)?;
+
+        emit!(DepositEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            mint: ctx.accounts.deposit_mint.key(),
+            deposit_entry_index: args.deposit_entry_index(),
+            amount,
+            deposit_entry_amount,
+        });
+        Ok(())
+    }
+
+    /// Adds tokens from a third party to an existing deposit entry of a
+    /// voter, for example DAO-matched deposits or treasury-funded
+    /// onboarding. The tokens belong to the voter from then on: only the
+    /// voter authority can withdraw them.
+    ///
+    /// Like `deposit` without a nonce, so third parties never interfere with
+    /// the voter's own nonces.
+    pub fn deposit_for(ctx: Context<Deposit>, deposit_entry_index: u8, amount: u64) -> Result<()> {
+        deposit(
+            ctx,
+            DepositArgs::V3 {
+                deposit_entry_index,
+                amount,
+                nonce: None,
+            },
+        )
+    }
+
+    /// Creates several deposit entries with their own lockups and funds them
+    /// with a single transfer of the total from `deposit_token`, for voters
+    /// that split their tokens into lockup tranches.
+    ///
+    /// Each tranche is checked like a separate `create_deposit_entry` and
+    /// `deposit`.
+    pub fn deposit_tranches(
+        ctx: Context<DepositTranches>,
+        args: DepositTranchesArgs,
+    ) -> Result<()> {
+        let DepositTranchesArgs::V1 { tranches } = args;
+        check_deposit_source(
+            &ctx.accounts.deposit_token,
+            &ctx.accounts.deposit_authority.key(),
+        )?;
+
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        registrar.check_depositor(
+            &ctx.accounts.registrar.key(),
+            &voter.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let mint = ctx.accounts.deposit_mint.key();
+        let voting_mint_config_idx = registrar.voting_mint_config_index(mint)?;
+        let mut total: u64 = 0;
+        for tranche in tranches.iter() {
+            let deposit = voter.unused_deposit_mut(tranche.deposit_entry_index)?;
+            *deposit = DepositEntry::new(
+                Lockup::new_from_periods(tranche.kind, curr_ts, tranche.periods)?,
+                voting_mint_config_idx as u8,
+            );
+            let deposit_entry_amount = voter.deposit(
+                registrar,
+                tranche.deposit_entry_index,
+                mint,
+                tranche.amount,
+                curr_ts,
+                clock.slot,
+            )?;
+            registrar.voting_mints[voting_mint_config_idx].track_deposit(tranche.amount)?;
+            total = total
+                .checked_add(tranche.amount)
+                .ok_or(ErrorCode::Overflow)?;
+
+            emit!(DepositEvent {
+                registrar: ctx.accounts.registrar.key(),
+                voter: ctx.accounts.voter.key(),
+                mint,
+                deposit_entry_index: tranche.deposit_entry_index,
+                amount: tranche.amount,
+                deposit_entry_amount,
+            });
+        }
+        voter.sync_rewards(registrar, curr_ts)?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        token::transfer(ctx.accounts.transfer_ctx(), total)?;
+        Ok(())
+    }
+
+    /// Moves the authority's tokens deposited with spl-governance directly
+    /// into a deposit entry of the voter, so voters of a realm that switches
+    /// to this addin don't need to withdraw and deposit again.
+    ///
+    /// Withdraws all governing tokens of the authority's token owner record
+    /// for `deposit_mint` through spl-governance, with the vault as the
+    /// destination, and deposits them like `deposit`. spl-governance
+    /// refuses while the token owner record has outstanding votes.
+    pub fn migrate_governance_deposit(
+        ctx: Context<MigrateGovernanceDeposit>,
+        deposit_entry_index: u8,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        registrar.check_depositor(
+            &ctx.accounts.registrar.key(),
+            &voter.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        let mint = ctx.accounts.deposit_mint.key();
+        let authority = ctx.accounts.authority.key();
+        use spl_governance::state::token_owner_record;
+        let token_owner_record_address_seeds =
+            token_owner_record::get_token_owner_record_address_seeds(
+                &registrar.realm,
+                &mint,
+                &authority,
+            );
+        let amount = token_owner_record::get_token_owner_record_data_for_seeds(
+            &registrar.governance_program_id,
+            &ctx.accounts.token_owner_record.to_account_info(),
+            &token_owner_record_address_seeds,
+        )?
+        .governing_token_deposit_amount;
+
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let deposit_entry_amount = voter.deposit(
+            registrar,
+            deposit_entry_index,
+            mint,
+            amount,
+            curr_ts,
+            clock.slot,
+        )?;
+        let voting_mint_config_idx = registrar.voting_mint_config_index(mint)?;
+        registrar.voting_mints[voting_mint_config_idx].track_deposit(amount)?;
+        voter.sync_rewards(registrar, curr_ts)?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        // The authority's signature carries over to spl-governance.
+        let withdraw_ix = spl_governance::instruction::withdraw_governing_tokens(
+            &registrar.governance_program_id,
+            &registrar.realm,
+            &ctx.accounts.vault.key(),
+            &authority,
+            &mint,
+        );
+        invoke(
+            &withdraw_ix,
+            &[
+                ctx.accounts.realm.to_account_info(),
+                ctx.accounts.governing_token_holding.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.token_owner_record.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.governance_program.to_account_info(),
+            ],
+        )?;
+
+        emit!(DepositEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            mint,
+            deposit_entry_index,
+            amount,
+            deposit_entry_amount,
+        });
+        Ok(())
+    }
+
+    /// Deposits `lamports` of the authority's SOL into a deposit entry for
+    /// wrapped SOL, wrapping them in the vault. Saves voters of realms
+    /// governed by SOL from wrapping it themselves.
+    pub fn deposit_sol(
+        ctx: Context<DepositSol>,
+        deposit_entry_index: u8,
+        lamports: u64,
+    ) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        registrar.check_depositor(
+            &ctx.accounts.registrar.key(),
+            &voter.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let deposit_entry_amount = voter.deposit(
+            registrar,
+            deposit_entry_index,
+            NATIVE_MINT,
+            lamports,
+            curr_ts,
+            clock.slot,
+        )?;
+        let voting_mint_config_idx = registrar.voting_mint_config_index(NATIVE_MINT)?;
+        registrar.voting_mints[voting_mint_config_idx].track_deposit(lamports)?;
+        voter.sync_rewards(registrar, curr_ts)?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        // Send the SOL to the vault and have it counted as wrapped SOL.
+        invoke(
+            &ctx.accounts.transfer_ix(lamports),
+            &[
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        invoke(
+            &ctx.accounts.sync_native_ix(),
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ],
+        )?;
+
+        emit!(DepositEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            mint: NATIVE_MINT,
+            deposit_entry_index,
+            amount: lamports,
+            deposit_entry_amount,
+        });
+        Ok(())
+    }
+
+    /// Starts the withdrawal cooldown for unlocked tokens of a deposit
+    /// entry, if the registrar has one configured.
+    ///
+    /// The requested tokens stop counting towards the voter's weight
+    /// immediately and can be withdrawn once the cooldown has passed.
+    /// Requesting more restarts the cooldown for everything requested.
+    pub fn request_withdraw(
+        ctx: Context<RequestWithdraw>,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.update_twab(registrar, curr_ts)?;
+        let deposit = voter.active_deposit_mut(deposit_entry_index)?;
+        require!(
+            amount <= deposit.amount_available(curr_ts),
+            InsufficientVestedTokens
+        );
+        deposit.amount_pending_withdrawal = deposit
+            .amount_pending_withdrawal
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        deposit.withdrawal_requested_ts = curr_ts;
+        Ok(())
+    }
+
+    /// Unlocks the locked tokens of a deposit entry before its lockup ends.
+    ///
+    /// The registrar's penalty share of the locked tokens is transferred to
+    /// `treasury`; the rest become unlocked and can be withdrawn as usual.
+    /// Like `withdraw`, this is only possible while the voter's token owner
+    /// record allows withdrawing, the records of the entry's delegates too,
+    /// and it writes the reduced weight to the voter's `VoterWeightRecord`.
+    pub fn unlock_early(ctx: Context<UnlockEarly>, deposit_entry_index: u8) -> Result<()> {
+        // The registrar signs the penalty transfer at the end, after its
+        // borrow is released.
+        let mut registrar_data = ctx.accounts.registrar.load_mut()?;
+        let registrar = &mut *registrar_data;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        require!(
+            registrar.early_unlock_treasury != Pubkey::default(),
+            EarlyUnlockDisabled
+        );
+
+        // Governance may forbid withdraws, for example when engaged in a vote.
+        TokenOwnerRecordHeader::load(
+            &ctx.accounts.token_owner_record.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm,
+            &registrar.realm_community_mint,
+            &ctx.accounts.authority.key(),
+        )?
+        .assert_can_withdraw_unlocked_tokens(registrar.proposal_locking)?;
+        voter.check_entry_delegates_can_withdraw(
+            &ctx.accounts.voter.key(),
+            registrar,
+            deposit_entry_index,
+            ctx.remaining_accounts,
+        )?;
+        voter.check_entry_not_locked(deposit_entry_index)?;
+
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        voter.update_twab(registrar, curr_ts)?;
+        let deposit = voter.active_deposit_mut(deposit_entry_index)?;
+        require!(
+            registrar.voting_mints[deposit.voting_mint_config_idx as usize].mint
+                == ctx.accounts.deposit_mint.key(),
+            InvalidMint
+        );
+        let penalty = deposit.unlock_early(registrar.early_unlock_penalty_bps, curr_ts)?;
+        registrar.voting_mints[deposit.voting_mint_config_idx as usize].track_withdraw(penalty);
+        let deposit_entry_amount = deposit.amount_deposited_native;
+        voter.check_proposal_escrow()?;
+        voter.sync_rewards(registrar, curr_ts)?;
+
+        let clock = Clock::get()?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        let record = &mut ctx.accounts.voter_weight_record;
+        let weight = voter.weight_for_action(
+            registrar,
+            record.weight_action,
+            curr_ts,
+            &[None; MAX_VOTING_MINTS],
+        )?;
+        record.voter_weight =
+            registrar.weight_for_action(weight, record.weight_action, record.weight_action_target);
+        record.voter_weight_expiry = Some(clock.slot);
+
+        emit!(EarlyUnlockEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            mint: ctx.accounts.deposit_mint.key(),
+            deposit_entry_index,
+            penalty,
+            deposit_entry_amount,
+        });
+        emit!(VoterWeightUpdated {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+
+        let (realm, bump) = (registrar.realm, registrar.bump);
+        drop(registrar_data);
+        token::transfer(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[&[realm.as_ref(), &[bump]]]),
+            penalty,
+        )?;
+        Ok(())
+    }
+
+    /// Withdraws tokens from a deposit entry.
+    ///
+    /// `amount` is in units of the native currency being withdrawn. Only
+    /// tokens that are no longer locked can be withdrawn, and only into a
+    /// token account of the voter authority. In membership mode, the amount
+    /// is deducted from the voter's `WithdrawalApproval`.
+    ///
+    /// Wrapped SOL can be withdrawn as SOL with `unwrap_sol`: the destination
+    /// is closed into the authority, so it must be a wrapped SOL account of
+    /// the authority that is only used for this.
+    ///
+    /// Also writes the reduced weight to the voter's `VoterWeightRecord`.
+    ///
+    /// If the entry is delegated, the voter's `VoterDelegations` and each
+    /// delegate's voter and token owner record are passed as additional
+    /// accounts, and the delegates' records must allow withdrawing too.
+    pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
+        let amount = args.amount();
+
+        // Load the accounts. The registrar signs the transfer at the end, so
+        // its borrow is released before that.
+        let mut registrar_data = ctx.accounts.registrar.load_mut()?;
+        let registrar = &mut *registrar_data;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+
+        // Like voter creation, withdraws can't be automated by other
+        // programs, unless they are on the registrar's allowlist.
+        registrar.check_not_cpi(ctx.program_id, &ctx.accounts.instructions.to_account_info())?;
+
+        // Governance may forbid withdraws, for example when engaged in a vote.
+        TokenOwnerRecordHeader::load(
+            &ctx.accounts.token_owner_record.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm,
+            &registrar.realm_community_mint,
+            &ctx.accounts.authority.key(),
+        )?
+        .assert_can_withdraw_unlocked_tokens(registrar.proposal_locking)?;
+        voter.check_entry_delegates_can_withdraw(
+            &ctx.accounts.voter.key(),
+            registrar,
+            args.deposit_entry_index(),
+            ctx.remaining_accounts,
+        )?;
+
+        // Must not withdraw in the same slot as depositing, to prevent people
+        // depositing, having the vote weight updated, withdrawing and then
+        // voting.
+        require!(
+            voter.last_deposit_slot < Clock::get()?.slot,
+            ErrorCode::InvalidToDepositAndWithdrawInOneSlot
+        );
+
+        voter.check_entry_not_locked(args.deposit_entry_index())?;
+
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        voter.update_twab(registrar, curr_ts)?;
+        let deposit = voter.active_deposit_mut(args.deposit_entry_index())?;
+        require!(
+            registrar.voting_mints[deposit.voting_mint_config_idx as usize].mint
+                == ctx.accounts.withdraw_mint.key(),
+            InvalidMint
+        );
+        require!(
+            amount <= deposit.amount_unlocked(curr_ts),
+            ErrorCode::InsufficientVestedTokens
+        );
+        registrar.check_withdraw_amount(
+            deposit.voting_mint_config_idx as usize,
+            amount,
+            deposit.amount_deposited_native,
+        )?;
+
+        // With a cooldown, only requested amounts can be withdrawn, and only
+        // once the cooldown has passed.
+        if registrar.withdrawal_cooldown_secs > 0 {
+            require!(
+                amount <= deposit.amount_pending_withdrawal,
+                InsufficientPendingWithdrawal
+            );
+            let cooldown_end_ts = deposit
+                .withdrawal_requested_ts
+                .checked_add(registrar.withdrawal_cooldown_secs as i64)
+                .ok_or(ErrorCode::Overflow)?;
+            require!(curr_ts >= cooldown_end_ts, WithdrawalCooldownActive);
+        }
+
+        // In membership mode, only approved amounts can be withdrawn.
+        if registrar.withdrawals_require_approval {
+            WithdrawalApproval::consume(
+                &ctx.accounts.withdrawal_approval.to_account_info(),
+                amount,
+            )?;
+        }
+
+        // Update deposit book keeping.
+        deposit.amount_deposited_native = deposit
+            .amount_deposited_native
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Underflow)?;
+        let mint_config = &mut registrar.voting_mints[deposit.voting_mint_config_idx as usize];
+        mint_config.check_remaining(deposit.amount_deposited_native)?;
+        mint_config.track_withdraw(amount);
+        deposit.amount_pending_withdrawal =
+            deposit.amount_pending_withdrawal.saturating_sub(amount);
+        let deposit_entry_amount = deposit.amount_deposited_native;
+        voter.check_proposal_escrow()?;
+        voter.sync_rewards(registrar, curr_ts)?;
+
+        let clock = Clock::get()?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        // Update the voter weight record, so it can't be used with the weight
+        // from before the withdraw.
+        let record = &mut ctx.accounts.voter_weight_record;
+        let weight = voter.weight_for_action(
+            registrar,
+            record.weight_action,
+            curr_ts,
+            &[None; MAX_VOTING_MINTS],
+        )?;
+        record.voter_weight =
+            registrar.weight_for_action(weight, record.weight_action, record.weight_action_target);
+        record.voter_weight_expiry = Some(clock.slot);
+
+        emit!(WithdrawEvent {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            mint: ctx.accounts.withdraw_mint.key(),
+            deposit_entry_index: args.deposit_entry_index(),
+            amount,
+            deposit_entry_amount,
+        });
+        emit!(VoterWeightUpdated {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+
+        // Transfer the tokens to withdraw.
+        let (realm, bump) = (registrar.realm, registrar.bump);
+        drop(registrar_data);
+        token::transfer(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[&[realm.as_ref(), &[bump]]]),
+            amount,
+        )?;
+
+        // Unwrap by closing the destination. The authority gets all its SOL,
+        // including the rent.
+        if args.unwrap_sol() {
+            require!(ctx.accounts.withdraw_mint.key() == NATIVE_MINT, InvalidMint);
+            token::close_account(ctx.accounts.close_destination_ctx())?;
+        }
+        Ok(())
+    }
+
+    /// Withdraws the whole balance of every deposit entry of `withdraw_mint`
+    /// that holds less than the mint's minimum withdraw and is fully
+    /// unlocked, so a voter left with such micro-balances can empty its
+    /// entries in one go and close. The entries stay in use, with a zero
+    /// balance.
+    ///
+    /// With a withdrawal cooldown, only balances that were requested and
+    /// whose cooldown has passed are swept.
+    ///
+    /// Delegated entries are swept too, but like with `withdraw` the voter's
+    /// `VoterDelegations` and each of their delegates' voter and token owner
+    /// record are passed as additional accounts, and the delegates' records
+    /// must allow withdrawing.
+    pub fn sweep_dust(ctx: Context<Withdraw>) -> Result<()> {
+        // Load the accounts. Like in withdraw, the registrar's borrow is
+        // released before it signs the transfer.
+        let mut registrar_data = ctx.accounts.registrar.load_mut()?;
+        let registrar = &mut *registrar_data;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+
+        registrar.check_not_cpi(ctx.program_id, &ctx.accounts.instructions.to_account_info())?;
+        TokenOwnerRecordHeader::load(
+            &ctx.accounts.token_owner_record.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm,
+            &registrar.realm_community_mint,
+            &ctx.accounts.authority.key(),
+        )?
+        .assert_can_withdraw_unlocked_tokens(registrar.proposal_locking)?;
+        require!(
+            voter.last_deposit_slot < Clock::get()?.slot,
+            ErrorCode::InvalidToDepositAndWithdrawInOneSlot
+        );
+
+        let idx = registrar.voting_mint_config_index(ctx.accounts.withdraw_mint.key())?;
+        let min_withdraw = registrar.min_withdraw_native[idx];
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        voter.update_twab(registrar, curr_ts)?;
+
+        let locked_entries = voter.proposal_locked_entries;
+        let mut swept = 0u64;
+        for index in 0..voter.deposits.len() {
+            let deposit = &voter.deposits[index];
+            let amount = deposit.amount_deposited_native;
+            if !deposit.is_used
+                || locked_entries[index] > 0
+                || deposit.voting_mint_config_idx as usize != idx
+                || amount == 0
+                || amount >= min_withdraw
+                || deposit.amount_unlocked(curr_ts) < amount
+            {
+                continue;
+            }
+            if registrar.withdrawal_cooldown_secs > 0 {
+                let cooldown_end_ts = deposit
+                    .withdrawal_requested_ts
+                    .checked_add(registrar.withdrawal_cooldown_secs as i64)
+                    .ok_or(ErrorCode::Overflow)?;
+                if deposit.amount_pending_withdrawal < amount || curr_ts < cooldown_end_ts {
+                    continue;
+                }
+            }
+            voter.check_entry_delegates_can_withdraw(
+                &ctx.accounts.voter.key(),
+                registrar,
+                index as u8,
+                ctx.remaining_accounts,
+            )?;
+
+            let deposit = &mut voter.deposits[index];
+            deposit.amount_deposited_native = 0;
+            deposit.amount_pending_withdrawal = 0;
+            swept = swept.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+            emit!(WithdrawEvent {
+                registrar: ctx.accounts.registrar.key(),
+                voter: ctx.accounts.voter.key(),
+                mint: ctx.accounts.withdraw_mint.key(),
+                deposit_entry_index: index as u8,
+                amount,
+                deposit_entry_amount: 0,
+            });
+        }
+        registrar.voting_mints[idx].track_withdraw(swept);
+        voter.check_proposal_escrow()?;
+        if registrar.withdrawals_require_approval {
+            WithdrawalApproval::consume(
+                &ctx.accounts.withdrawal_approval.to_account_info(),
+                swept,
+            )?;
+        }
+        voter.sync_rewards(registrar, curr_ts)?;
+
+        let clock = Clock::get()?;
+        voter.write_checkpoint(
+            &ctx.accounts.voter.key(),
+            registrar,
+            curr_ts,
+            clock.slot,
+            ctx.remaining_accounts,
+        )?;
+
+        let record = &mut ctx.accounts.voter_weight_record;
+        let weight = voter.weight_for_action(
+            registrar,
+            record.weight_action,
+            curr_ts,
+            &[None; MAX_VOTING_MINTS],
+        )?;
+        record.voter_weight =
+            registrar.weight_for_action(weight, record.weight_action, record.weight_action_target);
+        record.voter_weight_expiry = Some(clock.slot);
+        emit!(VoterWeightUpdated {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+
+        let (realm, bump) = (registrar.realm, registrar.bump);
+        drop(registrar_data);
+        token::transfer(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[&[realm.as_ref(), &[bump]]]),
+            swept,
+        )?;
+        Ok(())
+    }
+
+    /// Calculates the voting power for the given voter (the number of
+    /// deposited tokens plus extra weight for locked tokens) and writes it
+    /// into a `VoteWeightRecord` account to be used by the SPL governance
+    /// program.
+    ///
+    /// This "revise" instruction should be called in the same transaction,
+    /// immediately before voting.
+    ///
+    /// Since the weight only depends on on-chain state, anyone may call it,
+    /// for example a crank refreshing records before tallying.
+    ///
+    /// The record is restricted to the given governance action and target,
+    /// and the weight adjusted by the registrar's rules for them.
+    ///
+    /// With an NFT boost configured, the voter authority's token account
+    /// holding a collection NFT and that NFT's metadata account can be
+    /// passed as additional accounts to get the boost. With an upstream
+    /// addin, the voter's upstream record must be passed as well, and so
+    /// must the oracle accounts of voting mints with an oracle.
+    ///
+    /// The voters delegating to the voter are passed as additional accounts
+    /// too, each with its `VoterDelegations`, and the weight they delegate
+    /// is added to the voter's. A voter that delegates passes its own
+    /// `VoterDelegations`, and only gets the weight it doesn't delegate.
+    pub fn update_voter_weight_record(
+        ctx: Context<UpdateVoterWeightRecord>,
+        args: UpdateVoterWeightRecordArgs,
+    ) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        let voter = ctx.accounts.voter.load()?;
+        let record = &mut ctx.accounts.voter_weight_record;
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let prices = registrar.oracle_prices(ctx.remaining_accounts, clock.slot)?;
+        let weight = voter.own_weight_for_action(
+            &ctx.accounts.voter.key(),
+            &registrar,
+            args.weight_action(),
+            curr_ts,
+            &prices,
+            ctx.remaining_accounts,
+        )?;
+        let weight =
+            registrar.nft_boosted_weight(weight, &voter.authority, ctx.remaining_accounts)?;
+        let weight = registrar.combine_upstream_weight(
+            weight,
+            &voter.authority,
+            args.weight_action(),
+            args.weight_action_target(),
+            ctx.remaining_accounts,
+            clock.slot,
+        )?;
+        let delegated = voter.delegators_weight(
+            &ctx.accounts.voter.key(),
+            &registrar,
+            args.weight_action(),
+            curr_ts,
+            &prices,
+            ctx.remaining_accounts,
+        )?;
+        let weight = weight.checked_add(delegated).ok_or(ErrorCode::Overflow)?;
+        record.voter_weight =
+            registrar.weight_for_action(weight, args.weight_action(), args.weight_action_target());
+        record.voter_weight_expiry = Some(clock.slot);
+        record.weight_action = args.weight_action();
+        record.weight_action_target = args.weight_action_target();
+
+        emit!(VoterWeightUpdated {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    /// Like `update_voter_weight_record`, computing the weight from the
+    /// registrar settings cached in the voter. The registrar is only read
+    /// for its `config_generation`, so votes can be cast with fewer accounts
+    /// and less compute.
+    ///
+    /// Only works for voters whose registrar needs no other accounts to
+    /// compute weights, see `Registrar::weight_config_complete`. After the
+    /// registrar's settings change, it fails until `refresh_weight_config`
+    /// is called for the voter. Ignores the registrar's time offset. Voters
+    /// that delegate or have delegators need the `VoterDelegations` accounts
+    /// and aren't supported.
+    pub fn update_voter_weight_record_cached(
+        ctx: Context<UpdateVoterWeightRecordCached>,
+        args: UpdateVoterWeightRecordArgs,
+    ) -> Result<()> {
+        let voter = ctx.accounts.voter.load()?;
+        require!(voter.weight_config_cached, WeightConfigNotCached);
+        let config_generation = ctx.accounts.registrar.load()?.config_generation;
+        require!(
+            voter.weight_config_generation == config_generation,
+            WeightConfigStale
+        );
+        require!(
+            !voter.is_delegating() && voter.delegators_count == 0,
+            DelegatedWeightUnsupported
+        );
+        let record = &mut ctx.accounts.voter_weight_record;
+        let clock = Clock::get()?;
+        let config = voter.weight_config;
+        let weight =
+            voter.weight_with_config(&config, clock.unix_timestamp, &[None; MAX_VOTING_MINTS])?;
+        record.voter_weight = config.weight_for_action(weight, args.weight_action());
+        record.voter_weight_expiry = Some(clock.slot);
+        record.weight_action = args.weight_action();
+        record.weight_action_target = args.weight_action_target();
+
+        emit!(VoterWeightUpdated {
+            registrar: voter.registrar,
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    /// Copies the registrar's current weight settings into the voter, for
+    /// `update_voter_weight_record_cached`. Anyone may call it.
+    pub fn refresh_weight_config(ctx: Context<RefreshWeightConfig>) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        let mut voter = ctx.accounts.voter.load_mut()?;
+        voter.cache_weight_config(&registrar);
+        Ok(())
+    }
+
+    /// Like `update_voter_weight_record` for many voters at once, so cranks
+    /// can refresh records before tallying with fewer transactions.
+    ///
+    /// The remaining accounts are pairs of a voter and its writable
+    /// `VoterWeightRecord`, plus the oracle accounts of voting mints with an
+    /// oracle. Registrars with an NFT boost or an upstream addin need
+    /// accounts per voter and aren't supported, and neither are voters that
+    /// delegate or have delegators.
+    pub fn update_voter_weight_records(
+        ctx: Context<UpdateVoterWeightRecords>,
+        args: UpdateVoterWeightRecordArgs,
+    ) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        let registrar_key = ctx.accounts.registrar.key();
+        require!(
+            registrar.nft_collection == Pubkey::default()
+                && registrar.upstream_addin == Pubkey::default(),
+            BatchUpdateUnsupported
+        );
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+        let prices = registrar.oracle_prices(ctx.remaining_accounts, clock.slot)?;
+
+        let voter_accounts = ctx
+            .remaining_accounts
+            .iter()
+            .filter(|info| {
+                !registrar
+                    .voting_mints
+                    .iter()
+                    .any(|c| c.oracle == info.key())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        require!(voter_accounts.len() % 2 == 0, InvalidVoterAccounts);
+        for pair in voter_accounts.chunks_exact(2) {
+            let voter_loader = AccountLoader::<Voter>::try_from(&pair[0])?;
+            let voter = voter_loader.load()?;
+            require!(voter.registrar == registrar_key, InvalidVoterAccounts);
+            require!(
+                !voter.is_delegating() && voter.delegators_count == 0,
+                DelegatedWeightUnsupported
+            );
+            let record_address = Pubkey::create_program_address(
+                &[
+                    seeds::VOTER_WEIGHT_RECORD.as_ref(),
+                    registrar_key.as_ref(),
+                    voter.authority.as_ref(),
+                    &[voter.voter_weight_record_bump],
+                ],
+                ctx.program_id,
+            )
+            .map_err(|_| ErrorCode::InvalidVoterAccounts)?;
+            require!(pair[1].key() == record_address, InvalidVoterAccounts);
+            let mut record = Account::<VoterWeightRecord>::try_from(&pair[1])?;
+
+            let weight =
+                voter.weight_for_action(&registrar, args.weight_action(), curr_ts, &prices)?;
+            record.voter_weight = registrar.weight_for_action(
+                weight,
+                args.weight_action(),
+                args.weight_action_target(),
+            );
+            record.voter_weight_expiry = Some(clock.slot);
+            record.weight_action = args.weight_action();
+            record.weight_action_target = args.weight_action_target();
+            record.exit(ctx.program_id)?;
+
+            emit!(VoterWeightUpdated {
+                registrar: registrar_key,
+                voter: voter_loader.key(),
+                voter_weight: record.voter_weight,
+                weight_action: record.weight_action,
+                weight_action_target: record.weight_action_target,
+                slot: clock.slot,
+            });
+        }
+        Ok(())
+    }
+
+    /// Escrows the registrar's proposal deposit from the voter's deposited
+    /// community tokens and writes the voter's weight for creating the
+    /// proposal with index `proposal_index` of `governance` to its
+    /// `VoterWeightRecord`. Must be in the same transaction as
+    /// spl-governance's CreateProposal.
+    ///
+    /// The escrowed tokens keep their voting weight but can't be withdrawn
+    /// until `release_proposal_deposit` is called after the proposal ended.
+    /// Like `update_voter_weight_record`, takes oracle, NFT and upstream
+    /// addin accounts as remaining accounts.
+    pub fn escrow_proposal_deposit(
+        ctx: Context<EscrowProposalDeposit>,
+        proposal_index: u32,
+    ) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+
+        // Only the governance's next proposal, so the weight can't create
+        // another one while the escrowed proposal is never created.
+        let governance = GovernanceHeader::load(
+            &ctx.accounts.governance.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm,
+        )?;
+        require!(
+            governance.proposals_count == proposal_index && ctx.accounts.proposal.data_is_empty(),
+            InvalidProposal
+        );
+
+        let amount = registrar.proposal_deposit_native;
+        require!(
+            voter.proposal_deposit_available()? >= amount,
+            InsufficientProposalDeposit
+        );
+        voter.proposal_escrowed_native = voter
+            .proposal_escrowed_native
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let clock = Clock::get()?;
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.registrar = ctx.accounts.registrar.key();
+        escrow.voter = ctx.accounts.voter.key();
+        escrow.proposal = ctx.accounts.proposal.key();
+        escrow.payer = ctx.accounts.payer.key();
+        escrow.amount = amount;
+        escrow.slot = clock.slot;
+
+        let record = &mut ctx.accounts.voter_weight_record;
+        let action = Some(VoterWeightAction::CreateProposal);
+        let target = Some(ctx.accounts.governance.key());
+        let prices = registrar.oracle_prices(ctx.remaining_accounts, clock.slot)?;
+        let weight = voter.weight_for_action(
+            &registrar,
+            action,
+            registrar.clock_unix_timestamp()?,
+            &prices,
+        )?;
+        let weight =
+            registrar.nft_boosted_weight(weight, &voter.authority, ctx.remaining_accounts)?;
+        let weight = registrar.combine_upstream_weight(
+            weight,
+            &voter.authority,
+            action,
+            target,
+            ctx.remaining_accounts,
+            clock.slot,
+        )?;
+        record.voter_weight = registrar.weight_for_escrowed_action(weight, action, target);
+        record.voter_weight_expiry = Some(clock.slot);
+        record.weight_action = action;
+        record.weight_action_target = target;
+
+        emit!(VoterWeightUpdated {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    /// Releases the tokens escrowed for a proposal once voting on it is over,
+    /// or once the slot of the escrow passed without the proposal being
+    /// created. Anyone can call it; the escrow's rent goes back to its payer.
+    pub fn release_proposal_deposit(ctx: Context<ReleaseProposalDeposit>) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        let escrow = &ctx.accounts.escrow;
+        let proposal = ProposalHeader::load(
+            &ctx.accounts.proposal.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm_community_mint,
+        )?;
+        let released = match proposal {
+            Some(proposal) => proposal.is_final(),
+            None => Clock::get()?.slot > escrow.slot,
+        };
+        require!(released, ProposalNotFinal);
+
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.proposal_escrowed_native = voter
+            .proposal_escrowed_native
+            .checked_sub(escrow.amount)
+            .ok_or(ErrorCode::Underflow)?;
+        Ok(())
+    }
+
+    /// Locks `amount` of the voter's deposited community tokens until voting
+    /// on `proposal` is over, and writes the weight of just these tokens for
+    /// casting a vote on it to the voter's `VoterWeightRecord`. Must be in
+    /// the same transaction as spl-governance's CastVote. The weight is at
+    /// most the voter's weight for voting.
+    ///
+    /// Each voter can lock once per proposal, and tokens locked for
+    /// different proposals don't overlap. Like `update_voter_weight_record`,
+    /// takes the oracle accounts of voting mints with an oracle as remaining
+    /// accounts.
+    pub fn lock_for_proposal(ctx: Context<LockForProposal>, amount: u64) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        require!(registrar.proposal_locking, ProposalLockingDisabled);
+        let proposal = ProposalHeader::load(
+            &ctx.accounts.proposal.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm_community_mint,
+        )?
+        .ok_or(ErrorCode::ProposalNotOpen)?;
+        require!(!proposal.is_final(), ProposalNotOpen);
+
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        require!(
+            voter.proposal_deposit_available()? >= amount,
+            InsufficientProposalDeposit
+        );
+        voter.proposal_locked_native = voter
+            .proposal_locked_native
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let lock = &mut ctx.accounts.lock;
+        lock.registrar = ctx.accounts.registrar.key();
+        lock.voter = ctx.accounts.voter.key();
+        lock.proposal = ctx.accounts.proposal.key();
+        lock.payer = ctx.accounts.payer.key();
+        lock.amount = amount;
+
+        let clock = Clock::get()?;
+        let record = &mut ctx.accounts.voter_weight_record;
+        let action = Some(VoterWeightAction::CastVote);
+        let target = Some(ctx.accounts.proposal.key());
+        let prices = registrar.oracle_prices(ctx.remaining_accounts, clock.slot)?;
+        let voter_weight = voter.weight_for_action(
+            &registrar,
+            action,
+            registrar.clock_unix_timestamp()?,
+            &prices,
+        )?;
+        let locked_weight = registrar.voting_mints[0].weight(amount as u128)?;
+        let weight = locked_weight.min(voter_weight as u128) as u64;
+        record.voter_weight = registrar.weight_for_escrowed_action(weight, action, target);
+        record.voter_weight_expiry = Some(clock.slot);
+        record.weight_action = action;
+        record.weight_action_target = target;
+
+        emit!(VoterWeightUpdated {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    /// Like `lock_for_proposal`, locking the deposit entries with a bit set
+    /// in `deposit_entries` instead of an amount. The weight for the vote is
+    /// the entries' share of the voter's weight for voting, and until voting
+    /// is over no tokens can leave them. The voter's other entries stay
+    /// withdrawable, and entries can be locked for several proposals.
+    pub fn lock_entries_for_proposal(
+        ctx: Context<LockForProposal>,
+        deposit_entries: u32,
+    ) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        require!(registrar.proposal_locking, ProposalLockingDisabled);
+        let proposal = ProposalHeader::load(
+            &ctx.accounts.proposal.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm_community_mint,
+        )?
+        .ok_or(ErrorCode::ProposalNotOpen)?;
+        require!(!proposal.is_final(), ProposalNotOpen);
+
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.lock_entries(deposit_entries, true)?;
+
+        let lock = &mut ctx.accounts.lock;
+        lock.registrar = ctx.accounts.registrar.key();
+        lock.voter = ctx.accounts.voter.key();
+        lock.proposal = ctx.accounts.proposal.key();
+        lock.payer = ctx.accounts.payer.key();
+        lock.deposit_entries = deposit_entries;
+
+        let clock = Clock::get()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
         let record = &mut ctx.accounts.voter_weight_record;
-        record.voter_weight = voter.weight()?;
-        record.voter_weight_expiry = Some(Clock::get()?.slot);
+        let action = Some(VoterWeightAction::CastVote);
+        let target = Some(ctx.accounts.proposal.key());
+        let prices = registrar.oracle_prices(ctx.remaining_accounts, clock.slot)?;
+        let voter_weight = voter.weight_for_action(&registrar, action, curr_ts, &prices)?;
+        let locked_power =
+            voter.entries_power(&registrar, deposit_entries, action, curr_ts, &prices)?;
+        let power = voter.entries_power(&registrar, u32::MAX, action, curr_ts, &prices)?;
+        let weight = if power == 0 {
+            0
+        } else {
+            (voter_weight as u128 * locked_power as u128 / power as u128) as u64
+        };
+        record.voter_weight = registrar.weight_for_escrowed_action(weight, action, target);
+        record.voter_weight_expiry = Some(clock.slot);
+        record.weight_action = action;
+        record.weight_action_target = target;
+
+        emit!(VoterWeightUpdated {
+            registrar: ctx.accounts.registrar.key(),
+            voter: ctx.accounts.voter.key(),
+            voter_weight: record.voter_weight,
+            weight_action: record.weight_action,
+            weight_action_target: record.weight_action_target,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    /// Releases the tokens locked for a proposal once voting on it is over.
+    /// Anyone can call it; the lock's rent goes back to its payer.
+    pub fn release_proposal_lock(ctx: Context<ReleaseProposalLock>) -> Result<()> {
+        let registrar = ctx.accounts.registrar.load()?;
+        let lock = &ctx.accounts.lock;
+        let proposal = ProposalHeader::load(
+            &ctx.accounts.proposal.to_account_info(),
+            &registrar.governance_program_id,
+            &registrar.realm_community_mint,
+        )?;
+        require!(proposal.map_or(true, |p| p.is_final()), ProposalNotFinal);
+
+        let voter = &mut ctx.accounts.voter.load_mut()?;
+        voter.proposal_locked_native = voter
+            .proposal_locked_native
+            .checked_sub(lock.amount)
+            .ok_or(ErrorCode::Underflow)?;
+        if lock.deposit_entries != 0 {
+            voter.lock_entries(lock.deposit_entries, false)?;
+        }
+        Ok(())
+    }
+
+    /// Shifts the program's clock by `time_offset` seconds for everything
+    /// involving the registrar. Lets program tests check lockups without
+    /// waiting; fails unless built with the `localnet-test` feature.
+    pub fn set_time_offset(ctx: Context<SetTimeOffset>, time_offset: i64) -> Result<()> {
+        require!(cfg!(feature = "localnet-test"), TimeOffsetUnavailable);
+        let registrar = &mut ctx.accounts.registrar.load_mut()?;
+        registrar.time_offset = time_offset;
+        Ok(())
+    }
+
+    /// Computes the voter's current weight and sets it as the instruction's
+    /// return data, as a little-endian u64. Changes nothing.
+    ///
+    /// Lets other programs and `simulateTransaction` callers get the weight
+    /// without parsing account data. The registrar's per-action and
+    /// per-target rules are not applied.
+    pub fn get_voter_weight(ctx: Context<GetVoterWeight>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let voter = &ctx.accounts.voter.load()?;
+        let weight = voter.weight(registrar, registrar.clock_unix_timestamp()?)?;
+        anchor_lang::solana_program::program::set_return_data(&weight.to_le_bytes());
+        Ok(())
+    }
+
+    /// Sets the instruction's return data to the `ProgramVersion` of this
+    /// deployment, so clients and migration tooling can tell which version
+    /// of the program they talk to. Takes no accounts and changes nothing.
+    pub fn get_version(_ctx: Context<GetVersion>) -> Result<()> {
+        let version = ProgramVersion::current()
+            .try_to_vec()
+            .map_err(|_| ErrorCode::UnableToConvert)?;
+        anchor_lang::solana_program::program::set_return_data(&version);
+        Ok(())
+    }
+
+    /// Logs the voter's deposits and voting weight. Changes nothing.
+    ///
+    /// Meant to be simulated, to find out why a voter has the weight it has.
+    pub fn log_voter_info(ctx: Context<LogVoterInfo>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let voter = &ctx.accounts.voter.load()?;
+        let curr_ts = registrar.clock_unix_timestamp()?;
+
+        msg!(
+            "voter {} authority {} weight {} frozen {}",
+            ctx.accounts.voter.key(),
+            voter.authority,
+            voter.weight(registrar, curr_ts)?,
+            voter.frozen
+        );
+        for (index, deposit) in voter.deposits.iter().enumerate() {
+            if !deposit.is_used {
+                continue;
+            }
+            let lockup = &deposit.lockup;
+            // The braces copy fields out of the packed accounts, since
+            // formatting takes references.
+            msg!(
+                "deposit {}: mint {} deposited {} locked {} unlocked {} pending withdrawal {} voting power {}",
+                index,
+                registrar.voting_mints[deposit.voting_mint_config_idx as usize].mint,
+                { deposit.amount_deposited_native },
+                deposit.amount_locked(curr_ts),
+                deposit.amount_unlocked(curr_ts),
+                { deposit.amount_pending_withdrawal },
+                deposit.voting_power(registrar, curr_ts)?
+            );
+            msg!(
+                "deposit {}: lockup {:?} from {} to {}, {} periods left",
+                index,
+                lockup.kind,
+                { lockup.start_ts },
+                { lockup.end_ts },
+                lockup.periods_left(curr_ts)
+            );
+        }
+        Ok(())
+    }
+
+    /// Creates the registrar's `MaxVoterWeightRecord`, to be configured as the
+    /// realm's max voter weight addin account.
+    pub fn create_max_voter_weight_record(
+        ctx: Context<CreateMaxVoterWeightRecord>,
+        _max_voter_weight_record_bump: u8,
+    ) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let record = &mut ctx.accounts.max_voter_weight_record;
+        record.realm = registrar.realm;
+        record.governing_token_mint = registrar.realm_community_mint;
+        Ok(())
+    }
+
+    /// Computes the largest possible voting weight and writes it into the
+    /// registrar's `MaxVoterWeightRecord`.
+    ///
+    /// That is the circulating supply of all voting mints converted at their
+    /// rates, with the maximum lockup boost. The mint accounts of all voting
+    /// mints in use must be passed as remaining accounts, in order, followed
+    /// by the token accounts of all excluded supply accounts in use, in
+    /// order.
+    ///
+    /// Like `update_voter_weight_record`, this should be called in the same
+    /// transaction, immediately before the governance instruction using it.
+    pub fn update_max_vote_weight(ctx: Context<UpdateMaxVoteWeight>) -> Result<()> {
+        let registrar = &ctx.accounts.registrar.load()?;
+        let configs = registrar.voting_mints.iter().filter(|c| c.in_use());
+        let mints = ctx
+            .remaining_accounts
+            .iter()
+            .zip(configs.clone())
+            .map(|(info, config)| {
+                require!(info.key() == config.mint, InvalidVotingMintAccounts);
+                Ok(Account::<Mint>::try_from(info)?.into_inner())
+            })
+            .collect::<Result<Vec<Mint>>>()?;
+        let excluded_keys = registrar
+            .excluded_supply_accounts
+            .iter()
+            .filter(|a| **a != Pubkey::default());
+        let excluded = ctx
+            .remaining_accounts
+            .iter()
+            .skip(configs.count())
+            .zip(excluded_keys)
+            .map(|(info, key)| {
+                require!(info.key() == *key, InvalidExcludedSupplyAccounts);
+                Ok(Account::<TokenAccount>::try_from(info)?.into_inner())
+            })
+            .collect::<Result<Vec<TokenAccount>>>()?;
+
+        let record = &mut ctx.accounts.max_voter_weight_record;
+        record.max_voter_weight = registrar.max_vote_weight(&mints, &excluded)?;
+        record.max_voter_weight_expiry = Some(Clock::get()?.slot);
+        Ok(())
+    }
 
+    /// Records the deposits of all voting mints in the registrar's
+    /// `EpochSnapshot` for the current `epoch`, as a canonical participation
+    /// record for reward distribution and analytics.
+    ///
+    /// Anyone can crank this, paying the snapshot's rent. Each epoch can only
+    /// be snapshotted once, so it should be called early in the epoch.
+    pub fn snapshot_epoch(ctx: Context<SnapshotEpoch>, epoch: u64) -> Result<()> {
+        require!(epoch == Clock::get()?.epoch, InvalidEpoch);
+        let registrar = &ctx.accounts.registrar.load()?;
+        let snapshot = &mut ctx.accounts.epoch_snapshot;
+        snapshot.registrar = ctx.accounts.registrar.key();
+        snapshot.epoch = epoch;
+        snapshot.ts = registrar.clock_unix_timestamp()?;
+        for (total, config) in snapshot
+            .total_deposited_native
+            .iter_mut()
+            .zip(registrar.voting_mints.iter())
+        {
+            *total = config.total_deposited_native;
+        }
         Ok(())
     }
 
     /// Closes the voter account, allowing one to retrieve rent exemption SOL.
     /// Only accounts with no remaining deposits can be closed.
+    ///
+    /// The voter's `VoterWeightRecord` is closed as well, with its rent
+    /// refunded to whoever paid for it in `create_voter`.
     pub fn close_voter(ctx: Context<CloseVoter>) -> Result<()> {
         let voter = &ctx.accounts.voter.load()?;
-        require!(voter.amount_deposited == 0, VotingTokenNonZero);
+        require!(voter.amount_deposited_native()? == 0, VotingTokenNonZero);
+        require!(voter.position_entries == 0, DepositEntryHasPosition);
+        require!(
+            !voter.is_delegating() && voter.delegators_count == 0,
+            VoterHasDelegations
+        );
+        require!(
+            !voter.rent_sponsored
+                || ctx.accounts.sol_destination.key() == voter.voter_weight_record_payer,
+            SponsoredRentNotReturned
+        );
+        Ok(())
+    }
+
+    /// Tops up the lamports of an account owned by this program so that it
+    /// is rent exempt again, for example after it was resized.
+    ///
+    /// The payer covers the missing lamports.
+    pub fn top_up_rent(ctx: Context<TopUpRent>) -> Result<()> {
+        rent::top_up_rent_from_payer(
+            &ctx.accounts.account.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )
+    }
+
+    /// Like `top_up_rent`, but the missing lamports are taken from the
+    /// registrar's lamports in excess of its own rent exemption.
+    pub fn top_up_rent_from_registrar(ctx: Context<TopUpRentFromRegistrar>) -> Result<()> {
+        rent::top_up_rent_from_registrar(
+            &ctx.accounts.account.to_account_info(),
+            &ctx.accounts.registrar.to_account_info(),
+        )
+    }
+
+    /// Upgrades a registrar or voter created by an earlier version of the
+    /// program to the current layout, in place. Anyone may call it; accounts
+    /// that are up to date are left unchanged.
+    ///
+    /// The account size stays the same, since the runtime can't resize
+    /// accounts. Layout changes must fit into the reserved bytes.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let info = ctx.accounts.account.to_account_info();
+        let discriminator = info
+            .try_borrow_data()?
+            .get(..8)
+            .map(|d| d.to_vec())
+            .ok_or(ErrorCode::InvalidMigrationAccount)?;
+        if discriminator == Registrar::discriminator() {
+            let loader = AccountLoader::<Registrar>::try_from(&info)?;
+            let mut registrar = loader.load_mut()?;
+            let old_version = registrar.version;
+            registrar.migrate();
+            msg!("registrar version {} -> {}", old_version, REGISTRAR_VERSION);
+        } else if discriminator == Voter::discriminator() {
+            let loader = AccountLoader::<Voter>::try_from(&info)?;
+            let mut voter = loader.load_mut()?;
+            let old_version = voter.version;
+            voter.migrate();
+            msg!("voter version {} -> {}", old_version, VOTER_VERSION);
+        } else {
+            return Err(ErrorCode::InvalidMigrationAccount.into());
+        }
         Ok(())
     }
 }
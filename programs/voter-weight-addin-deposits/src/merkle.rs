@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+/// Leaf of a merkle distribution: `amount` native tokens for `claimant`,
+/// at position `index`.
+pub fn leaf(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &index.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Whether `proof` shows that `leaf` is in the tree with `root`. Each pair
+/// of nodes is hashed in sorted order, so proofs don't need to say on which
+/// side a sibling is.
+pub fn verify(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).0
+        } else {
+            keccak::hashv(&[sibling, &node]).0
+        }
+    });
+    computed == root
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+/// The Metaplex token metadata program.
+pub mod metadata_program {
+    use anchor_lang::declare_id;
+    declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+/// The leading fields of a Metaplex `Metadata` account, up to the
+/// collection.
+#[derive(AnchorDeserialize, Clone, Debug)]
+pub struct Metadata {
+    pub key: u8,
+    pub update_authority: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<Creator>>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    pub collection: Option<Collection>,
+}
+
+#[derive(AnchorDeserialize, Clone, Debug)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(AnchorDeserialize, Clone, Debug)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+/// Address of the Metaplex metadata account of `mint`.
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"metadata".as_ref(),
+            metadata_program::ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &metadata_program::ID,
+    )
+}
+
+/// Whether `accounts` show that `wallet` holds an NFT that is a verified
+/// member of `collection`: a token account of the wallet holding the NFT,
+/// together with the NFT's metadata account.
+pub fn holds_collection_nft<'info>(
+    wallet: &Pubkey,
+    collection: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+) -> bool {
+    accounts
+        .iter()
+        .filter(|info| info.owner == &anchor_spl::token::ID)
+        .filter_map(|info| {
+            let data = info.try_borrow_data().ok()?;
+            let mut data_slice: &[u8] = &data;
+            TokenAccount::try_deserialize(&mut data_slice).ok()
+        })
+        .filter(|token| token.owner == *wallet && token.amount > 0)
+        .any(|token| {
+            let (metadata_address, _) = find_metadata_address(&token.mint);
+            accounts
+                .iter()
+                .find(|info| info.key() == metadata_address)
+                .filter(|info| info.owner == &metadata_program::ID)
+                .and_then(|info| {
+                    let data = info.try_borrow_data().ok()?;
+                    let mut data_slice: &[u8] = &data;
+                    Metadata::deserialize(&mut data_slice).ok()
+                })
+                .and_then(|metadata| metadata.collection)
+                .map_or(false, |c| c.verified && c.key == *collection)
+        })
+}
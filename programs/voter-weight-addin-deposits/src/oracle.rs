@@ -0,0 +1,72 @@
+use crate::account::MAX_VOTING_MINTS;
+use crate::error::*;
+use anchor_lang::prelude::*;
+use std::convert::TryInto;
+
+/// Pyth price accounts start with this.
+const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+/// Pyth account type of price accounts.
+const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+/// Pyth status of a price that is currently trading.
+const PYTH_STATUS_TRADING: u32 = 1;
+
+/// A price older than this many slots is not used.
+pub const MAX_ORACLE_STALENESS_SLOTS: u64 = 25;
+
+/// Prices of the registrar's voting mints, by index. `None` for mints
+/// without an oracle.
+pub type OraclePrices = [Option<OraclePrice>; MAX_VOTING_MINTS];
+
+/// A price from an oracle: `price * 10^expo`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OraclePrice {
+    pub price: u64,
+    pub expo: i32,
+}
+
+impl OraclePrice {
+    /// Reads the aggregate price of a Pyth price account. The price must be
+    /// positive, trading and published at most
+    /// `MAX_ORACLE_STALENESS_SLOTS` before `curr_slot`.
+    pub fn from_pyth(account: &AccountInfo, curr_slot: u64) -> Result<OraclePrice> {
+        let data = account.try_borrow_data()?;
+        require!(data.len() >= 240, InvalidOraclePrice);
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        require!(
+            u32_at(0) == PYTH_MAGIC && u32_at(8) == PYTH_PRICE_ACCOUNT_TYPE,
+            InvalidOraclePrice
+        );
+        let expo = u32_at(20) as i32;
+        let price = u64_at(208) as i64;
+        let status = u32_at(224);
+        let publish_slot = u64_at(232);
+        require!(
+            price > 0 && status == PYTH_STATUS_TRADING && expo.abs() <= 18,
+            InvalidOraclePrice
+        );
+        require!(
+            curr_slot.saturating_sub(publish_slot) <= MAX_ORACLE_STALENESS_SLOTS,
+            StaleOraclePrice
+        );
+        Ok(OraclePrice {
+            price: price as u64,
+            expo,
+        })
+    }
+
+    /// The value of `amount` at this price.
+    pub fn apply(&self, amount: u128) -> Result<u128> {
+        let value = amount
+            .checked_mul(self.price as u128)
+            .ok_or(ErrorCode::Overflow)?;
+        let scale = 10u128.pow(self.expo.unsigned_abs());
+        Ok(if self.expo >= 0 {
+            value.checked_mul(scale).ok_or(ErrorCode::Overflow)?
+        } else {
+            value / scale
+        })
+    }
+}
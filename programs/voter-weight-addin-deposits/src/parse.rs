@@ -0,0 +1,153 @@
+//! Parsing of this program's accounts into plain structs, for indexers and
+//! analytics that read account data off-chain.
+//!
+//! The structs own their fields, aren't packed and contain only the entries
+//! in use, so they can be stored or serialized as they are. Parsing checks
+//! the account discriminator and makes no syscalls.
+//!
+//! Only compiled with the `no-entrypoint` feature.
+
+use crate::account::{LockupKind, Registrar, Voter, VoterWeightAction, VoterWeightRecord};
+use crate::error::*;
+use anchor_lang::prelude::*;
+
+/// A voting mint of a registrar, see `VotingMintConfig`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VotingMintData {
+    /// Index in `Registrar::voting_mints`.
+    pub idx: u8,
+    pub mint: Pubkey,
+    pub rate: u64,
+    pub digit_shift: i8,
+    pub total_deposited_native: u64,
+    /// `None` for mints without an oracle.
+    pub oracle: Option<Pubkey>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegistrarData {
+    pub authority: Pubkey,
+    pub governance_program_id: Pubkey,
+    pub realm: Pubkey,
+    pub realm_community_mint: Pubkey,
+    pub grant_authority: Pubkey,
+    pub voting_mints: Vec<VotingMintData>,
+    pub vault_shard_count: u8,
+    pub lockup_saturation_secs: u64,
+    pub max_extra_lockup_vote_weight_factor: u64,
+    pub withdrawal_cooldown_secs: u64,
+    pub deposits_paused: bool,
+    pub vote_escrow_weight: bool,
+    pub version: u8,
+}
+
+/// A deposit entry of a voter, see `DepositEntry`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepositData {
+    /// Index in `Voter::deposits`.
+    pub idx: u8,
+    pub voting_mint_config_idx: u8,
+    pub amount_deposited_native: u64,
+    pub amount_initially_locked_native: u64,
+    pub amount_pending_withdrawal: u64,
+    pub lockup_kind: LockupKind,
+    pub lockup_start_ts: i64,
+    pub lockup_end_ts: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoterData {
+    pub authority: Pubkey,
+    pub registrar: Pubkey,
+    pub vault_shard: u8,
+    pub deposits: Vec<DepositData>,
+    pub frozen: bool,
+    pub reward_pending: u64,
+    pub version: u8,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoterWeightRecordData {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub voter_weight: u64,
+    pub voter_weight_expiry: Option<u64>,
+    pub weight_action: Option<VoterWeightAction>,
+    pub weight_action_target: Option<Pubkey>,
+}
+
+pub fn parse_registrar(mut data: &[u8]) -> Result<RegistrarData> {
+    let registrar = Registrar::try_deserialize(&mut data)?;
+    let voting_mints = registrar
+        .voting_mints
+        .iter()
+        .enumerate()
+        .filter(|(_, config)| config.in_use())
+        .map(|(idx, config)| VotingMintData {
+            idx: idx as u8,
+            mint: config.mint,
+            rate: config.rate,
+            digit_shift: config.digit_shift,
+            total_deposited_native: config.total_deposited_native,
+            oracle: Some(config.oracle).filter(|oracle| *oracle != Pubkey::default()),
+        })
+        .collect();
+    Ok(RegistrarData {
+        authority: registrar.authority,
+        governance_program_id: registrar.governance_program_id,
+        realm: registrar.realm,
+        realm_community_mint: registrar.realm_community_mint,
+        grant_authority: registrar.grant_authority,
+        voting_mints,
+        vault_shard_count: registrar.vault_shard_count,
+        lockup_saturation_secs: registrar.lockup_saturation_secs,
+        max_extra_lockup_vote_weight_factor: registrar.max_extra_lockup_vote_weight_factor,
+        withdrawal_cooldown_secs: registrar.withdrawal_cooldown_secs,
+        deposits_paused: registrar.deposits_paused,
+        vote_escrow_weight: registrar.vote_escrow_weight,
+        version: registrar.version,
+    })
+}
+
+pub fn parse_voter(mut data: &[u8]) -> Result<VoterData> {
+    let voter = Voter::try_deserialize(&mut data)?;
+    let deposits = voter
+        .deposits
+        .iter()
+        .enumerate()
+        .filter(|(_, deposit)| deposit.is_used)
+        .map(|(idx, deposit)| DepositData {
+            idx: idx as u8,
+            voting_mint_config_idx: deposit.voting_mint_config_idx,
+            amount_deposited_native: deposit.amount_deposited_native,
+            amount_initially_locked_native: deposit.amount_initially_locked_native,
+            amount_pending_withdrawal: deposit.amount_pending_withdrawal,
+            lockup_kind: deposit.lockup.kind,
+            lockup_start_ts: deposit.lockup.start_ts,
+            lockup_end_ts: deposit.lockup.end_ts,
+        })
+        .collect();
+    Ok(VoterData {
+        authority: voter.authority,
+        registrar: voter.registrar,
+        vault_shard: voter.vault_shard,
+        deposits,
+        frozen: voter.frozen,
+        reward_pending: voter.reward_pending,
+        version: voter.version,
+    })
+}
+
+pub fn parse_voter_weight_record(mut data: &[u8]) -> Result<VoterWeightRecordData> {
+    let record = VoterWeightRecord::try_deserialize(&mut data)?;
+    Ok(VoterWeightRecordData {
+        realm: record.realm,
+        governing_token_mint: record.governing_token_mint,
+        governing_token_owner: record.governing_token_owner,
+        voter_weight: record.voter_weight,
+        voter_weight_expiry: record.voter_weight_expiry,
+        weight_action: record.weight_action,
+        weight_action_target: record.weight_action_target,
+    })
+}
@@ -0,0 +1,89 @@
+use crate::error::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    program::{invoke, invoke_signed},
+    system_instruction,
+};
+
+/// Lamports `account` is missing to be rent exempt at its current size.
+pub fn rent_shortfall(account: &AccountInfo) -> Result<u64> {
+    let minimum_balance = Rent::get()?.minimum_balance(account.data_len());
+    Ok(minimum_balance.saturating_sub(account.lamports()))
+}
+
+/// Transfers the lamports `account` needs to be rent exempt from `payer`.
+pub fn top_up_rent_from_payer<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let shortfall = rent_shortfall(account)?;
+    if shortfall == 0 {
+        return Ok(());
+    }
+    require!(payer.lamports() >= shortfall, RentPayerUnderfunded);
+    invoke(
+        &system_instruction::transfer(payer.key, account.key, shortfall),
+        &[payer.clone(), account.clone(), system_program.clone()],
+    )?;
+    Ok(())
+}
+
+/// Moves the lamports `account` needs to be rent exempt out of the
+/// registrar's lamports in excess of its own rent exemption.
+///
+/// Both accounts must be owned by this program.
+pub fn top_up_rent_from_registrar(account: &AccountInfo, registrar: &AccountInfo) -> Result<()> {
+    require!(account.key != registrar.key, InvalidRentTopUpTarget);
+    let shortfall = rent_shortfall(account)?;
+    if shortfall == 0 {
+        return Ok(());
+    }
+    let registrar_minimum_balance = Rent::get()?.minimum_balance(registrar.data_len());
+    let registrar_excess = registrar
+        .lamports()
+        .saturating_sub(registrar_minimum_balance);
+    require!(
+        registrar_excess >= shortfall,
+        RegistrarRentReserveUnderfunded
+    );
+    let registrar_lamports = registrar
+        .lamports()
+        .checked_sub(shortfall)
+        .ok_or(ErrorCode::Underflow)?;
+    let account_lamports = account
+        .lamports()
+        .checked_add(shortfall)
+        .ok_or(ErrorCode::Overflow)?;
+    **registrar.try_borrow_mut_lamports()? = registrar_lamports;
+    **account.try_borrow_mut_lamports()? = account_lamports;
+    Ok(())
+}
+
+/// Creates `account`, an address of this program with `account_seeds`, with
+/// `space` bytes of data owned by this program. The rent is paid by
+/// `sponsor`, a system account at an address of this program with
+/// `sponsor_seeds`.
+pub fn create_account_from_sponsor<'info>(
+    account: &AccountInfo<'info>,
+    sponsor: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    account_seeds: &[&[u8]],
+    sponsor_seeds: &[&[u8]],
+) -> Result<()> {
+    let lamports = Rent::get()?.minimum_balance(space);
+    require!(sponsor.lamports() >= lamports, RentPayerUnderfunded);
+    invoke_signed(
+        &system_instruction::create_account(
+            sponsor.key,
+            account.key,
+            lamports,
+            space as u64,
+            &crate::ID,
+        ),
+        &[sponsor.clone(), account.clone(), system_program.clone()],
+        &[sponsor_seeds, account_seeds],
+    )?;
+    Ok(())
+}
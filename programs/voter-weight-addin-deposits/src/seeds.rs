@@ -0,0 +1,202 @@
+use anchor_lang::prelude::*;
+
+pub const VOTER_WEIGHT_RECORD: [u8; 19] = *b"voter-weight-record";
+pub const MAX_VOTER_WEIGHT_RECORD: [u8; 23] = *b"max-voter-weight-record";
+pub const VAULT: [u8; 5] = *b"vault";
+pub const ALLOWLIST: [u8; 9] = *b"allowlist";
+pub const BLOCKLIST: [u8; 9] = *b"blocklist";
+pub const REWARD_VAULT: [u8; 12] = *b"reward-vault";
+pub const CHECKPOINTS: [u8; 11] = *b"checkpoints";
+pub const MERKLE_DISTRIBUTOR: [u8; 18] = *b"merkle-distributor";
+pub const DISTRIBUTOR_VAULT: [u8; 17] = *b"distributor-vault";
+pub const CLAIM_STATUS: [u8; 12] = *b"claim-status";
+pub const RENT_SPONSOR: [u8; 12] = *b"rent-sponsor";
+pub const PROPOSAL_DEPOSIT: [u8; 16] = *b"proposal-deposit";
+pub const WITHDRAWAL_APPROVAL: [u8; 19] = *b"withdrawal-approval";
+pub const DEPOSIT_LABELS: [u8; 14] = *b"deposit-labels";
+pub const POSITION: [u8; 8] = *b"position";
+pub const VOTER_DELEGATIONS: [u8; 17] = *b"voter-delegations";
+pub const DELEGATION_RECORD: [u8; 17] = *b"delegation-record";
+pub const PROPOSAL_LOCK: [u8; 13] = *b"proposal-lock";
+pub const EPOCH_SNAPSHOT: [u8; 14] = *b"epoch-snapshot";
+
+/// Address of the registrar for `realm`.
+pub fn find_registrar_address(realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[realm.as_ref()], &crate::ID)
+}
+
+/// Address of the voter account of `authority` in `registrar`.
+pub fn find_voter_address(registrar: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[registrar.as_ref(), authority.as_ref()], &crate::ID)
+}
+
+/// Address of the `VoterWeightRecord` of `authority` in `registrar`.
+pub fn find_voter_weight_record_address(registrar: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            VOTER_WEIGHT_RECORD.as_ref(),
+            registrar.as_ref(),
+            authority.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Address of the `MaxVoterWeightRecord` of `registrar`.
+pub fn find_max_voter_weight_record_address(registrar: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MAX_VOTER_WEIGHT_RECORD.as_ref(), registrar.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Address of the token account holding the deposited `mint` tokens of
+/// `registrar` for the vault shard with index `shard`.
+pub fn find_vault_address(registrar: &Pubkey, mint: &Pubkey, shard: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[registrar.as_ref(), VAULT.as_ref(), mint.as_ref(), &[shard]],
+        &crate::ID,
+    )
+}
+
+/// Address of the `AllowlistEntry` of `wallet` in `registrar`.
+pub fn find_allowlist_entry_address(registrar: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[registrar.as_ref(), ALLOWLIST.as_ref(), wallet.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Address of the `BlocklistEntry` of `wallet` in `registrar`.
+pub fn find_blocklist_entry_address(registrar: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[registrar.as_ref(), BLOCKLIST.as_ref(), wallet.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Address of the token account holding the rewards of `registrar`.
+pub fn find_reward_vault_address(registrar: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[registrar.as_ref(), REWARD_VAULT.as_ref()], &crate::ID)
+}
+
+/// Address of the `VoterCheckpoints` of `voter`.
+pub fn find_voter_checkpoints_address(voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[voter.as_ref(), CHECKPOINTS.as_ref()], &crate::ID)
+}
+
+/// Address of the `DepositLabels` of `voter`.
+pub fn find_deposit_labels_address(voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[voter.as_ref(), DEPOSIT_LABELS.as_ref()], &crate::ID)
+}
+
+/// Address of the `VoterDelegations` of `voter`.
+pub fn find_voter_delegations_address(voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[voter.as_ref(), VOTER_DELEGATIONS.as_ref()], &crate::ID)
+}
+
+/// Address of the `DelegationRecord` of `delegator` delegating to `delegate`.
+pub fn find_delegation_record_address(delegator: &Pubkey, delegate: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            delegator.as_ref(),
+            DELEGATION_RECORD.as_ref(),
+            delegate.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Address of the `MerkleDistributor` of `registrar` with merkle root `root`.
+pub fn find_merkle_distributor_address(registrar: &Pubkey, root: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            registrar.as_ref(),
+            MERKLE_DISTRIBUTOR.as_ref(),
+            root.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Address of the token account holding the tokens of `distributor`.
+pub fn find_distributor_vault_address(distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[distributor.as_ref(), DISTRIBUTOR_VAULT.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Address of the `ClaimStatus` of the claim at `index` of `distributor`.
+pub fn find_claim_status_address(distributor: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            distributor.as_ref(),
+            CLAIM_STATUS.as_ref(),
+            index.to_le_bytes().as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Address of the system account holding the SOL that pays the rent of
+/// sponsored voters of `registrar`.
+pub fn find_rent_sponsor_address(registrar: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[registrar.as_ref(), RENT_SPONSOR.as_ref()], &crate::ID)
+}
+
+/// Address of the `ProposalDepositEscrow` for `proposal` in `registrar`.
+pub fn find_proposal_deposit_escrow_address(registrar: &Pubkey, proposal: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            registrar.as_ref(),
+            PROPOSAL_DEPOSIT.as_ref(),
+            proposal.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Address of the `ProposalLock` of `voter` for `proposal`.
+pub fn find_proposal_lock_address(voter: &Pubkey, proposal: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[voter.as_ref(), PROPOSAL_LOCK.as_ref(), proposal.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Address of the `EpochSnapshot` of `registrar` for `epoch`.
+pub fn find_epoch_snapshot_address(registrar: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            registrar.as_ref(),
+            EPOCH_SNAPSHOT.as_ref(),
+            epoch.to_le_bytes().as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Address of the `WithdrawalApproval` of `voter` in `registrar`.
+pub fn find_withdrawal_approval_address(registrar: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            registrar.as_ref(),
+            WITHDRAWAL_APPROVAL.as_ref(),
+            voter.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Address of the `Position` of `position_mint` in `registrar`.
+pub fn find_position_address(registrar: &Pubkey, position_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            registrar.as_ref(),
+            POSITION.as_ref(),
+            position_mint.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
@@ -0,0 +1,65 @@
+//! Bindings for JavaScript clients, built for `wasm32-unknown-unknown`.
+//!
+//! They run the same weight computation and address derivation as the
+//! program, on account data fetched by the client. Pubkeys are passed as 32
+//! bytes.
+//!
+//! Only compiled with the `wasm` feature.
+
+use crate::account::{compute_voter_weight, Registrar, Voter};
+use crate::seeds;
+use anchor_lang::prelude::*;
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+fn js_error(e: impl std::fmt::Display) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+fn pubkey(bytes: &[u8]) -> std::result::Result<Pubkey, JsValue> {
+    <[u8; 32]>::try_from(bytes)
+        .map(Pubkey::new_from_array)
+        .map_err(|_| js_error("expected 32 pubkey bytes"))
+}
+
+/// Voting weight of a voter at `curr_ts`, see `compute_voter_weight`.
+#[wasm_bindgen(js_name = voterWeight)]
+pub fn voter_weight(
+    registrar_data: &[u8],
+    voter_data: &[u8],
+    curr_ts: i64,
+) -> std::result::Result<u64, JsValue> {
+    let mut data = registrar_data;
+    let registrar = Registrar::try_deserialize(&mut data).map_err(js_error)?;
+    let mut data = voter_data;
+    let voter = Voter::try_deserialize(&mut data).map_err(js_error)?;
+    Ok(compute_voter_weight(&voter, &registrar, curr_ts))
+}
+
+/// Address of the registrar of `realm`.
+#[wasm_bindgen(js_name = findRegistrarAddress)]
+pub fn find_registrar_address(realm: &[u8]) -> std::result::Result<Vec<u8>, JsValue> {
+    let (address, _) = seeds::find_registrar_address(&pubkey(realm)?);
+    Ok(address.to_bytes().to_vec())
+}
+
+/// Address of the voter of `authority` in `registrar`.
+#[wasm_bindgen(js_name = findVoterAddress)]
+pub fn find_voter_address(
+    registrar: &[u8],
+    authority: &[u8],
+) -> std::result::Result<Vec<u8>, JsValue> {
+    let (address, _) = seeds::find_voter_address(&pubkey(registrar)?, &pubkey(authority)?);
+    Ok(address.to_bytes().to_vec())
+}
+
+/// Address of the `VoterWeightRecord` of `authority` in `registrar`.
+#[wasm_bindgen(js_name = findVoterWeightRecordAddress)]
+pub fn find_voter_weight_record_address(
+    registrar: &[u8],
+    authority: &[u8],
+) -> std::result::Result<Vec<u8>, JsValue> {
+    let (address, _) =
+        seeds::find_voter_weight_record_address(&pubkey(registrar)?, &pubkey(authority)?);
+    Ok(address.to_bytes().to_vec())
+}
@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
+use anchor_spl::token::TokenAccount;
+use solana_program::program_pack::Pack;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::transport::TransportError;
 use solana_sdk::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     signature::{Keypair, Signer},
 };
 use voter_weight_addin_deposits as addin;
@@ -24,6 +26,23 @@ pub struct RegistrarCookie {
 
 pub struct VoterCookie {
     pub address: Pubkey,
+    pub authority: Pubkey,
+    pub voter_weight_record: Pubkey,
+}
+
+pub struct PositionCookie {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    /// The token account the NFT was minted into.
+    pub holder: Pubkey,
+}
+
+/// A voter weight record for spl-governance instructions, with the
+/// instruction that refreshes it. spl-governance only accepts records that
+/// were refreshed in the current slot, so the refresh is sent along.
+pub struct VoterWeightCookie {
+    pub address: Pubkey,
+    pub refresh: Instruction,
 }
 
 impl AddinCookie {
@@ -32,14 +51,11 @@ impl AddinCookie {
         realm: &GovernanceRealmCookie,
         payer: &Keypair,
     ) -> RegistrarCookie {
-        let (registrar, registrar_bump) =
-            Pubkey::find_program_address(&[&realm.realm.to_bytes()], &self.program_id);
+        let (registrar, registrar_bump) = addin::seeds::find_registrar_address(&realm.realm);
 
         let community_token_mint = realm.community_token_mint.pubkey.unwrap();
-        let vault = spl_associated_token_account::get_associated_token_address(
-            &registrar,
-            &community_token_mint,
-        );
+        let (vault, _vault_bump) =
+            addin::seeds::find_vault_address(&registrar, &community_token_mint, 0);
 
         let data = anchor_lang::InstructionData::data(&addin::instruction::CreateRegistrar {
             registrar_bump,
@@ -56,7 +72,6 @@ impl AddinCookie {
                 payer: payer.pubkey(),
                 system_program: solana_sdk::system_program::id(),
                 token_program: spl_token::id(),
-                associated_token_program: spl_associated_token_account::id(),
                 rent: solana_program::sysvar::rent::id(),
             },
             None,
@@ -83,45 +98,93 @@ impl AddinCookie {
         }
     }
 
-    pub async fn create_voter(
+    pub async fn verify_registrar(
         &self,
+        realm: &GovernanceRealmCookie,
         registrar: &RegistrarCookie,
-        authority: &Keypair,
-        payer: &Keypair,
-    ) -> VoterCookie {
-        let (voter, voter_bump) = Pubkey::find_program_address(
-            &[
-                &registrar.address.to_bytes(),
-                &authority.pubkey().to_bytes(),
-            ],
-            &self.program_id,
-        );
-        let (voter_weight_record, voter_weight_record_bump) = Pubkey::find_program_address(
-            &[
-                b"voter-weight-record".as_ref(),
-                &registrar.address.to_bytes(),
-                &authority.pubkey().to_bytes(),
-            ],
-            &self.program_id,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::VerifyRegistrar {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::VerifyRegistrar {
+                registrar: registrar.address,
+                realm: realm.realm,
+                realm_config: realm.realm_config,
+            },
+            None,
         );
 
-        let data = anchor_lang::InstructionData::data(&addin::instruction::CreateVoter {
-            voter_bump,
-            voter_weight_record_bump,
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_voting_mint(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        mint: &MintCookie,
+        idx: u8,
+        rate: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureVotingMint {
+            idx,
+            rate,
         });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &addin::accounts::CreateVoter {
-                voter,
-                voter_weight_record,
+            &addin::accounts::ConfigureVotingMint {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+                mint: mint.pubkey.unwrap(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_vault_shard(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        mint: &MintCookie,
+        shard: u8,
+        payer: &Keypair,
+    ) -> std::result::Result<(), TransportError> {
+        let (vault, _vault_bump) =
+            addin::seeds::find_vault_address(&registrar.address, &mint.pubkey.unwrap(), shard);
+
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::CreateVaultShard { shard });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateVaultShard {
                 registrar: registrar.address,
+                mint: mint.pubkey.unwrap(),
                 authority: authority.pubkey(),
+                vault,
                 payer: payer.pubkey(),
-                token_program: spl_token::id(),
-                associated_token_program: spl_associated_token_account::id(),
                 system_program: solana_sdk::system_program::id(),
+                token_program: spl_token::id(),
                 rent: solana_program::sysvar::rent::id(),
-                instructions: solana_program::sysvar::instructions::id(),
             },
             None,
         );
@@ -139,30 +202,58 @@ impl AddinCookie {
         self.solana
             .process_transaction(&instructions, Some(&[&signer1, &signer2]))
             .await
-            .unwrap();
+    }
+
+    pub async fn configure_quadratic_weight(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        quadratic_weight: bool,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureQuadraticWeight {
+                quadratic_weight,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureQuadraticWeight {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
 
-        VoterCookie { address: voter }
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
     }
 
-    pub async fn deposit(
+    #[allow(dead_code)]
+    pub async fn configure_min_withdraw(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
         authority: &Keypair,
-        token_address: Pubkey,
-        amount: u64,
+        idx: u8,
+        min_withdraw_native: u64,
     ) -> std::result::Result<(), TransportError> {
-        let data = anchor_lang::InstructionData::data(&addin::instruction::Deposit { amount });
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureMinWithdraw {
+            idx,
+            min_withdraw_native,
+        });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &addin::accounts::Deposit {
+            &addin::accounts::ConfigureMinAmounts {
                 registrar: registrar.address,
-                voter: voter.address,
-                vault: registrar.vault,
-                deposit_mint: registrar.mint.pubkey.unwrap(),
-                deposit_token: token_address,
                 authority: authority.pubkey(),
-                token_program: spl_token::id(),
             },
             None,
         );
@@ -181,27 +272,23 @@ impl AddinCookie {
             .await
     }
 
-    pub async fn withdraw(
+    #[allow(dead_code)]
+    pub async fn configure_weight_scale(
         &self,
         registrar: &RegistrarCookie,
-        voter: &VoterCookie,
-        token_owner_record: &TokenOwnerRecordCookie,
         authority: &Keypair,
-        token_address: Pubkey,
-        amount: u64,
+        numerator: u64,
+        denominator: u64,
     ) -> std::result::Result<(), TransportError> {
-        let data = anchor_lang::InstructionData::data(&addin::instruction::Withdraw { amount });
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureWeightScale {
+            numerator,
+            denominator,
+        });
 
         let accounts = anchor_lang::ToAccountMetas::to_account_metas(
-            &addin::accounts::Withdraw {
+            &addin::accounts::ConfigureWeightScale {
                 registrar: registrar.address,
-                voter: voter.address,
-                token_owner_record: token_owner_record.address,
-                vault: registrar.vault,
-                withdraw_mint: registrar.mint.pubkey.unwrap(),
-                destination: token_address,
                 authority: authority.pubkey(),
-                token_program: spl_token::id(),
             },
             None,
         );
@@ -219,20 +306,2907 @@ impl AddinCookie {
             .process_transaction(&instructions, Some(&[&signer]))
             .await
     }
-}
 
-impl RegistrarCookie {
-    pub async fn vault_balance(&self, solana: &SolanaCookie) -> u64 {
-        solana
-        .get_account::<TokenAccount>(self.vault)
-        .await.amount
+    #[allow(dead_code)]
+    pub async fn configure_proposal_deposit(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        proposal_deposit_native: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureProposalDeposit {
+                proposal_deposit_native,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureProposalDeposit {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
     }
-}
 
-impl VoterCookie {
-    pub async fn deposit_amount(&self, solana: &SolanaCookie) -> u64 {
-        solana
-        .get_account::<addin::account::Voter>(self.address)
-        .await.amount_deposited
+    #[allow(dead_code)]
+    pub async fn configure_proposal_locking(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        proposal_locking: bool,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureProposalLocking {
+                proposal_locking,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureProposalLocking {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_weight_action_rule(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        action: addin::account::VoterWeightAction,
+        rule: addin::account::WeightRule,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureWeightActionRule {
+                action,
+                rule,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureWeightAction {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    pub async fn configure_registrar_authority(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        new_authority: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureRegistrarAuthority {
+                new_authority,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureRegistrarAuthority {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Builds the instruction without sending it, so it can also be
+    /// executed by a governance proposal.
+    pub fn configure_lockup_params_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: Pubkey,
+        lockup_saturation_secs: u64,
+        max_extra_lockup_vote_weight_factor: u64,
+    ) -> Instruction {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureLockupParams {
+            lockup_saturation_secs,
+            max_extra_lockup_vote_weight_factor,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureLockupParams {
+                registrar: registrar.address,
+                authority,
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    pub async fn configure_lockup_params(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        lockup_saturation_secs: u64,
+        max_extra_lockup_vote_weight_factor: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let instructions = vec![self.configure_lockup_params_instruction(
+            registrar,
+            authority.pubkey(),
+            lockup_saturation_secs,
+            max_extra_lockup_vote_weight_factor,
+        )];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_allowlist_required(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        allowlist_required: bool,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureAllowlistRequired {
+                allowlist_required,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureAllowlistRequired {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Adds `wallet` to the allowlist and returns its `AllowlistEntry`.
+    #[allow(dead_code)]
+    pub async fn add_to_allowlist(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        wallet: Pubkey,
+    ) -> std::result::Result<Pubkey, TransportError> {
+        let (allowlist_entry, _) =
+            addin::seeds::find_allowlist_entry_address(&registrar.address, &wallet);
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::AddToAllowlist { wallet });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::AddToAllowlist {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+                allowlist_entry,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await?;
+        Ok(allowlist_entry)
+    }
+
+    /// Adds `wallet` to the blocklist and returns its `BlocklistEntry`.
+    #[allow(dead_code)]
+    pub async fn add_to_blocklist(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        wallet: Pubkey,
+    ) -> std::result::Result<Pubkey, TransportError> {
+        let (blocklist_entry, _) =
+            addin::seeds::find_blocklist_entry_address(&registrar.address, &wallet);
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::AddToBlocklist { wallet });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::AddToBlocklist {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+                blocklist_entry,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await?;
+        Ok(blocklist_entry)
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_gatekeeper(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        gatekeeper_program: Pubkey,
+        gatekeeper_network: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureGatekeeper {
+            gatekeeper_program,
+            gatekeeper_network,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureGatekeeper {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_nft_boost(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        nft_collection: Pubkey,
+        nft_boost_bps: u16,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureNftBoost {
+            nft_collection,
+            nft_boost_bps,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureNftBoost {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_upstream_addin(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        upstream_addin: Pubkey,
+        upstream_combination: addin::account::WeightCombination,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureUpstreamAddin {
+                upstream_addin,
+                upstream_combination,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureUpstreamAddin {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_oracle(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        idx: u8,
+        oracle: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureOracle {
+            idx,
+            oracle,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureOracle {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_early_unlock(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        early_unlock_treasury: Pubkey,
+        early_unlock_penalty_bps: u16,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureEarlyUnlock {
+            early_unlock_treasury,
+            early_unlock_penalty_bps,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureEarlyUnlock {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Freezes the voter, or unfreezes it if `frozen` is false.
+    #[allow(dead_code)]
+    pub async fn freeze_voter(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        frozen: bool,
+    ) -> std::result::Result<(), TransportError> {
+        let data = if frozen {
+            anchor_lang::InstructionData::data(&addin::instruction::FreezeVoter {})
+        } else {
+            anchor_lang::InstructionData::data(&addin::instruction::UnfreezeVoter {})
+        };
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::FreezeVoter {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_twab_window(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        twab_window_secs: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ConfigureTwabWindow {
+            twab_window_secs,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureTwabWindow {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_withdrawal_cooldown(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        withdrawal_cooldown_secs: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureWithdrawalCooldown {
+                withdrawal_cooldown_secs,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureWithdrawalCooldown {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn request_withdraw(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::RequestWithdraw {
+            deposit_entry_index,
+            amount,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::RequestWithdraw {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn reset_lockup(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+        periods: u32,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ResetLockup {
+            deposit_entry_index,
+            periods,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ResetLockup {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn internal_transfer(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        source_deposit_entry_index: u8,
+        target_deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::InternalTransfer {
+            source_deposit_entry_index,
+            target_deposit_entry_index,
+            amount,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::InternalTransfer {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[cfg(feature = "localnet-test")]
+    pub async fn set_time_offset(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        time_offset: i64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::SetTimeOffset {
+            time_offset,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::SetTimeOffset {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    pub async fn create_voter(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+    ) -> VoterCookie {
+        self.create_voter_with_accounts(registrar, authority, payer, vec![])
+            .await
+    }
+
+    /// Creates the voter and has create_voter create the authority's token
+    /// owner record along with it.
+    #[allow(dead_code)]
+    pub async fn create_voter_with_token_owner_record(
+        &self,
+        registrar: &RegistrarCookie,
+        realm: &GovernanceRealmCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+    ) -> (VoterCookie, TokenOwnerRecordCookie) {
+        let community_token_mint = realm.community_token_mint.pubkey.unwrap();
+        let token_owner_record =
+            spl_governance::state::token_owner_record::get_token_owner_record_address(
+                &realm.governance.program_id,
+                &realm.realm,
+                &community_token_mint,
+                &authority.pubkey(),
+            );
+        let remaining_accounts = vec![
+            AccountMeta::new(token_owner_record, false),
+            AccountMeta::new_readonly(realm.realm, false),
+            AccountMeta::new_readonly(community_token_mint, false),
+            AccountMeta::new_readonly(realm.governance.program_id, false),
+        ];
+        let voter = self
+            .create_voter_with_accounts(registrar, authority, payer, remaining_accounts)
+            .await;
+        (
+            voter,
+            TokenOwnerRecordCookie {
+                address: token_owner_record,
+            },
+        )
+    }
+
+    /// Builds the instruction without sending it, so it can also be sent
+    /// through CPI.
+    pub fn create_voter_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: Pubkey,
+        payer: Pubkey,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        let (voter, voter_bump) = addin::seeds::find_voter_address(&registrar.address, &authority);
+        let (voter_weight_record, voter_weight_record_bump) =
+            addin::seeds::find_voter_weight_record_address(&registrar.address, &authority);
+
+        let data = anchor_lang::InstructionData::data(&addin::instruction::CreateVoter {
+            voter_bump,
+            voter_weight_record_bump,
+        });
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateVoter {
+                voter,
+                voter_weight_record,
+                registrar: registrar.address,
+                authority,
+                payer,
+                system_program: solana_sdk::system_program::id(),
+                rent: solana_program::sysvar::rent::id(),
+                instructions: solana_program::sysvar::instructions::id(),
+            },
+            None,
+        );
+        accounts.extend(remaining_accounts);
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    async fn create_voter_with_accounts(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> VoterCookie {
+        self.try_create_voter(registrar, authority, payer, remaining_accounts)
+            .await
+            .unwrap()
+    }
+
+    /// Like `create_voter`, passing `remaining_accounts` and returning the
+    /// error if the voter can't be created.
+    pub async fn try_create_voter(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> std::result::Result<VoterCookie, TransportError> {
+        let instructions = vec![self.create_voter_instruction(
+            registrar,
+            authority.pubkey(),
+            payer.pubkey(),
+            remaining_accounts,
+        )];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await?;
+
+        Ok(self.voter(registrar, authority.pubkey()))
+    }
+
+    #[allow(dead_code)]
+    pub async fn fund_rent_sponsor(
+        &self,
+        registrar: &RegistrarCookie,
+        payer: &Keypair,
+        lamports: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::FundRentSponsor { lamports });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::FundRentSponsor {
+                registrar: registrar.address,
+                rent_sponsor: registrar.rent_sponsor(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&payer.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_reward_vault(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        reward_mint: &MintCookie,
+        reward_rate_per_sec: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::CreateRewardVault {
+            reward_rate_per_sec,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateRewardVault {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+                reward_vault: registrar.reward_vault(),
+                reward_mint: reward_mint.pubkey.unwrap(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                token_program: spl_token::id(),
+                rent: solana_program::sysvar::rent::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&authority.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&payer.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn set_reward_rate(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        reward_rate_per_sec: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::SetRewardRate {
+            reward_rate_per_sec,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::SetRewardRate {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn claim_rewards(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        destination: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ClaimRewards {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ClaimRewards {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                reward_vault: registrar.reward_vault(),
+                destination,
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Creates the `MerkleDistributor` for `root` and returns its address.
+    #[allow(dead_code)]
+    pub async fn create_merkle_distributor(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        root: [u8; 32],
+        lockup_kind: addin::account::LockupKind,
+        lockup_periods: u32,
+    ) -> std::result::Result<Pubkey, TransportError> {
+        let (distributor, _) =
+            addin::seeds::find_merkle_distributor_address(&registrar.address, &root);
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::CreateMerkleDistributor {
+                root,
+                lockup_kind,
+                lockup_periods,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateMerkleDistributor {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+                distributor,
+                distributor_vault: addin::seeds::find_distributor_vault_address(&distributor).0,
+                mint: registrar.mint.pubkey.unwrap(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                token_program: spl_token::id(),
+                rent: solana_program::sysvar::rent::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await?;
+        Ok(distributor)
+    }
+
+    #[allow(dead_code)]
+    pub async fn claim_from_merkle(
+        &self,
+        registrar: &RegistrarCookie,
+        distributor: Pubkey,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        deposit_entry_index: u8,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ClaimFromMerkle {
+            args: addin::args::ClaimFromMerkleArgs::V1 {
+                index,
+                amount,
+                proof,
+                deposit_entry_index,
+            },
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ClaimFromMerkle {
+                registrar: registrar.address,
+                distributor,
+                distributor_vault: addin::seeds::find_distributor_vault_address(&distributor).0,
+                claim_status: addin::seeds::find_claim_status_address(&distributor, index).0,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                vault: registrar.vault,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_deposit_labels(
+        &self,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::CreateDepositLabels {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateDepositLabels {
+                voter: voter.address,
+                authority: authority.pubkey(),
+                deposit_labels: voter.deposit_labels(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn set_deposit_label(
+        &self,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+        label: &str,
+    ) -> std::result::Result<(), TransportError> {
+        let mut padded = [0u8; addin::account::DEPOSIT_LABEL_LEN];
+        padded[..label.len()].copy_from_slice(label.as_bytes());
+        let data = anchor_lang::InstructionData::data(&addin::instruction::SetDepositLabel {
+            deposit_entry_index,
+            label: padded,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::SetDepositLabel {
+                voter: voter.address,
+                authority: authority.pubkey(),
+                deposit_labels: voter.deposit_labels(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_position_transfers(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        position_transfers_allowed: bool,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigurePositionTransfers {
+                position_transfers_allowed,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigurePositionTransfers {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Creates a new position mint and mints the position NFT into `holder`,
+    /// a token account of the voter authority for it.
+    #[allow(dead_code)]
+    pub async fn create_position(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+        deposit_entry_index: u8,
+    ) -> std::result::Result<PositionCookie, TransportError> {
+        let mint = Keypair::new();
+        // The holder can only be initialized once the mint is.
+        let holder = Keypair::new();
+        let position = addin::seeds::find_position_address(&registrar.address, &mint.pubkey()).0;
+        let rent = self
+            .solana
+            .rent
+            .minimum_balance(spl_token::state::Mint::LEN);
+        let holder_rent = self
+            .solana
+            .rent
+            .minimum_balance(spl_token::state::Account::LEN);
+
+        let data = anchor_lang::InstructionData::data(&addin::instruction::CreatePosition {
+            deposit_entry_index,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreatePosition {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                position,
+                position_mint: mint.pubkey(),
+                holder: holder.pubkey(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![
+            solana_sdk::system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent,
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &registrar.address,
+                Some(&registrar.address),
+                0,
+            )
+            .unwrap(),
+            solana_sdk::system_instruction::create_account(
+                &payer.pubkey(),
+                &holder.pubkey(),
+                holder_rent,
+                spl_token::state::Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &holder.pubkey(),
+                &mint.pubkey(),
+                &authority.pubkey(),
+            )
+            .unwrap(),
+            Instruction {
+                program_id: self.program_id,
+                accounts,
+                data,
+            },
+        ];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2, &mint, &holder]))
+            .await?;
+
+        Ok(PositionCookie {
+            address: position,
+            mint: mint.pubkey(),
+            holder: holder.pubkey(),
+        })
+    }
+
+    #[allow(dead_code)]
+    pub async fn transfer_position(
+        &self,
+        registrar: &RegistrarCookie,
+        position: &PositionCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        holder: Pubkey,
+        target_voter: &VoterCookie,
+        target_holder: Pubkey,
+        target_deposit_entry_index: u8,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::TransferPosition {
+            target_deposit_entry_index,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::TransferPosition {
+                registrar: registrar.address,
+                position: position.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                token_owner_record: token_owner_record.address,
+                holder,
+                target_voter: target_voter.address,
+                target_holder,
+                position_mint: position.mint,
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn close_position(
+        &self,
+        registrar: &RegistrarCookie,
+        position: &PositionCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        holder: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ClosePosition {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ClosePosition {
+                registrar: registrar.address,
+                position: position.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                holder,
+                position_mint: position.mint,
+                sol_destination: authority.pubkey(),
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_voter_delegations(
+        &self,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        payer: &Keypair,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::CreateVoterDelegations {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateVoterDelegations {
+                voter: voter.address,
+                authority: authority.pubkey(),
+                delegations: voter.delegations(),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_delegation_record(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        delegate_voter: &VoterCookie,
+        payer: &Keypair,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::CreateDelegationRecord {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateDelegationRecord {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                delegate_voter: delegate_voter.address,
+                delegation_record: voter.delegation_record(delegate_voter),
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn delegate(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        delegate_voter: &VoterCookie,
+        deposit_entry_index: u8,
+        share_kind: addin::account::DelegationShareKind,
+        share: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::Delegate {
+            deposit_entry_index,
+            share_kind,
+            share,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::Delegate {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                token_owner_record: token_owner_record.address,
+                delegations: voter.delegations(),
+                delegate_voter: delegate_voter.address,
+                delegation_record: voter.delegation_record(delegate_voter),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_undelegate_cooldown(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        undelegate_cooldown_secs: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureUndelegateCooldown {
+                undelegate_cooldown_secs,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureUndelegateCooldown {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn request_undelegate(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        delegate_voter: &VoterCookie,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::RequestUndelegate {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::RequestUndelegate {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                delegations: voter.delegations(),
+                delegate_voter: delegate_voter.address,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn undelegate(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        delegate_voter: &VoterCookie,
+        delegate_token_owner_record: &TokenOwnerRecordCookie,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::Undelegate {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::Undelegate {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                delegations: voter.delegations(),
+                delegate_voter: delegate_voter.address,
+                delegate_token_owner_record: delegate_token_owner_record.address,
+                delegation_record: voter.delegation_record(delegate_voter),
+                sol_destination: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_withdrawal_approval(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        withdrawals_require_approval: bool,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ConfigureWithdrawalApproval {
+                withdrawals_require_approval,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureWithdrawalApproval {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn approve_withdrawal(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        voter: &VoterCookie,
+        amount: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ApproveWithdrawal { amount });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ApproveWithdrawal {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+                voter: voter.address,
+                withdrawal_approval: addin::seeds::find_withdrawal_approval_address(
+                    &registrar.address,
+                    &voter.address,
+                )
+                .0,
+                rent_sponsor: registrar.rent_sponsor(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_voter_sponsored(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+    ) -> std::result::Result<VoterCookie, TransportError> {
+        let voter = self.voter(registrar, authority.pubkey());
+        let (_, voter_bump) =
+            addin::seeds::find_voter_address(&registrar.address, &authority.pubkey());
+        let (_, voter_weight_record_bump) =
+            addin::seeds::find_voter_weight_record_address(&registrar.address, &authority.pubkey());
+
+        let data = anchor_lang::InstructionData::data(&addin::instruction::CreateVoterSponsored {
+            voter_bump,
+            voter_weight_record_bump,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateVoterSponsored {
+                voter: voter.address,
+                voter_weight_record: voter.voter_weight_record,
+                registrar: registrar.address,
+                rent_sponsor: registrar.rent_sponsor(),
+                authority: authority.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                instructions: solana_program::sysvar::instructions::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await?;
+        Ok(voter)
+    }
+
+    /// The voter of `authority`, which may not exist yet.
+    pub fn voter(&self, registrar: &RegistrarCookie, authority: Pubkey) -> VoterCookie {
+        let (voter, _voter_bump) = addin::seeds::find_voter_address(&registrar.address, &authority);
+        let (voter_weight_record, _voter_weight_record_bump) =
+            addin::seeds::find_voter_weight_record_address(&registrar.address, &authority);
+        VoterCookie {
+            address: voter,
+            authority,
+            voter_weight_record,
+        }
+    }
+
+    pub async fn create_deposit_entry(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+        kind: addin::account::LockupKind,
+        periods: u32,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::CreateDepositEntry {
+            args: addin::args::CreateDepositEntryArgs::V1 {
+                deposit_entry_index,
+                kind,
+                periods,
+            },
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateDepositEntry {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    pub async fn deposit(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), TransportError> {
+        self.deposit_mint(
+            registrar,
+            voter,
+            authority,
+            &registrar.mint,
+            token_address,
+            deposit_entry_index,
+            amount,
+        )
+        .await
+    }
+
+    /// Deposits tokens of `mint`, which must have a vault in shard 0.
+    pub async fn deposit_mint(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        mint: &MintCookie,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let instructions = vec![self.deposit_instruction(
+            registrar,
+            voter,
+            authority.pubkey(),
+            mint,
+            token_address,
+            deposit_entry_index,
+            amount,
+        )];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Like `deposit`, passing `remaining_accounts`.
+    #[allow(dead_code)]
+    pub async fn deposit_with_accounts(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> std::result::Result<(), TransportError> {
+        let mut instruction = self.deposit_instruction(
+            registrar,
+            voter,
+            authority.pubkey(),
+            &registrar.mint,
+            token_address,
+            deposit_entry_index,
+            amount,
+        );
+        instruction.accounts.extend(remaining_accounts);
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&[instruction], Some(&[&signer]))
+            .await
+    }
+
+    pub fn deposit_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: Pubkey,
+        mint: &MintCookie,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        let deposit_mint = mint.pubkey.unwrap();
+        let (vault, _vault_bump) =
+            addin::seeds::find_vault_address(&registrar.address, &deposit_mint, 0);
+
+        let data = anchor_lang::InstructionData::data(&addin::instruction::Deposit {
+            args: addin::args::DepositArgs::V3 {
+                deposit_entry_index,
+                amount,
+                nonce: None,
+            },
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::Deposit {
+                registrar: registrar.address,
+                voter: voter.address,
+                vault,
+                deposit_mint,
+                deposit_token: token_address,
+                deposit_authority: authority,
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn migrate_governance_deposit(
+        &self,
+        registrar: &RegistrarCookie,
+        realm: &GovernanceRealmCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        deposit_entry_index: u8,
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::MigrateGovernanceDeposit {
+                deposit_entry_index,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::MigrateGovernanceDeposit {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: authority.pubkey(),
+                vault: registrar.vault,
+                deposit_mint: registrar.mint.pubkey.unwrap(),
+                realm: realm.realm,
+                governing_token_holding: realm.community_token_account,
+                token_owner_record: token_owner_record.address,
+                governance_program: realm.governance.program_id,
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    pub async fn withdraw(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> std::result::Result<(), TransportError> {
+        let instructions = vec![self.withdraw_instruction(
+            registrar,
+            voter,
+            token_owner_record,
+            authority.pubkey(),
+            token_address,
+            deposit_entry_index,
+            amount,
+        )];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Withdraws from a delegated deposit entry, passing the voter's
+    /// `VoterDelegations` and each delegate with its token owner record.
+    #[allow(dead_code)]
+    pub async fn withdraw_with_delegates(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+        delegates: &[(&VoterCookie, &TokenOwnerRecordCookie)],
+    ) -> std::result::Result<(), TransportError> {
+        let mut instruction = self.withdraw_instruction(
+            registrar,
+            voter,
+            token_owner_record,
+            authority.pubkey(),
+            token_address,
+            deposit_entry_index,
+            amount,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(voter.delegations(), false));
+        for (delegate, delegate_token_owner_record) in delegates {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(delegate.address, false));
+            instruction.accounts.push(AccountMeta::new_readonly(
+                delegate_token_owner_record.address,
+                false,
+            ));
+        }
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&[instruction], Some(&[&signer]))
+            .await
+    }
+
+    pub fn withdraw_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: Pubkey,
+        token_address: Pubkey,
+        deposit_entry_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::Withdraw {
+            args: addin::args::WithdrawArgs::V2 {
+                deposit_entry_index,
+                amount,
+            },
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::Withdraw {
+                registrar: registrar.address,
+                voter: voter.address,
+                token_owner_record: token_owner_record.address,
+                vault: registrar.vault,
+                withdraw_mint: registrar.mint.pubkey.unwrap(),
+                destination: token_address,
+                voter_weight_record: voter.voter_weight_record,
+                authority,
+                token_program: spl_token::id(),
+                instructions: solana_program::sysvar::instructions::id(),
+                withdrawal_approval: addin::seeds::find_withdrawal_approval_address(
+                    &registrar.address,
+                    &voter.address,
+                )
+                .0,
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn unlock_early(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        treasury: Pubkey,
+        deposit_entry_index: u8,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::UnlockEarly {
+            deposit_entry_index,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::UnlockEarly {
+                registrar: registrar.address,
+                voter: voter.address,
+                token_owner_record: token_owner_record.address,
+                vault: registrar.vault,
+                deposit_mint: registrar.mint.pubkey.unwrap(),
+                treasury,
+                voter_weight_record: voter.voter_weight_record,
+                authority: authority.pubkey(),
+                token_program: spl_token::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn sweep_dust(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let instructions = vec![self.sweep_dust_instruction(
+            registrar,
+            voter,
+            token_owner_record,
+            authority.pubkey(),
+            token_address,
+        )];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Like `sweep_dust`, passing the voter's `VoterDelegations` and the
+    /// accounts of `delegates`, like `withdraw_with_delegates`.
+    #[allow(dead_code)]
+    pub async fn sweep_dust_with_delegates(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: &Keypair,
+        token_address: Pubkey,
+        delegates: &[(&VoterCookie, &TokenOwnerRecordCookie)],
+    ) -> std::result::Result<(), TransportError> {
+        let mut instruction = self.sweep_dust_instruction(
+            registrar,
+            voter,
+            token_owner_record,
+            authority.pubkey(),
+            token_address,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(voter.delegations(), false));
+        for (delegate, delegate_token_owner_record) in delegates {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(delegate.address, false));
+            instruction.accounts.push(AccountMeta::new_readonly(
+                delegate_token_owner_record.address,
+                false,
+            ));
+        }
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&[instruction], Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub fn sweep_dust_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        authority: Pubkey,
+        token_address: Pubkey,
+    ) -> Instruction {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::SweepDust {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::Withdraw {
+                registrar: registrar.address,
+                voter: voter.address,
+                token_owner_record: token_owner_record.address,
+                vault: registrar.vault,
+                withdraw_mint: registrar.mint.pubkey.unwrap(),
+                destination: token_address,
+                voter_weight_record: voter.voter_weight_record,
+                authority,
+                token_program: spl_token::id(),
+                instructions: solana_program::sysvar::instructions::id(),
+                withdrawal_approval: addin::seeds::find_withdrawal_approval_address(
+                    &registrar.address,
+                    &voter.address,
+                )
+                .0,
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn snapshot_epoch(
+        &self,
+        registrar: &RegistrarCookie,
+        epoch: u64,
+        payer: &Keypair,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::SnapshotEpoch { epoch });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::SnapshotEpoch {
+                registrar: registrar.address,
+                epoch_snapshot: addin::seeds::find_epoch_snapshot_address(&registrar.address, epoch)
+                    .0,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&payer.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn close_voter(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        authority: &Keypair,
+        sol_destination: Pubkey,
+        voter_weight_record_rent_destination: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::CloseVoter {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CloseVoter {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_weight_record: voter.voter_weight_record,
+                authority: authority.pubkey(),
+                sol_destination,
+                voter_weight_record_rent_destination,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    pub fn update_voter_weight_record_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> Instruction {
+        self.update_voter_weight_record_for_action_instruction(registrar, voter, None)
+    }
+
+    pub fn update_voter_weight_record_for_action_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        weight_action: Option<addin::account::VoterWeightAction>,
+    ) -> Instruction {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::UpdateVoterWeightRecord {
+                args: addin::args::UpdateVoterWeightRecordArgs::V1 { weight_action },
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::UpdateVoterWeightRecord {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_weight_record: voter.voter_weight_record,
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    pub async fn update_voter_weight_record(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> std::result::Result<(), TransportError> {
+        let instructions = vec![self.update_voter_weight_record_instruction(registrar, voter)];
+
+        self.solana
+            .process_transaction(&instructions, None)
+            .await
+    }
+
+    /// Updates the record of `voter`, passing `remaining_accounts`, like NFT,
+    /// oracle or upstream addin accounts.
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_with_accounts(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> std::result::Result<(), TransportError> {
+        let mut instruction = self.update_voter_weight_record_instruction(registrar, voter);
+        instruction.accounts.extend(remaining_accounts);
+
+        self.solana.process_transaction(&[instruction], None).await
+    }
+
+    /// Updates the record of `voter`, passing its own `VoterDelegations` and
+    /// the accounts of the `delegators`.
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_with_delegations(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegators: &[&VoterCookie],
+    ) -> std::result::Result<(), TransportError> {
+        let instruction = self
+            .update_voter_weight_record_with_delegations_instruction(registrar, voter, delegators);
+
+        self.solana.process_transaction(&[instruction], None).await
+    }
+
+    #[allow(dead_code)]
+    pub fn update_voter_weight_record_with_delegations_instruction(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegators: &[&VoterCookie],
+    ) -> Instruction {
+        let mut instruction = self.update_voter_weight_record_instruction(registrar, voter);
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(voter.delegations(), false));
+        for delegator in delegators {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(delegator.address, false));
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(delegator.delegations(), false));
+        }
+        instruction
+    }
+
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_record_for_action(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        weight_action: addin::account::VoterWeightAction,
+    ) -> std::result::Result<(), TransportError> {
+        let instructions = vec![self.update_voter_weight_record_for_action_instruction(
+            registrar,
+            voter,
+            Some(weight_action),
+        )];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    #[allow(dead_code)]
+    pub fn refreshed_voter_weight(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> VoterWeightCookie {
+        VoterWeightCookie {
+            address: voter.voter_weight_record,
+            refresh: self.update_voter_weight_record_instruction(registrar, voter),
+        }
+    }
+
+    /// Like `refreshed_voter_weight`, for a delegate of `delegators`.
+    #[allow(dead_code)]
+    pub fn refreshed_voter_weight_with_delegations(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        delegators: &[&VoterCookie],
+    ) -> VoterWeightCookie {
+        VoterWeightCookie {
+            address: voter.voter_weight_record,
+            refresh: self.update_voter_weight_record_with_delegations_instruction(
+                registrar, voter, delegators,
+            ),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn refreshed_voter_weight_for_action(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        weight_action: addin::account::VoterWeightAction,
+    ) -> VoterWeightCookie {
+        VoterWeightCookie {
+            address: voter.voter_weight_record,
+            refresh: self.update_voter_weight_record_for_action_instruction(
+                registrar,
+                voter,
+                Some(weight_action),
+            ),
+        }
+    }
+
+    /// The voter weight record for creating the proposal with index
+    /// `proposal_index` of `governance`, refreshed by escrowing the proposal
+    /// deposit.
+    #[allow(dead_code)]
+    pub fn escrowed_voter_weight(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        realm: &GovernanceRealmCookie,
+        governance: &AccountGovernanceCookie,
+        proposal_index: u32,
+        payer: Pubkey,
+    ) -> VoterWeightCookie {
+        let proposal = realm.proposal_address(governance, proposal_index);
+        let (escrow, _) =
+            addin::seeds::find_proposal_deposit_escrow_address(&registrar.address, &proposal);
+
+        let data = anchor_lang::InstructionData::data(&addin::instruction::EscrowProposalDeposit {
+            proposal_index,
+        });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::EscrowProposalDeposit {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: voter.authority,
+                voter_weight_record: voter.voter_weight_record,
+                governance: governance.address,
+                proposal,
+                escrow,
+                payer,
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        VoterWeightCookie {
+            address: voter.voter_weight_record,
+            refresh: Instruction {
+                program_id: self.program_id,
+                accounts,
+                data,
+            },
+        }
+    }
+
+    /// The voter weight record for voting on `proposal`, refreshed by
+    /// locking `amount` for it.
+    #[allow(dead_code)]
+    pub fn locked_voter_weight(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        proposal: &ProposalCookie,
+        amount: u64,
+        payer: Pubkey,
+    ) -> VoterWeightCookie {
+        let (lock, _) = addin::seeds::find_proposal_lock_address(&voter.address, &proposal.address);
+
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::LockForProposal { amount });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::LockForProposal {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: voter.authority,
+                voter_weight_record: voter.voter_weight_record,
+                proposal: proposal.address,
+                lock,
+                payer,
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        VoterWeightCookie {
+            address: voter.voter_weight_record,
+            refresh: Instruction {
+                program_id: self.program_id,
+                accounts,
+                data,
+            },
+        }
+    }
+
+    /// The voter weight record for voting on `proposal`, refreshed by
+    /// locking the deposit entries in `deposit_entries` for it.
+    #[allow(dead_code)]
+    pub fn entries_locked_voter_weight(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        proposal: &ProposalCookie,
+        deposit_entries: u32,
+        payer: Pubkey,
+    ) -> VoterWeightCookie {
+        let (lock, _) = addin::seeds::find_proposal_lock_address(&voter.address, &proposal.address);
+
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::LockEntriesForProposal {
+                deposit_entries,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::LockForProposal {
+                registrar: registrar.address,
+                voter: voter.address,
+                authority: voter.authority,
+                voter_weight_record: voter.voter_weight_record,
+                proposal: proposal.address,
+                lock,
+                payer,
+                system_program: solana_sdk::system_program::id(),
+            },
+            None,
+        );
+
+        VoterWeightCookie {
+            address: voter.voter_weight_record,
+            refresh: Instruction {
+                program_id: self.program_id,
+                accounts,
+                data,
+            },
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn release_proposal_deposit(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        proposal: Pubkey,
+        payer: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let (escrow, _) =
+            addin::seeds::find_proposal_deposit_escrow_address(&registrar.address, &proposal);
+
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::ReleaseProposalDeposit {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ReleaseProposalDeposit {
+                registrar: registrar.address,
+                voter: voter.address,
+                escrow,
+                proposal,
+                payer,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    #[allow(dead_code)]
+    pub async fn release_proposal_lock(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+        proposal: Pubkey,
+        payer: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let (lock, _) = addin::seeds::find_proposal_lock_address(&voter.address, &proposal);
+
+        let data = anchor_lang::InstructionData::data(&addin::instruction::ReleaseProposalLock {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ReleaseProposalLock {
+                registrar: registrar.address,
+                voter: voter.address,
+                lock,
+                proposal,
+                payer,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    /// Updates the records of `voters` at once, like a crank would.
+    #[allow(dead_code)]
+    pub async fn update_voter_weight_records(
+        &self,
+        registrar: &RegistrarCookie,
+        voters: &[&VoterCookie],
+    ) -> std::result::Result<(), TransportError> {
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::UpdateVoterWeightRecords {
+                args: addin::args::UpdateVoterWeightRecordArgs::V1 {
+                    weight_action: None,
+                },
+            });
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::UpdateVoterWeightRecords {
+                registrar: registrar.address,
+            },
+            None,
+        );
+        for voter in voters {
+            accounts.push(AccountMeta::new_readonly(voter.address, false));
+            accounts.push(AccountMeta::new(voter.voter_weight_record, false));
+        }
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    pub async fn update_voter_weight_record_cached(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(
+            &addin::instruction::UpdateVoterWeightRecordCached {
+                args: addin::args::UpdateVoterWeightRecordArgs::V1 {
+                    weight_action: None,
+                },
+            },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::UpdateVoterWeightRecordCached {
+                registrar: registrar.address,
+                voter: voter.address,
+                voter_weight_record: voter.voter_weight_record,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, None)
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn refresh_weight_config(
+        &self,
+        registrar: &RegistrarCookie,
+        voter: &VoterCookie,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::RefreshWeightConfig {});
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::RefreshWeightConfig {
+                registrar: registrar.address,
+                voter: voter.address,
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+
+    #[allow(dead_code)]
+    pub async fn configure_excluded_supply_account(
+        &self,
+        registrar: &RegistrarCookie,
+        authority: &Keypair,
+        idx: u8,
+        token_account: Pubkey,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(
+            &addin::instruction::ConfigureExcludedSupplyAccount { idx, token_account },
+        );
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::ConfigureExcludedSupplyAccount {
+                registrar: registrar.address,
+                authority: authority.pubkey(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&authority.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn create_max_voter_weight_record(
+        &self,
+        registrar: &RegistrarCookie,
+        payer: &Keypair,
+    ) -> std::result::Result<(), TransportError> {
+        let (max_voter_weight_record, max_voter_weight_record_bump) =
+            addin::seeds::find_max_voter_weight_record_address(&registrar.address);
+        let data =
+            anchor_lang::InstructionData::data(&addin::instruction::CreateMaxVoterWeightRecord {
+                _max_voter_weight_record_bump: max_voter_weight_record_bump,
+            });
+
+        let accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::CreateMaxVoterWeightRecord {
+                registrar: registrar.address,
+                max_voter_weight_record,
+                payer: payer.pubkey(),
+                system_program: solana_sdk::system_program::id(),
+                rent: solana_program::sysvar::rent::id(),
+            },
+            None,
+        );
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        // clone the secrets
+        let signer = Keypair::from_base58_string(&payer.to_base58_string());
+
+        self.solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Updates the registrar's `MaxVoterWeightRecord`, passing the voting
+    /// mints and excluded supply accounts in `remaining_accounts`.
+    #[allow(dead_code)]
+    pub async fn update_max_vote_weight(
+        &self,
+        registrar: &RegistrarCookie,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> std::result::Result<(), TransportError> {
+        let data = anchor_lang::InstructionData::data(&addin::instruction::UpdateMaxVoteWeight {});
+
+        let mut accounts = anchor_lang::ToAccountMetas::to_account_metas(
+            &addin::accounts::UpdateMaxVoteWeight {
+                registrar: registrar.address,
+                max_voter_weight_record: registrar.max_voter_weight_record(),
+            },
+            None,
+        );
+        accounts.extend(remaining_accounts);
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        }];
+
+        self.solana.process_transaction(&instructions, None).await
+    }
+}
+
+impl RegistrarCookie {
+    #[allow(dead_code)]
+    pub fn max_voter_weight_record(&self) -> Pubkey {
+        addin::seeds::find_max_voter_weight_record_address(&self.address).0
+    }
+
+    #[allow(dead_code)]
+    pub async fn max_voter_weight(&self, solana: &SolanaCookie) -> u64 {
+        solana
+            .get_account::<addin::account::MaxVoterWeightRecord>(self.max_voter_weight_record())
+            .await
+            .max_voter_weight
+    }
+    #[allow(dead_code)]
+    pub fn rent_sponsor(&self) -> Pubkey {
+        addin::seeds::find_rent_sponsor_address(&self.address).0
+    }
+
+    #[allow(dead_code)]
+    pub fn reward_vault(&self) -> Pubkey {
+        addin::seeds::find_reward_vault_address(&self.address).0
+    }
+
+    pub async fn vault_balance(&self, solana: &SolanaCookie) -> u64 {
+        solana
+        .get_account::<TokenAccount>(self.vault)
+        .await.amount
+    }
+}
+
+impl VoterCookie {
+    #[allow(dead_code)]
+    pub fn deposit_labels(&self) -> Pubkey {
+        addin::seeds::find_deposit_labels_address(&self.address).0
+    }
+
+    #[allow(dead_code)]
+    pub fn delegations(&self) -> Pubkey {
+        addin::seeds::find_voter_delegations_address(&self.address).0
+    }
+
+    #[allow(dead_code)]
+    pub fn delegation_record(&self, delegate: &VoterCookie) -> Pubkey {
+        addin::seeds::find_delegation_record_address(&self.address, &delegate.address).0
+    }
+
+    pub async fn deposit_amount(&self, solana: &SolanaCookie) -> u64 {
+        solana
+        .get_account::<addin::account::Voter>(self.address)
+        .await.amount_deposited_native().unwrap()
+    }
+
+    pub async fn voter_weight(&self, solana: &SolanaCookie) -> u64 {
+        solana
+            .get_account::<addin::account::VoterWeightRecord>(self.voter_weight_record)
+            .await
+            .voter_weight
     }
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program::invoke,
+    program_error::ProgramError,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::transport::TransportError;
+
+use crate::*;
+
+/// A program that passes its instruction data and accounts on to another
+/// program through CPI. The first account is the program to call.
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (program, instruction_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let instruction = Instruction {
+        program_id: *program.key,
+        accounts: instruction_accounts
+            .iter()
+            .map(|a| AccountMeta {
+                pubkey: *a.key,
+                is_signer: a.is_signer,
+                is_writable: a.is_writable,
+            })
+            .collect(),
+        data: data.to_vec(),
+    };
+    invoke(&instruction, accounts)
+}
+
+#[derive(Clone)]
+pub struct CpiForwarderCookie {
+    pub solana: Arc<solana::SolanaCookie>,
+    pub program_id: Pubkey,
+}
+
+impl CpiForwarderCookie {
+    /// Sends `instruction` through the forwarder, so its program is invoked
+    /// through CPI.
+    #[allow(dead_code)]
+    pub async fn forward(
+        &self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<(), TransportError> {
+        let mut accounts = vec![AccountMeta::new_readonly(instruction.program_id, false)];
+        accounts.extend(instruction.accounts);
+
+        let instructions = vec![Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: instruction.data,
+        }];
+
+        self.solana
+            .process_transaction(&instructions, Some(signers))
+            .await
+    }
+}
@@ -1,10 +1,47 @@
 use std::sync::Arc;
 
+use solana_program::{
+    account_info::AccountInfo, borsh::try_from_slice_unchecked, entrypoint::ProgramResult,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transport::TransportError;
+use spl_governance::state::enums::{VoteThresholdPercentage, VoteTipping};
+use spl_governance::state::governance::GovernanceConfig;
+use spl_governance::state::proposal::{ProposalV2, VoteType};
+use spl_governance::state::token_owner_record::TokenOwnerRecordV2;
+use spl_governance::state::vote_record::{Vote, VoteChoice};
 
 use crate::*;
 
+/// Runs spl-governance natively.
+///
+/// It serializes accounts through the `&mut [u8]` in their `AccountInfo`,
+/// which leaves that slice pointing at the unwritten tail of the data. Under
+/// BPF the runtime reads the data length from the serialized input instead,
+/// so restore the slices from there.
+pub fn process_governance_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let data_ptrs = accounts
+        .iter()
+        .map(|account| account.data.borrow_mut().as_mut_ptr())
+        .collect::<Vec<_>>();
+    let result = spl_governance::processor::process_instruction(program_id, accounts, data);
+    for (account, data_ptr) in accounts.iter().zip(data_ptrs) {
+        // The serialized length, kept up to date by AccountInfo::realloc,
+        // precedes the data.
+        unsafe {
+            let len = *(data_ptr.offset(-8) as *const u64) as usize;
+            *account.data.borrow_mut() = std::slice::from_raw_parts_mut(data_ptr, len);
+        }
+    }
+    result
+}
+
 #[derive(Clone)]
 pub struct GovernanceCookie {
     pub solana: Arc<solana::SolanaCookie>,
@@ -26,6 +63,50 @@ pub struct TokenOwnerRecordCookie {
     pub address: Pubkey,
 }
 
+impl TokenOwnerRecordCookie {
+    /// Tokens deposited into spl-governance itself.
+    #[allow(dead_code)]
+    pub async fn governing_token_deposit_amount(&self, solana: &SolanaCookie) -> u64 {
+        let data = solana.get_account_data(self.address).await;
+        try_from_slice_unchecked::<TokenOwnerRecordV2>(&data)
+            .unwrap()
+            .governing_token_deposit_amount
+    }
+}
+
+#[derive(Clone)]
+pub struct AccountGovernanceCookie {
+    pub address: Pubkey,
+}
+
+#[derive(Clone)]
+pub struct ProposalCookie {
+    pub address: Pubkey,
+}
+
+impl ProposalCookie {
+    #[allow(dead_code)]
+    pub async fn yes_votes(&self, solana: &SolanaCookie) -> u64 {
+        let data = solana.get_account_data(self.address).await;
+        try_from_slice_unchecked::<ProposalV2>(&data)
+            .unwrap()
+            .options[0]
+            .vote_weight
+    }
+}
+
+#[derive(Clone)]
+pub struct ProposalInstructionCookie {
+    pub address: Pubkey,
+    pub instruction: Instruction,
+}
+
+/// With an addin, spl-governance instructions take the voter weight record,
+/// which must be refreshed in the same transaction.
+fn refresh_instructions(voter_weight: Option<&VoterWeightCookie>) -> Vec<Instruction> {
+    voter_weight.iter().map(|v| v.refresh.clone()).collect()
+}
+
 impl GovernanceCookie {
     pub async fn create_realm(
         &self,
@@ -34,6 +115,26 @@ impl GovernanceCookie {
         community_token_mint: &MintCookie,
         payer: &Keypair,
         voter_weight_addin: &Pubkey,
+    ) -> GovernanceRealmCookie {
+        self.create_realm_with_options(
+            name,
+            realm_authority,
+            community_token_mint,
+            payer,
+            Some(*voter_weight_addin),
+        )
+        .await
+    }
+
+    /// Without an addin, voters deposit their tokens into spl-governance
+    /// itself.
+    pub async fn create_realm_with_options(
+        &self,
+        name: &str,
+        realm_authority: Pubkey,
+        community_token_mint: &MintCookie,
+        payer: &Keypair,
+        voter_weight_addin: Option<Pubkey>,
     ) -> GovernanceRealmCookie {
         let realm = Pubkey::find_program_address(
             &[b"governance".as_ref(), name.as_ref()],
@@ -61,7 +162,8 @@ impl GovernanceCookie {
             &community_token_mint.pubkey.unwrap(),
             &payer.pubkey(),
             None,
-            Some(*voter_weight_addin),
+            voter_weight_addin,
+            None,
             name.to_string(),
             0,
             spl_governance::state::enums::MintMaxVoteWeightSource::SupplyFraction(10000000000),
@@ -120,4 +222,326 @@ impl GovernanceRealmCookie {
 
         TokenOwnerRecordCookie { address: record }
     }
+    pub async fn deposit_governing_tokens(
+        &self,
+        owner: &Keypair,
+        token_account: Pubkey,
+        payer: &Keypair,
+    ) -> TokenOwnerRecordCookie {
+        let community_token_mint = self.community_token_mint.pubkey.unwrap();
+        let amount = self
+            .governance
+            .solana
+            .token_account_balance(token_account)
+            .await;
+        let record = spl_governance::state::token_owner_record::get_token_owner_record_address(
+            &self.governance.program_id,
+            &self.realm,
+            &community_token_mint,
+            &owner.pubkey(),
+        );
+
+        let instructions = vec![spl_governance::instruction::deposit_governing_tokens(
+            &self.governance.program_id,
+            &self.realm,
+            &token_account,
+            &owner.pubkey(),
+            &owner.pubkey(),
+            &payer.pubkey(),
+            amount,
+            &community_token_mint,
+        )];
+
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&owner.to_base58_string());
+
+        self.governance
+            .solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+            .unwrap();
+
+        TokenOwnerRecordCookie { address: record }
+    }
+
+    /// Creates a governance over `governed_account` whose proposals pass
+    /// with a single yes vote and can be executed right away.
+    pub async fn create_account_governance(
+        &self,
+        governed_account: Pubkey,
+        token_owner_record: &TokenOwnerRecordCookie,
+        voter_weight: Option<&VoterWeightCookie>,
+        owner: &Keypair,
+        payer: &Keypair,
+    ) -> AccountGovernanceCookie {
+        let address = spl_governance::state::governance::get_governance_address(
+            &self.governance.program_id,
+            &self.realm,
+            &governed_account,
+        );
+
+        let config = GovernanceConfig {
+            vote_threshold_percentage: VoteThresholdPercentage::YesVote(60),
+            min_community_weight_to_create_proposal: 1,
+            min_transaction_hold_up_time: 0,
+            max_voting_time: 3600,
+            vote_tipping: VoteTipping::Strict,
+            proposal_cool_off_time: 0,
+            min_council_weight_to_create_proposal: 1,
+        };
+
+        let mut instructions = refresh_instructions(voter_weight);
+        instructions.push(spl_governance::instruction::create_governance(
+            &self.governance.program_id,
+            &self.realm,
+            Some(&governed_account),
+            &token_owner_record.address,
+            &payer.pubkey(),
+            &owner.pubkey(),
+            voter_weight.map(|v| v.address),
+            config,
+        ));
+
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&owner.to_base58_string());
+
+        self.governance
+            .solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+            .unwrap();
+
+        AccountGovernanceCookie { address }
+    }
+
+    pub fn proposal_address(
+        &self,
+        governance: &AccountGovernanceCookie,
+        proposal_index: u32,
+    ) -> Pubkey {
+        spl_governance::state::proposal::get_proposal_address(
+            &self.governance.program_id,
+            &governance.address,
+            &self.community_token_mint.pubkey.unwrap(),
+            &proposal_index.to_le_bytes(),
+        )
+    }
+
+    /// Creates a proposal with `instruction` and signs it off, so it is open
+    /// for voting.
+    pub async fn create_proposal(
+        &self,
+        governance: &AccountGovernanceCookie,
+        token_owner_record: &TokenOwnerRecordCookie,
+        voter_weight: Option<&VoterWeightCookie>,
+        owner: &Keypair,
+        payer: &Keypair,
+        proposal_index: u32,
+        instruction: Instruction,
+    ) -> (ProposalCookie, ProposalInstructionCookie) {
+        let program_id = &self.governance.program_id;
+        let community_token_mint = self.community_token_mint.pubkey.unwrap();
+        let proposal = self.proposal_address(governance, proposal_index);
+        let option_index: u8 = 0;
+        let instruction_index: u16 = 0;
+        let proposal_instruction =
+            spl_governance::state::proposal_transaction::get_proposal_transaction_address(
+                program_id,
+                &proposal,
+                &option_index.to_le_bytes(),
+                &instruction_index.to_le_bytes(),
+            );
+
+        let mut instructions = refresh_instructions(voter_weight);
+        instructions.extend(vec![
+            spl_governance::instruction::create_proposal(
+                program_id,
+                &governance.address,
+                &token_owner_record.address,
+                &owner.pubkey(),
+                &payer.pubkey(),
+                voter_weight.map(|v| v.address),
+                &self.realm,
+                "reconfigure".to_string(),
+                "".to_string(),
+                &community_token_mint,
+                VoteType::SingleChoice,
+                vec!["yes".to_string()],
+                true,
+                proposal_index,
+            ),
+            spl_governance::instruction::insert_transaction(
+                program_id,
+                &governance.address,
+                &proposal,
+                &token_owner_record.address,
+                &owner.pubkey(),
+                &payer.pubkey(),
+                option_index,
+                instruction_index,
+                0,
+                vec![instruction.clone().into()],
+            ),
+            spl_governance::instruction::add_signatory(
+                program_id,
+                &proposal,
+                &token_owner_record.address,
+                &owner.pubkey(),
+                &payer.pubkey(),
+                &owner.pubkey(),
+            ),
+            spl_governance::instruction::sign_off_proposal(
+                program_id,
+                &self.realm,
+                &governance.address,
+                &proposal,
+                &owner.pubkey(),
+                None,
+            ),
+        ]);
+
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&owner.to_base58_string());
+
+        self.governance
+            .solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+            .unwrap();
+
+        (
+            ProposalCookie { address: proposal },
+            ProposalInstructionCookie {
+                address: proposal_instruction,
+                instruction,
+            },
+        )
+    }
+
+    pub async fn cast_yes_vote(
+        &self,
+        governance: &AccountGovernanceCookie,
+        proposal: &ProposalCookie,
+        proposal_owner_record: &TokenOwnerRecordCookie,
+        voter_record: &TokenOwnerRecordCookie,
+        voter_weight: Option<&VoterWeightCookie>,
+        voter: &Keypair,
+        payer: &Keypair,
+    ) -> Result<(), TransportError> {
+        let mut instructions = refresh_instructions(voter_weight);
+        instructions.push(spl_governance::instruction::cast_vote(
+            &self.governance.program_id,
+            &self.realm,
+            &governance.address,
+            &proposal.address,
+            &proposal_owner_record.address,
+            &voter_record.address,
+            &voter.pubkey(),
+            &self.community_token_mint.pubkey.unwrap(),
+            &payer.pubkey(),
+            voter_weight.map(|v| v.address),
+            None,
+            Vote::Approve(vec![VoteChoice {
+                rank: 0,
+                weight_percentage: 100,
+            }]),
+        ));
+
+        let signer1 = Keypair::from_base58_string(&payer.to_base58_string());
+        let signer2 = Keypair::from_base58_string(&voter.to_base58_string());
+
+        self.governance
+            .solana
+            .process_transaction(&instructions, Some(&[&signer1, &signer2]))
+            .await
+    }
+
+    /// Relinquishes the vote of `voter_record` on the proposal, which must
+    /// be final unless `voter` withdraws the vote.
+    #[allow(dead_code)]
+    pub async fn relinquish_vote(
+        &self,
+        governance: &AccountGovernanceCookie,
+        proposal: &ProposalCookie,
+        voter_record: &TokenOwnerRecordCookie,
+        voter: &Keypair,
+    ) -> Result<(), TransportError> {
+        let instructions = vec![spl_governance::instruction::relinquish_vote(
+            &self.governance.program_id,
+            &governance.address,
+            &proposal.address,
+            &voter_record.address,
+            &self.community_token_mint.pubkey.unwrap(),
+            Some(voter.pubkey()),
+            Some(voter.pubkey()),
+        )];
+
+        let signer = Keypair::from_base58_string(&voter.to_base58_string());
+
+        self.governance
+            .solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    /// Cancels the proposal, which its owner can do at any time before
+    /// voting ends.
+    #[allow(dead_code)]
+    pub async fn cancel_proposal(
+        &self,
+        governance: &AccountGovernanceCookie,
+        proposal: &ProposalCookie,
+        proposal_owner_record: &TokenOwnerRecordCookie,
+        owner: &Keypair,
+    ) -> Result<(), TransportError> {
+        let instructions = vec![spl_governance::instruction::cancel_proposal(
+            &self.governance.program_id,
+            &self.realm,
+            &governance.address,
+            &proposal.address,
+            &proposal_owner_record.address,
+            &owner.pubkey(),
+        )];
+
+        let signer = Keypair::from_base58_string(&owner.to_base58_string());
+
+        self.governance
+            .solana
+            .process_transaction(&instructions, Some(&[&signer]))
+            .await
+    }
+
+    pub async fn execute_instruction(
+        &self,
+        governance: &AccountGovernanceCookie,
+        proposal: &ProposalCookie,
+        proposal_instruction: &ProposalInstructionCookie,
+    ) -> Result<(), TransportError> {
+        // The governance PDA signs inside spl-governance, not in the
+        // transaction.
+        let instruction_accounts = proposal_instruction
+            .instruction
+            .accounts
+            .iter()
+            .map(|a| AccountMeta {
+                pubkey: a.pubkey,
+                is_signer: false,
+                is_writable: a.is_writable,
+            })
+            .collect::<Vec<_>>();
+
+        let instructions = vec![spl_governance::instruction::execute_transaction(
+            &self.governance.program_id,
+            &governance.address,
+            &proposal.address,
+            &proposal_instruction.address,
+            &proposal_instruction.instruction.program_id,
+            &instruction_accounts,
+        )];
+
+        self.governance
+            .solana
+            .process_transaction(&instructions, None)
+            .await
+    }
 }
@@ -11,14 +11,18 @@ use spl_token::{state::*, *};
 
 pub use addin::*;
 pub use cookies::*;
+pub use cpi_forwarder::*;
 pub use governance::*;
 pub use solana::*;
+pub use stress::*;
 pub use utils::*;
 
 pub mod addin;
 pub mod cookies;
+pub mod cpi_forwarder;
 pub mod governance;
 pub mod solana;
+pub mod stress;
 pub mod utils;
 
 trait AddPacked {
@@ -49,6 +53,7 @@ pub struct TestContext {
     pub solana: Arc<SolanaCookie>,
     pub governance: GovernanceCookie,
     pub addin: AddinCookie,
+    pub cpi_forwarder: CpiForwarderCookie,
     pub mints: Vec<MintCookie>,
     pub users: Vec<UserCookie>,
     pub quote_index: usize,
@@ -56,6 +61,13 @@ pub struct TestContext {
 
 impl TestContext {
     pub async fn new() -> Self {
+        Self::new_with_options(4, 200000).await
+    }
+
+    /// A context with `num_users` funded users, in which every instruction
+    /// may use at most `compute_max_units`. Compute is only metered when the
+    /// program runs as BPF, with test-bpf.
+    pub async fn new_with_options(num_users: usize, compute_max_units: u64) -> Self {
         let addin_program_id = voter_weight_addin_deposits::id();
 
         let mut test = ProgramTest::new(
@@ -63,14 +75,29 @@ impl TestContext {
             addin_program_id,
             processor!(voter_weight_addin_deposits::entry),
         );
-        test.set_bpf_compute_max_units(200000);
+        test.set_bpf_compute_max_units(compute_max_units);
 
         let governance_program_id =
             Pubkey::from_str(&"GovernanceProgram11111111111111111111111111").unwrap();
         test.add_program(
             "spl_governance",
             governance_program_id,
-            processor!(spl_governance::processor::process_instruction),
+            processor!(governance::process_governance_instruction),
+        );
+
+        // Replaces the bundled BPF spl-token, which the program-test VM can't
+        // run on current compilers.
+        test.add_program(
+            "spl_token",
+            spl_token::id(),
+            processor!(spl_token::processor::Processor::process),
+        );
+
+        let cpi_forwarder_program_id = Pubkey::new_unique();
+        test.add_program(
+            "cpi_forwarder",
+            cpi_forwarder_program_id,
+            processor!(cpi_forwarder::process_instruction),
         );
 
         // Supress some of the logs
@@ -129,7 +156,6 @@ impl TestContext {
         let quote_index = mints.len() - 1;
 
         // Users
-        let num_users = 4;
         let mut users = Vec::new();
         for _ in 0..num_users {
             let user_key = Keypair::new();
@@ -186,6 +212,10 @@ impl TestContext {
                 solana: solana.clone(),
                 program_id: addin_program_id,
             },
+            cpi_forwarder: CpiForwarderCookie {
+                solana: solana.clone(),
+                program_id: cpi_forwarder_program_id,
+            },
             mints,
             users,
             quote_index,
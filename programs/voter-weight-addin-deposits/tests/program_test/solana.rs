@@ -6,6 +6,7 @@ use solana_program::{program_pack::Pack, rent::*, system_instruction};
 use solana_program_test::*;
 use solana_sdk::{
     account::ReadableAccount,
+    compute_budget::ComputeBudgetInstruction,
     instruction::Instruction,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
@@ -37,9 +38,9 @@ impl SolanaCookie {
             all_signers.extend_from_slice(signers);
         }
 
-        // This fails when warping is involved - https://gitmemory.com/issue/solana-labs/solana/18201/868325078
-        // let recent_blockhash = self.context.banks_client.get_recent_blockhash().await.unwrap();
-
+        // Fetching a recent blockhash here fails when warping is involved -
+        // https://gitmemory.com/issue/solana-labs/solana/18201/868325078
+        // The clock helpers refresh `last_blockhash` instead.
         transaction.sign(&all_signers, context.last_blockhash);
 
         context
@@ -49,6 +50,21 @@ impl SolanaCookie {
                 solana_sdk::commitment_config::CommitmentLevel::Processed,
             )
             .await
+            .map_err(|e| e.into())
+    }
+
+    /// Like `process_transaction`, but the transaction may use at most
+    /// `units` compute units. Compute is only metered with test-bpf.
+    #[allow(dead_code)]
+    pub async fn process_transaction_with_compute_limit(
+        &self,
+        instructions: &[Instruction],
+        signers: Option<&[&Keypair]>,
+        units: u32,
+    ) -> Result<(), TransportError> {
+        let mut limited = vec![ComputeBudgetInstruction::set_compute_unit_limit(units)];
+        limited.extend_from_slice(instructions);
+        self.process_transaction(&limited, signers).await
     }
 
     pub async fn get_bincode_account<T: serde::de::DeserializeOwned>(&self, address: &Pubkey) -> T {
@@ -69,6 +85,20 @@ impl SolanaCookie {
         .await
     }
 
+    /// Replaces `last_blockhash` with one that wasn't used yet, so that
+    /// sending a transaction again after changing the clock isn't rejected
+    /// as already processed.
+    async fn refresh_blockhash(&self) {
+        let mut context = self.context.borrow_mut();
+        let last_blockhash = context.last_blockhash;
+        context.last_blockhash = context
+            .banks_client
+            .get_new_blockhash(&last_blockhash)
+            .await
+            .unwrap()
+            .0;
+    }
+
     #[allow(dead_code)]
     pub async fn advance_clock_by_slots(&self, slots: u64) {
         let clock = self.get_clock().await;
@@ -76,6 +106,41 @@ impl SolanaCookie {
             .borrow_mut()
             .warp_to_slot(clock.slot + slots)
             .unwrap();
+        self.refresh_blockhash().await;
+    }
+
+    /// Sets the clock's unix timestamp, for testing lockups and cooldowns
+    /// without the `localnet-test` time offset. The slot stays the same.
+    #[allow(dead_code)]
+    pub async fn set_unix_timestamp(&self, unix_timestamp: i64) {
+        let mut clock = self.get_clock().await;
+        clock.unix_timestamp = unix_timestamp;
+        self.context.borrow_mut().set_sysvar(&clock);
+        self.refresh_blockhash().await;
+    }
+
+    /// Moves the clock's unix timestamp `secs` seconds forward.
+    #[allow(dead_code)]
+    pub async fn advance_unix_timestamp(&self, secs: i64) {
+        let clock = self.get_clock().await;
+        self.set_unix_timestamp(clock.unix_timestamp + secs).await;
+    }
+
+    /// Writes an account with `data`, owned by `owner`, at `address`, for
+    /// accounts of programs the tests don't run, like gateway tokens.
+    #[allow(dead_code)]
+    pub fn set_account(&self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let lamports = self.rent.minimum_balance(data.len());
+        let account = solana_sdk::account::Account {
+            lamports,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context
+            .borrow_mut()
+            .set_account(&address, &account.into());
     }
 
     #[allow(dead_code)]
@@ -106,6 +171,18 @@ impl SolanaCookie {
         return keypair.pubkey();
     }
 
+    /// Lamports of the account at `address`, zero if there is none.
+    #[allow(dead_code)]
+    pub async fn get_lamports(&self, address: Pubkey) -> u64 {
+        self.context
+            .borrow_mut()
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .map_or(0, |a| a.lamports)
+    }
+
     #[allow(dead_code)]
     pub async fn get_account_data(&self, address: Pubkey) -> Vec<u8> {
         self.context
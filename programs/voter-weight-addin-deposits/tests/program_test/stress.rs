@@ -0,0 +1,85 @@
+use solana_sdk::transport::TransportError;
+use voter_weight_addin_deposits as addin;
+
+use crate::*;
+
+/// A voter created by `create_voters_with_deposits`.
+pub struct StressVoter {
+    pub voter: VoterCookie,
+    pub token_owner_record: TokenOwnerRecordCookie,
+}
+
+/// Creates a voter for each of `users`, together with its token owner
+/// record. Each deposits `amount` of the community mint unlocked into entry
+/// 0 and `amount` locked in a 10 day cliff lockup into entry 1.
+#[allow(dead_code)]
+pub async fn create_voters_with_deposits(
+    context: &TestContext,
+    realm: &GovernanceRealmCookie,
+    registrar: &RegistrarCookie,
+    users: &[UserCookie],
+    amount: u64,
+) -> Result<Vec<StressVoter>, TransportError> {
+    let payer = &context.users[0].key;
+    let mut voters = Vec::with_capacity(users.len());
+    for user in users {
+        let (voter, token_owner_record) = context
+            .addin
+            .create_voter_with_token_owner_record(registrar, realm, &user.key, payer)
+            .await;
+        context
+            .addin
+            .create_deposit_entry(
+                registrar,
+                &voter,
+                &user.key,
+                1,
+                addin::account::LockupKind::Cliff,
+                10,
+            )
+            .await?;
+        for deposit_entry_index in 0..2 {
+            context
+                .addin
+                .deposit(
+                    registrar,
+                    &voter,
+                    &user.key,
+                    user.token_accounts[0],
+                    deposit_entry_index,
+                    amount,
+                )
+                .await?;
+        }
+        voters.push(StressVoter {
+            voter,
+            token_owner_record,
+        });
+    }
+    Ok(voters)
+}
+
+/// Checks that the registrar's vault holds exactly the tokens deposited by
+/// `voters`, and that the registrar's total for the community mint agrees.
+/// Returns that amount.
+#[allow(dead_code)]
+#[allow(unaligned_references)]
+pub async fn assert_deposits_conserved(
+    solana: &SolanaCookie,
+    registrar: &RegistrarCookie,
+    voters: &[StressVoter],
+) -> u64 {
+    let mut deposited = 0u64;
+    for voter in voters {
+        deposited += voter.voter.deposit_amount(solana).await;
+    }
+    assert_eq!(registrar.vault_balance(solana).await, deposited);
+    let registrar_data = solana
+        .get_account::<addin::account::Registrar>(registrar.address)
+        .await;
+    assert_eq!(
+        { registrar_data.voting_mints[0].total_deposited_native },
+        deposited
+    );
+    deposited
+}
@@ -1,6 +1,11 @@
+use std::convert::TryFrom;
+
 use bytemuck::{bytes_of, Contiguous};
 use solana_program::program_error::ProgramError;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use solana_sdk::transport::TransportError;
+use voter_weight_addin_deposits::error::ErrorCode;
 
 #[allow(dead_code)]
 pub fn gen_signer_seeds<'a>(nonce: &'a u64, acc_pk: &'a Pubkey) -> [&'a [u8]; 2] {
@@ -26,3 +31,52 @@ pub fn create_signer_key_and_nonce(program_id: &Pubkey, acc_pk: &Pubkey) -> (Pub
     }
     panic!("Could not generate signer key");
 }
+
+/// The error the failing instruction of a transaction returned, if any.
+#[allow(dead_code)]
+pub fn instruction_error<T>(result: Result<T, TransportError>) -> Option<ProgramError> {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(_, err))) => {
+            ProgramError::try_from(err).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Asserts that the transaction failed with `expected`, which may be an
+/// error of this program, of anchor or of spl-governance.
+#[allow(dead_code)]
+pub fn assert_instruction_error(
+    result: Result<(), TransportError>,
+    expected: impl Into<ProgramError>,
+) {
+    assert_eq!(instruction_error(result), Some(expected.into()));
+}
+
+/// The error of this program that a failed transaction returned, if any.
+#[allow(dead_code)]
+pub fn addin_error<T>(result: Result<T, TransportError>) -> Option<ErrorCode> {
+    match instruction_error(result)? {
+        ProgramError::Custom(code) => ErrorCode::from_code(code),
+        _ => None,
+    }
+}
+
+/// Asserts that a transaction failed with an error of this program, like
+/// `assert_addin_err!(result, ErrorCode::InsufficientVestedTokens)`.
+#[macro_export]
+macro_rules! assert_addin_err {
+    ($result:expr, $expected:expr) => {
+        let expected: voter_weight_addin_deposits::error::ErrorCode = $expected;
+        match $crate::program_test::addin_error($result) {
+            Some(actual) => assert_eq!(
+                u32::from(actual),
+                u32::from(expected),
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            ),
+            None => panic!("expected {:?}, got another result", expected),
+        }
+    };
+}
@@ -1,4 +1,3 @@
-use anchor_spl::token::TokenAccount;
 use solana_program_test::*;
 use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
 
@@ -30,6 +29,7 @@ async fn test_basic() -> Result<(), TransportError> {
         .await;
 
     let registrar = context.addin.create_registrar(&realm, payer).await;
+    context.addin.verify_registrar(&realm, &registrar).await?;
     let voter = context
         .addin
         .create_voter(&registrar, &voter_authority, &payer)
@@ -52,6 +52,7 @@ async fn test_basic() -> Result<(), TransportError> {
             &voter,
             &voter_authority,
             reference_account,
+            0,
             10000,
         )
         .await?;
@@ -72,6 +73,7 @@ async fn test_basic() -> Result<(), TransportError> {
             &token_owner_record,
             &voter_authority,
             reference_account,
+            0,
             10000,
         )
         .await.expect_err("fails because a deposit happened in the same slot");
@@ -87,6 +89,7 @@ async fn test_basic() -> Result<(), TransportError> {
             &token_owner_record,
             &voter_authority,
             reference_account,
+            0,
             10000,
         )
         .await?;
@@ -99,5 +102,39 @@ async fn test_basic() -> Result<(), TransportError> {
     let balance_after_withdraw = voter.deposit_amount(&context.solana).await;
     assert_eq!(balance_after_withdraw, 0);
 
+    // closing refunds the voter weight record's rent to its payer
+    let record_rent = context.solana.get_lamports(voter.voter_weight_record).await;
+    let payer_before_close = context.solana.get_lamports(payer.pubkey()).await;
+    context
+        .addin
+        .close_voter(
+            &registrar,
+            &voter,
+            &voter_authority,
+            voter_authority.pubkey(),
+            payer.pubkey(),
+        )
+        .await?;
+    assert_eq!(
+        context.solana.get_lamports(voter.voter_weight_record).await,
+        0
+    );
+    assert_eq!(
+        context.solana.get_lamports(payer.pubkey()).await,
+        payer_before_close + record_rent
+    );
+
+    // create_voter can create the token owner record
+    let voter2_authority = &context.users[2].key;
+    let (_voter2, token_owner_record2) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter2_authority, &payer)
+        .await;
+    let record2_data = context
+        .solana
+        .get_account_data(token_owner_record2.address)
+        .await;
+    assert!(!record2_data.is_empty());
+
     Ok(())
 }
@@ -0,0 +1,122 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::LockupKind;
+
+use program_test::*;
+
+mod program_test;
+
+// Compute units each instruction may use. The client's
+// `AddinInstruction::default_compute_units` must not be lower.
+//
+// update_voter_weight_record is sent in the same transaction as
+// spl-governance's CastVote, so it has the tightest budget.
+const CREATE_VOTER_COMPUTE_UNITS: u32 = 50_000;
+const DEPOSIT_COMPUTE_UNITS: u32 = 30_000;
+const UPDATE_VOTER_WEIGHT_RECORD_COMPUTE_UNITS: u32 = 15_000;
+const WITHDRAW_COMPUTE_UNITS: u32 = 50_000;
+
+// Each instruction is sent in a transaction limited to its budget, and fails
+// if it needs more. Compute is only metered when the program runs as BPF:
+// cargo test-bpf --test test_compute
+#[tokio::test]
+async fn test_compute_budgets() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let create_voter = context.addin.create_voter_instruction(
+        &registrar,
+        voter_authority.pubkey(),
+        payer.pubkey(),
+        vec![],
+    );
+    solana
+        .process_transaction_with_compute_limit(
+            &[create_voter],
+            Some(&[payer, voter_authority]),
+            CREATE_VOTER_COMPUTE_UNITS,
+        )
+        .await?;
+    let voter = context.addin.voter(&registrar, voter_authority.pubkey());
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    // the weight is most expensive with deposits of every lockup kind
+    let lockups = [
+        (1, LockupKind::Cliff),
+        (2, LockupKind::Daily),
+        (3, LockupKind::Constant),
+    ];
+    for &(index, kind) in lockups.iter() {
+        context
+            .addin
+            .create_deposit_entry(&registrar, &voter, &voter_authority, index, kind, 30)
+            .await?;
+    }
+    for index in 0..4 {
+        let deposit = context.addin.deposit_instruction(
+            &registrar,
+            &voter,
+            voter_authority.pubkey(),
+            &registrar.mint,
+            token_account,
+            index,
+            10000,
+        );
+        solana
+            .process_transaction_with_compute_limit(
+                &[deposit],
+                Some(&[voter_authority]),
+                DEPOSIT_COMPUTE_UNITS,
+            )
+            .await?;
+    }
+
+    let update = context
+        .addin
+        .update_voter_weight_record_instruction(&registrar, &voter);
+    solana
+        .process_transaction_with_compute_limit(
+            &[update],
+            None,
+            UPDATE_VOTER_WEIGHT_RECORD_COMPUTE_UNITS,
+        )
+        .await?;
+
+    solana.advance_clock_by_slots(2).await;
+    let withdraw = context.addin.withdraw_instruction(
+        &registrar,
+        &voter,
+        &token_owner_record,
+        voter_authority.pubkey(),
+        token_account,
+        0,
+        10000,
+    );
+    solana
+        .process_transaction_with_compute_limit(
+            &[withdraw],
+            Some(&[voter_authority]),
+            WITHDRAW_COMPUTE_UNITS,
+        )
+        .await?;
+
+    Ok(())
+}
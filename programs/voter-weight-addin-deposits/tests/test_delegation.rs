@@ -0,0 +1,687 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use spl_governance::error::GovernanceError;
+use voter_weight_addin_deposits::account::{
+    DelegationRecord, DelegationShareKind, LOCKUP_FACTOR_SCALE, SECS_PER_DAY,
+};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_delegation() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    context
+        .addin
+        .create_voter_delegations(&voter, &voter_authority, &payer)
+        .await?;
+
+    let delegate_authority = &context.users[2].key;
+    let (delegate_voter, delegate_token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &delegate_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &delegate_voter,
+            &delegate_authority,
+            context.users[2].token_accounts[0],
+            0,
+            3000,
+        )
+        .await?;
+    let other_authority = &context.users[3].key;
+    let other_voter = context
+        .addin
+        .create_voter(&registrar, &other_authority, &payer)
+        .await;
+
+    // a quarter to one delegate, half to another
+    for delegate in [&delegate_voter, &other_voter].iter() {
+        context
+            .addin
+            .create_delegation_record(&registrar, &voter, &voter_authority, delegate, &payer)
+            .await?;
+    }
+    let record = context
+        .solana
+        .get_account::<DelegationRecord>(voter.delegation_record(&delegate_voter))
+        .await;
+    assert_eq!(record.delegator, voter.address);
+    assert_eq!(record.delegate, delegate_voter.address);
+    context
+        .addin
+        .delegate(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            &delegate_voter,
+            0,
+            DelegationShareKind::Bps,
+            2500,
+        )
+        .await?;
+    context
+        .addin
+        .delegate(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            &other_voter,
+            0,
+            DelegationShareKind::Amount,
+            5000,
+        )
+        .await?;
+
+    context
+        .addin
+        .update_voter_weight_record_with_delegations(&registrar, &voter, &[])
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 2500);
+    context
+        .addin
+        .update_voter_weight_record_with_delegations(&registrar, &delegate_voter, &[&voter])
+        .await?;
+    assert_eq!(delegate_voter.voter_weight(&context.solana).await, 5500);
+    context
+        .addin
+        .update_voter_weight_record_with_delegations(&registrar, &other_voter, &[&voter])
+        .await?;
+    assert_eq!(other_voter.voter_weight(&context.solana).await, 5000);
+
+    // without its delegations the voter's own weight is unknown
+    let result = context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingDelegationsAccount);
+
+    // neither the cached path nor the crank know about delegations
+    for v in [&voter, &delegate_voter].iter() {
+        let result = context
+            .addin
+            .update_voter_weight_record_cached(&registrar, v)
+            .await;
+        assert_addin_err!(result, ErrorCode::DelegatedWeightUnsupported);
+        let result = context
+            .addin
+            .update_voter_weight_records(&registrar, &[v])
+            .await;
+        assert_addin_err!(result, ErrorCode::DelegatedWeightUnsupported);
+    }
+    assert_eq!(delegate_voter.voter_weight(&context.solana).await, 5500);
+
+    // the shares of an entry can't exceed its deposit
+    let result = context
+        .addin
+        .delegate(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            &other_voter,
+            0,
+            DelegationShareKind::Amount,
+            8000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::DelegationExceedsDeposit);
+    let result = context
+        .addin
+        .delegate(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            &other_voter,
+            0,
+            DelegationShareKind::Bps,
+            10001,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidDelegationShare);
+
+    // delegates can't delegate
+    context
+        .addin
+        .create_voter_delegations(&delegate_voter, &delegate_authority, &payer)
+        .await?;
+    context
+        .addin
+        .create_delegation_record(
+            &registrar,
+            &delegate_voter,
+            &delegate_authority,
+            &other_voter,
+            &payer,
+        )
+        .await?;
+    let result = context
+        .addin
+        .delegate(
+            &registrar,
+            &delegate_voter,
+            &delegate_token_owner_record,
+            &delegate_authority,
+            &other_voter,
+            0,
+            DelegationShareKind::Bps,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::DelegationChain);
+
+    // only voters delegating to the delegate can be passed
+    let result = context
+        .addin
+        .update_voter_weight_record_with_delegations(&registrar, &other_voter, &[&delegate_voter])
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidDelegator);
+
+    context
+        .addin
+        .undelegate(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &delegate_token_owner_record,
+        )
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .get_lamports(voter.delegation_record(&delegate_voter))
+            .await,
+        0
+    );
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .update_voter_weight_record_with_delegations(&registrar, &voter, &[])
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 5000);
+    let result = context
+        .addin
+        .update_voter_weight_record_with_delegations(&registrar, &delegate_voter, &[&voter])
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidDelegator);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undelegate_cooldown() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_undelegate_cooldown(&registrar, &realm_authority, SECS_PER_DAY as u64)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    context
+        .addin
+        .create_voter_delegations(&voter, &voter_authority, &payer)
+        .await?;
+
+    let delegate_authority = &context.users[2].key;
+    let (delegate_voter, delegate_token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &delegate_authority, &payer)
+        .await;
+    context
+        .addin
+        .create_delegation_record(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &payer,
+        )
+        .await?;
+    context
+        .addin
+        .delegate(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            &delegate_voter,
+            0,
+            DelegationShareKind::Bps,
+            5000,
+        )
+        .await?;
+
+    // undelegating needs a request first
+    let result = context
+        .addin
+        .undelegate(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &delegate_token_owner_record,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::UndelegateCooldownActive);
+    context
+        .addin
+        .request_undelegate(&registrar, &voter, &voter_authority, &delegate_voter)
+        .await?;
+
+    // while the cooldown runs, the share can't be reduced
+    let result = context
+        .addin
+        .delegate(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            &delegate_voter,
+            0,
+            DelegationShareKind::Bps,
+            4000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::DelegationShareReduced);
+    context.solana.advance_clock_by_slots(2).await;
+    let result = context
+        .addin
+        .undelegate(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &delegate_token_owner_record,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::UndelegateCooldownActive);
+
+    context.solana.advance_clock_by_slots(2).await;
+    context.solana.advance_unix_timestamp(SECS_PER_DAY).await;
+    context
+        .addin
+        .undelegate(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &delegate_token_owner_record,
+        )
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record_with_delegations(&registrar, &voter, &[])
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delegated_weight_votes_once() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let voter_token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            voter_token_account,
+            0,
+            10000,
+        )
+        .await?;
+    context
+        .addin
+        .create_voter_delegations(&voter, &voter_authority, &payer)
+        .await?;
+
+    let delegate_authority = &context.users[2].key;
+    let (delegate_voter, delegate_token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &delegate_authority, &payer)
+        .await;
+    context
+        .addin
+        .create_delegation_record(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &payer,
+        )
+        .await?;
+    context
+        .addin
+        .delegate(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            &delegate_voter,
+            0,
+            DelegationShareKind::Bps,
+            10000,
+        )
+        .await?;
+
+    // another voter owns the proposal, the delegate votes with the weight
+    let owner_authority = &context.users[3].key;
+    let (owner, owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &owner_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &owner,
+            &owner_authority,
+            context.users[3].token_accounts[0],
+            0,
+            1000,
+        )
+        .await?;
+    let owner_weight = context.addin.refreshed_voter_weight(&registrar, &owner);
+    let governance = realm
+        .create_account_governance(
+            registrar.address,
+            &owner_record,
+            Some(&owner_weight),
+            owner_authority,
+            payer,
+        )
+        .await;
+    let configure = context.addin.configure_lockup_params_instruction(
+        &registrar,
+        governance.address,
+        365 * 24 * 60 * 60,
+        LOCKUP_FACTOR_SCALE,
+    );
+    let (proposal, _) = realm
+        .create_proposal(
+            &governance,
+            &owner_record,
+            Some(&owner_weight),
+            owner_authority,
+            payer,
+            0,
+            configure,
+        )
+        .await;
+
+    let delegate_weight = context.addin.refreshed_voter_weight_with_delegations(
+        &registrar,
+        &delegate_voter,
+        &[&voter],
+    );
+    realm
+        .cast_yes_vote(
+            &governance,
+            &proposal,
+            &owner_record,
+            &delegate_token_owner_record,
+            Some(&delegate_weight),
+            delegate_authority,
+            payer,
+        )
+        .await?;
+    assert_eq!(proposal.yes_votes(&context.solana).await, 10000);
+    context.solana.advance_clock_by_slots(2).await;
+
+    // the delegated tokens can't leave while the delegate's vote stands
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            voter_token_account,
+            0,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingDelegationsAccount);
+    let result = context
+        .addin
+        .withdraw_with_delegates(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            voter_token_account,
+            0,
+            10000,
+            &[],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingDelegateAccounts);
+    let result = context
+        .addin
+        .withdraw_with_delegates(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            voter_token_account,
+            0,
+            10000,
+            &[(&delegate_voter, &delegate_token_owner_record)],
+        )
+        .await;
+    assert_instruction_error(
+        result,
+        GovernanceError::AllVotesMustBeRelinquishedToWithdrawGoverningTokens,
+    );
+
+    // nor can it leave as dust
+    context
+        .addin
+        .configure_min_withdraw(&registrar, &realm_authority, 0, 20000)
+        .await?;
+    let result = context
+        .addin
+        .sweep_dust(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            voter_token_account,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingDelegationsAccount);
+    let result = context
+        .addin
+        .sweep_dust_with_delegates(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            voter_token_account,
+            &[],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingDelegateAccounts);
+    let result = context
+        .addin
+        .sweep_dust_with_delegates(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            voter_token_account,
+            &[(&delegate_voter, &delegate_token_owner_record)],
+        )
+        .await;
+    assert_instruction_error(
+        result,
+        GovernanceError::AllVotesMustBeRelinquishedToWithdrawGoverningTokens,
+    );
+    assert_eq!(voter.deposit_amount(&context.solana).await, 10000);
+
+    // nor can the weight come back to vote again
+    let result = context
+        .addin
+        .undelegate(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &delegate_token_owner_record,
+        )
+        .await;
+    assert_instruction_error(
+        result,
+        GovernanceError::AllVotesMustBeRelinquishedToWithdrawGoverningTokens,
+    );
+
+    // and delegate can't shrink the share or change its kind instead
+    let reduced_shares = vec![
+        (DelegationShareKind::Bps, 1),
+        (DelegationShareKind::Amount, 10000),
+    ];
+    for (share_kind, share) in reduced_shares {
+        let result = context
+            .addin
+            .delegate(
+                &registrar,
+                &voter,
+                &token_owner_record,
+                &voter_authority,
+                &delegate_voter,
+                0,
+                share_kind,
+                share,
+            )
+            .await;
+        assert_addin_err!(result, ErrorCode::DelegationShareReduced);
+    }
+
+    // the vote decided the proposal, once it is relinquished they can
+    realm
+        .relinquish_vote(
+            &governance,
+            &proposal,
+            &delegate_token_owner_record,
+            delegate_authority,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .withdraw_with_delegates(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            voter_token_account,
+            0,
+            10000,
+            &[(&delegate_voter, &delegate_token_owner_record)],
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+    context
+        .addin
+        .undelegate(
+            &registrar,
+            &voter,
+            &voter_authority,
+            &delegate_voter,
+            &delegate_token_owner_record,
+        )
+        .await?;
+
+    Ok(())
+}
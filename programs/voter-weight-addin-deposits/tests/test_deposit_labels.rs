@@ -0,0 +1,75 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{DepositLabels, LockupKind};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_deposit_labels() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            30,
+        )
+        .await?;
+    context
+        .addin
+        .create_deposit_labels(&voter, &voter_authority, &payer)
+        .await?;
+
+    context
+        .addin
+        .set_deposit_label(&voter, &voter_authority, 1, "team-grant-2024")
+        .await?;
+    let labels = context
+        .solana
+        .get_account::<DepositLabels>(voter.deposit_labels())
+        .await;
+    assert_eq!(labels.label(0), Some(""));
+    assert_eq!(labels.label(1), Some("team-grant-2024"));
+
+    // only entries in use can be labeled
+    let result = context
+        .addin
+        .set_deposit_label(&voter, &voter_authority, 2, "unused")
+        .await;
+    assert_addin_err!(result, ErrorCode::DepositEntryNotFound);
+
+    // and only by the voter authority
+    let result = context
+        .addin
+        .set_deposit_label(&voter, &context.users[2].key, 1, "stolen")
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
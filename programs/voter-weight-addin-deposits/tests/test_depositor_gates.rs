@@ -0,0 +1,447 @@
+use anchor_lang::AnchorSerialize;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::AccountMeta, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transport::TransportError,
+};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_freeze_voter() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    // only the registrar authority can freeze
+    context
+        .addin
+        .freeze_voter(&registrar, &voter, &voter_authority, true)
+        .await
+        .expect_err("not the registrar authority");
+    context
+        .addin
+        .freeze_voter(&registrar, &voter, &realm_authority, true)
+        .await?;
+
+    // a frozen voter has no weight and can't deposit
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 0);
+    let result = context
+        .addin
+        .deposit(&registrar, &voter, &voter_authority, token_account, 0, 1000)
+        .await;
+    assert_addin_err!(result, ErrorCode::VoterFrozen);
+
+    // but it can withdraw
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            4000,
+        )
+        .await?;
+
+    // unfreezing restores both
+    context
+        .addin
+        .freeze_voter(&registrar, &voter, &realm_authority, false)
+        .await?;
+    context
+        .addin
+        .deposit(&registrar, &voter, &voter_authority, token_account, 0, 1000)
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 7000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_allowlist() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_allowlist_required(&registrar, &realm_authority, true)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+
+    // a wallet that isn't listed can't create a voter
+    let result = context
+        .addin
+        .try_create_voter(&registrar, &voter_authority, &payer, vec![])
+        .await;
+    assert_addin_err!(result, ErrorCode::NotAllowlisted);
+
+    // nor with the entry of another wallet
+    let other_entry = context
+        .addin
+        .add_to_allowlist(
+            &registrar,
+            &realm_authority,
+            &payer,
+            context.users[2].key.pubkey(),
+        )
+        .await?;
+    let result = context
+        .addin
+        .try_create_voter(
+            &registrar,
+            &voter_authority,
+            &payer,
+            vec![AccountMeta::new_readonly(other_entry, false)],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::NotAllowlisted);
+
+    // once listed, it can create a voter and deposit with its entry
+    let entry = context
+        .addin
+        .add_to_allowlist(
+            &registrar,
+            &realm_authority,
+            &payer,
+            voter_authority.pubkey(),
+        )
+        .await?;
+    let voter = context
+        .addin
+        .try_create_voter(
+            &registrar,
+            &voter_authority,
+            &payer,
+            vec![AccountMeta::new_readonly(entry, false)],
+        )
+        .await?;
+
+    let result = context
+        .addin
+        .deposit_with_accounts(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+            vec![],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::NotAllowlisted);
+    context
+        .addin
+        .deposit_with_accounts(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+            vec![AccountMeta::new_readonly(entry, false)],
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 10000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_blocklist() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    let blocked_authority = &context.users[2].key;
+    let blocked_entry = context
+        .addin
+        .add_to_blocklist(
+            &registrar,
+            &realm_authority,
+            &payer,
+            blocked_authority.pubkey(),
+        )
+        .await?;
+
+    // a blocked wallet can't create a voter, and leaving out its entry
+    // doesn't help
+    let result = context
+        .addin
+        .try_create_voter(
+            &registrar,
+            &blocked_authority,
+            &payer,
+            vec![AccountMeta::new_readonly(blocked_entry, false)],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::Blocklisted);
+    let result = context
+        .addin
+        .try_create_voter(&registrar, &blocked_authority, &payer, vec![])
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingBlocklistAccount);
+
+    // other wallets must pass the address of their entry, which doesn't
+    // exist, and not some other one
+    let (entry, _) = voter_weight_addin_deposits::seeds::find_blocklist_entry_address(
+        &registrar.address,
+        &voter_authority.pubkey(),
+    );
+    let result = context
+        .addin
+        .deposit_with_accounts(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+            vec![],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingBlocklistAccount);
+    let result = context
+        .addin
+        .deposit_with_accounts(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+            vec![AccountMeta::new_readonly(blocked_entry, false)],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingBlocklistAccount);
+    context
+        .addin
+        .deposit_with_accounts(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+            vec![AccountMeta::new_readonly(entry, false)],
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 10000);
+
+    let other_authority = &context.users[3].key;
+    let (other_entry, _) = voter_weight_addin_deposits::seeds::find_blocklist_entry_address(
+        &registrar.address,
+        &other_authority.pubkey(),
+    );
+    context
+        .addin
+        .try_create_voter(
+            &registrar,
+            &other_authority,
+            &payer,
+            vec![AccountMeta::new_readonly(other_entry, false)],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Data of a gateway token of `network` for `wallet`. `state` 0 is active,
+/// 1 revoked.
+fn gateway_token_data(
+    wallet: Pubkey,
+    network: Pubkey,
+    state: u8,
+    expire_time: Option<i64>,
+) -> Vec<u8> {
+    let features = 0u8;
+    let parent_gateway_token: Option<Pubkey> = None;
+    let owner_identity: Option<Pubkey> = None;
+    let issuing_gatekeeper = Pubkey::new_unique();
+    (
+        features,
+        parent_gateway_token,
+        wallet,
+        owner_identity,
+        network,
+        issuing_gatekeeper,
+        state,
+        expire_time,
+    )
+        .try_to_vec()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_gateway_token() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let gatekeeper_program = Pubkey::new_unique();
+    let gatekeeper_network = Pubkey::new_unique();
+    context
+        .addin
+        .configure_gatekeeper(
+            &registrar,
+            &realm_authority,
+            gatekeeper_program,
+            gatekeeper_network,
+        )
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let wallet = voter_authority.pubkey();
+    let now = context.solana.get_clock().await.unix_timestamp;
+
+    let result = context
+        .addin
+        .try_create_voter(&registrar, &voter_authority, &payer, vec![])
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingGatewayToken);
+
+    // tokens that don't attest the wallet: owned by another program, for
+    // another wallet or network, revoked or expired
+    let invalid_tokens = vec![
+        (
+            Pubkey::new_unique(),
+            gateway_token_data(wallet, gatekeeper_network, 0, None),
+        ),
+        (
+            gatekeeper_program,
+            gateway_token_data(Pubkey::new_unique(), gatekeeper_network, 0, None),
+        ),
+        (
+            gatekeeper_program,
+            gateway_token_data(wallet, Pubkey::new_unique(), 0, None),
+        ),
+        (
+            gatekeeper_program,
+            gateway_token_data(wallet, gatekeeper_network, 1, None),
+        ),
+        (
+            gatekeeper_program,
+            gateway_token_data(wallet, gatekeeper_network, 0, Some(now)),
+        ),
+    ];
+    for (owner, data) in invalid_tokens {
+        let token = Pubkey::new_unique();
+        context.solana.set_account(token, owner, data);
+        let result = context
+            .addin
+            .try_create_voter(
+                &registrar,
+                &voter_authority,
+                &payer,
+                vec![AccountMeta::new_readonly(token, false)],
+            )
+            .await;
+        assert_addin_err!(result, ErrorCode::MissingGatewayToken);
+    }
+
+    let token = Pubkey::new_unique();
+    context.solana.set_account(
+        token,
+        gatekeeper_program,
+        gateway_token_data(wallet, gatekeeper_network, 0, Some(now + 60)),
+    );
+    context
+        .addin
+        .try_create_voter(
+            &registrar,
+            &voter_authority,
+            &payer,
+            vec![AccountMeta::new_readonly(token, false)],
+        )
+        .await?;
+
+    Ok(())
+}
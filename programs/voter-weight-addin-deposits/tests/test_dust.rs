@@ -0,0 +1,108 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_min_withdraw_and_sweep_dust() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_min_withdraw(&registrar, &realm_authority, 0, 1000)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // amounts below the minimum can't be withdrawn
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            999,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::WithdrawBelowMinimum);
+
+    // leaving less than the minimum behind is fine
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            9995,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 5);
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            3,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::WithdrawBelowMinimum);
+
+    // and it is swept in full
+    context
+        .addin
+        .sweep_dust(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+    assert_eq!(registrar.vault_balance(&context.solana).await, 0);
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use solana_program_test::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::LockupKind;
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_unlock_early() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let mint = context.mints[0].pubkey.unwrap();
+
+    // lock 10000 tokens for 10 days
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            10,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            1,
+            10000,
+        )
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert!(voter.voter_weight(&context.solana).await > 10000);
+
+    let treasury_owner = Pubkey::new_unique();
+    let treasury = context
+        .solana
+        .create_token_account(&treasury_owner, mint)
+        .await;
+    let other_account = context
+        .solana
+        .create_token_account(&Pubkey::new_unique(), mint)
+        .await;
+
+    // early unlocks are off until a treasury is configured, even into an
+    // account of the unset treasury
+    let unset_treasury = context
+        .solana
+        .create_token_account(&Pubkey::default(), mint)
+        .await;
+    let result = context
+        .addin
+        .unlock_early(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            unset_treasury,
+            1,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::EarlyUnlockDisabled);
+
+    let result = context
+        .addin
+        .configure_early_unlock(&registrar, &realm_authority, treasury_owner, 10001)
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidEarlyUnlockPenalty);
+    context
+        .addin
+        .configure_early_unlock(&registrar, &realm_authority, treasury_owner, 1000)
+        .await?;
+
+    // the penalty only goes to the treasury's token accounts
+    context
+        .addin
+        .unlock_early(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            other_account,
+            1,
+        )
+        .await
+        .expect_err("not a token account of the treasury");
+
+    // a tenth of the locked tokens go to the treasury, the rest is unlocked
+    // and only has the weight of the deposit
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .unlock_early(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            treasury,
+            1,
+        )
+        .await?;
+    assert_eq!(context.solana.token_account_balance(treasury).await, 1000);
+    assert_eq!(registrar.vault_balance(&context.solana).await, 9000);
+    assert_eq!(voter.deposit_amount(&context.solana).await, 9000);
+    assert_eq!(voter.voter_weight(&context.solana).await, 9000);
+
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            1,
+            9000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    Ok(())
+}
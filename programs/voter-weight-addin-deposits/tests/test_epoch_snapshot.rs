@@ -0,0 +1,74 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::EpochSnapshot;
+use voter_weight_addin_deposits::error::ErrorCode;
+use voter_weight_addin_deposits::seeds::find_epoch_snapshot_address;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_snapshot_epoch() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+
+    // only the current epoch can be snapshotted
+    let epoch = context.solana.get_clock().await.epoch;
+    let result = context
+        .addin
+        .snapshot_epoch(&registrar, epoch + 1, payer)
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidEpoch);
+
+    context
+        .addin
+        .snapshot_epoch(&registrar, epoch, payer)
+        .await?;
+    let snapshot = context
+        .solana
+        .get_account::<EpochSnapshot>(find_epoch_snapshot_address(&registrar.address, epoch).0)
+        .await;
+    assert_eq!(snapshot.registrar, registrar.address);
+    assert_eq!(snapshot.epoch, epoch);
+    assert_eq!(snapshot.total_deposited_native, [10000, 0, 0, 0]);
+
+    // and only once
+    let result = context
+        .addin
+        .snapshot_epoch(&registrar, epoch, &context.users[2].key)
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use solana_program::{program_option::COption, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{Registrar, LOCKUP_FACTOR_SCALE};
+
+use program_test::*;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_governance_config() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    // The voters of this realm deposit into spl-governance directly; only
+    // the registrar's configuration is under test.
+    let realm = context
+        .governance
+        .create_realm_with_options(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            None,
+        )
+        .await;
+
+    // the test mints start without supply, which would defeat every proposal
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(
+        spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000_000_000_000_000,
+            decimals: context.mints[0].decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    context
+        .solana
+        .set_account(context.mints[0].pubkey.unwrap(), spl_token::id(), data);
+
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    // the realm doesn't list the addin
+    assert!(context
+        .addin
+        .verify_registrar(&realm, &registrar)
+        .await
+        .is_err());
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .deposit_governing_tokens(voter_authority, context.users[1].token_accounts[0], payer)
+        .await;
+    let governance = realm
+        .create_account_governance(
+            registrar.address,
+            &token_owner_record,
+            None,
+            voter_authority,
+            payer,
+        )
+        .await;
+
+    // hand the registrar over to the governance
+    context
+        .addin
+        .configure_registrar_authority(&registrar, &realm_authority, governance.address)
+        .await?;
+    assert!(context
+        .addin
+        .configure_lockup_params(&registrar, &realm_authority, 1, 0)
+        .await
+        .is_err());
+
+    let lockup_saturation_secs = 365 * 24 * 60 * 60;
+    let max_extra_lockup_vote_weight_factor = 2 * LOCKUP_FACTOR_SCALE;
+    let configure = context.addin.configure_lockup_params_instruction(
+        &registrar,
+        governance.address,
+        lockup_saturation_secs,
+        max_extra_lockup_vote_weight_factor,
+    );
+    let (proposal, proposal_instruction) = realm
+        .create_proposal(
+            &governance,
+            &token_owner_record,
+            None,
+            voter_authority,
+            payer,
+            0,
+            configure,
+        )
+        .await;
+
+    // not executable before the proposal passed
+    assert!(realm
+        .execute_instruction(&governance, &proposal, &proposal_instruction)
+        .await
+        .is_err());
+
+    realm
+        .cast_yes_vote(
+            &governance,
+            &proposal,
+            &token_owner_record,
+            &token_owner_record,
+            None,
+            voter_authority,
+            payer,
+        )
+        .await?;
+    // spl-governance executes only after the second the vote completed in
+    context.solana.advance_unix_timestamp(1).await;
+    realm
+        .execute_instruction(&governance, &proposal, &proposal_instruction)
+        .await?;
+
+    let registrar_data = context
+        .solana
+        .get_account::<Registrar>(registrar.address)
+        .await;
+    assert_eq!(
+        { registrar_data.lockup_saturation_secs },
+        lockup_saturation_secs
+    );
+    assert_eq!(
+        { registrar_data.max_extra_lockup_vote_weight_factor },
+        max_extra_lockup_vote_weight_factor
+    );
+    assert_eq!(registrar_data.authority, governance.address);
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use proptest::prelude::*;
+use voter_weight_addin_deposits::account::*;
+
+const NUM_ENTRIES: usize = 4;
+const START_TS: i64 = 1_600_000_000;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Deposit {
+        entry: usize,
+        amount: u64,
+    },
+    /// Withdraws `bps` basis points of the unlocked amount.
+    Withdraw {
+        entry: usize,
+        bps: u64,
+    },
+    AdvanceClock {
+        secs: i64,
+    },
+}
+
+fn lockup_kind() -> impl Strategy<Value = LockupKind> {
+    prop_oneof![
+        Just(LockupKind::None),
+        Just(LockupKind::Cliff),
+        Just(LockupKind::Daily),
+        Just(LockupKind::Constant),
+    ]
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..NUM_ENTRIES, 1..1_000_000_000_000u64)
+            .prop_map(|(entry, amount)| Op::Deposit { entry, amount }),
+        (0..NUM_ENTRIES, 0..=10_000u64).prop_map(|(entry, bps)| Op::Withdraw { entry, bps }),
+        prop_oneof![
+            0..SECS_PER_DAY,
+            SECS_PER_DAY..400 * SECS_PER_DAY,
+            Just(SECS_PER_DAY),
+        ]
+        .prop_map(|secs| Op::AdvanceClock { secs }),
+    ]
+}
+
+fn weight_config() -> WeightConfig {
+    let mut mint_rates = [0; MAX_VOTING_MINTS];
+    mint_rates[0] = 1;
+    WeightConfig {
+        mint_rates,
+        mint_digit_shifts: [0; MAX_VOTING_MINTS],
+        lockup_saturation_secs: DEFAULT_LOCKUP_SATURATION_SECS,
+        max_extra_lockup_vote_weight_factor: DEFAULT_MAX_EXTRA_LOCKUP_VOTE_WEIGHT_FACTOR,
+        vote_escrow_weight: false,
+        twab_window_secs: 0,
+        quadratic_weight: false,
+        max_voter_weight_per_voter: 0,
+        weight_action_bps: [BPS_SCALE; VOTER_WEIGHT_ACTION_COUNT],
+    }
+}
+
+fn voting_mint_config() -> VotingMintConfig {
+    VotingMintConfig {
+        mint: solana_sdk::pubkey::Pubkey::new_unique(),
+        rate: 1,
+        min_deposit_native: 0,
+        min_remaining_native: 0,
+        max_voter_deposit_native: 0,
+        total_deposited_native: 0,
+        max_total_deposit_native: 0,
+        digit_shift: 0,
+        oracle: Default::default(),
+    }
+}
+
+/// Deposits and withdraws the way the program's instructions do, keeping
+/// track of the tokens that would be in the vault.
+struct Model {
+    config: WeightConfig,
+    mint_config: VotingMintConfig,
+    entries: Vec<DepositEntry>,
+    vault: u64,
+    curr_ts: i64,
+}
+
+impl Model {
+    fn new(lockups: &[(LockupKind, u32)]) -> Self {
+        let entries = lockups
+            .iter()
+            .map(|&(kind, periods)| {
+                let periods = if kind == LockupKind::None { 0 } else { periods };
+                let lockup = Lockup::new_from_periods(kind, START_TS, periods).unwrap();
+                DepositEntry::new(lockup, 0)
+            })
+            .collect();
+        Self {
+            config: weight_config(),
+            mint_config: voting_mint_config(),
+            entries,
+            vault: 0,
+            curr_ts: START_TS,
+        }
+    }
+
+    fn apply(&mut self, op: &Op) {
+        match *op {
+            Op::Deposit { entry, amount } => {
+                self.entries[entry].deposit(amount, self.curr_ts).unwrap();
+                self.mint_config.track_deposit(amount).unwrap();
+                self.vault += amount;
+            }
+            Op::Withdraw { entry, bps } => {
+                let deposit = &mut self.entries[entry];
+                let unlocked = deposit.amount_unlocked(self.curr_ts);
+                let amount = (unlocked as u128 * bps as u128 / BPS_SCALE as u128) as u64;
+                deposit.amount_deposited_native -= amount;
+                self.mint_config.track_withdraw(amount);
+                self.vault -= amount;
+            }
+            Op::AdvanceClock { secs } => self.curr_ts += secs,
+        }
+    }
+
+    fn entry_weights(&self) -> Vec<u64> {
+        self.entries
+            .iter()
+            .map(|e| {
+                e.voting_power_with_config(&self.config, self.curr_ts)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn check_invariants(&self) {
+        let mut deposited = 0u64;
+        for entry in self.entries.iter() {
+            let amount = entry.amount_deposited_native;
+            let locked = entry.amount_locked(self.curr_ts);
+            assert!(locked <= amount);
+            assert_eq!(entry.amount_unlocked(self.curr_ts), amount - locked);
+
+            // The deposited amount has full weight, the locked amount up to
+            // the maximum extra factor on top.
+            let weight = entry
+                .voting_power_with_config(&self.config, self.curr_ts)
+                .unwrap();
+            let max_extra = (locked as u128
+                * self.config.max_extra_lockup_vote_weight_factor as u128
+                / LOCKUP_FACTOR_SCALE as u128) as u64;
+            assert!(weight >= amount);
+            assert!(weight <= amount + max_extra);
+            deposited += amount;
+        }
+        assert_eq!(self.vault, deposited);
+        assert_eq!({ self.mint_config.total_deposited_native }, deposited);
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_deposit_withdraw_invariants(
+        lockups in proptest::collection::vec((lockup_kind(), 1..2000u32), NUM_ENTRIES),
+        ops in proptest::collection::vec(op(), 1..50),
+    ) {
+        let mut model = Model::new(&lockups);
+        model.check_invariants();
+        for op in ops.iter() {
+            let weights_before = model.entry_weights();
+            let locked_before: Vec<u64> = model
+                .entries
+                .iter()
+                .map(|e| e.amount_locked(model.curr_ts))
+                .collect();
+
+            model.apply(op);
+            model.check_invariants();
+
+            let weights_after = model.entry_weights();
+            let weight_before: u64 = weights_before.iter().sum();
+            let weight_after: u64 = weights_after.iter().sum();
+            match *op {
+                Op::Deposit { .. } => prop_assert!(weight_after >= weight_before),
+                Op::Withdraw { .. } => prop_assert!(weight_after <= weight_before),
+                Op::AdvanceClock { .. } => {
+                    // Locked amounts only ever unlock as time passes. Daily
+                    // vesting rounds the locked amount per period, which can
+                    // raise its weight slightly when a period ends, so only
+                    // the other kinds have non-increasing weights.
+                    for (i, entry) in model.entries.iter().enumerate() {
+                        prop_assert!(entry.amount_locked(model.curr_ts) <= locked_before[i]);
+                        if entry.lockup.kind != LockupKind::Daily {
+                            prop_assert!(weights_after[i] <= weights_before[i]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
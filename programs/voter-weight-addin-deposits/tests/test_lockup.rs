@@ -0,0 +1,480 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{compute_voter_weight, LockupKind, Registrar, Voter};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_cliff_lockup() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+
+    // lock 10000 tokens for 10 days
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            10,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            reference_account,
+            1,
+            10000,
+        )
+        .await?;
+
+    context.solana.advance_clock_by_slots(2).await;
+
+    // the entry already exists
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            10,
+        )
+        .await
+        .expect_err("deposit entry 1 is already in use");
+
+    // tokens are still locked
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            reference_account,
+            1,
+            1,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientVestedTokens);
+
+    // locked tokens get extra weight for the remaining lockup time
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    let weight = voter.voter_weight(&context.solana).await;
+    assert!(weight > 10000);
+    assert!(weight < 20000);
+    assert_eq!(voter.deposit_amount(&context.solana).await, 10000);
+
+    // clients compute the same weight off-chain
+    let now = context.solana.get_clock().await.unix_timestamp;
+    let registrar_data = context
+        .solana
+        .get_account::<Registrar>(registrar.address)
+        .await;
+    let voter_data = context.solana.get_account::<Voter>(voter.address).await;
+    assert_eq!(
+        compute_voter_weight(&voter_data, &registrar_data, now),
+        weight
+    );
+
+    // the weight from the settings cached in the voter is the same
+    context
+        .addin
+        .update_voter_weight_record_cached(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, weight);
+
+    // after a settings change the cached copy is outdated until refreshed
+    context
+        .addin
+        .configure_quadratic_weight(&registrar, &realm_authority, true)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    let result = context
+        .addin
+        .update_voter_weight_record_cached(&registrar, &voter)
+        .await;
+    assert_addin_err!(result, ErrorCode::WeightConfigStale);
+
+    context
+        .addin
+        .refresh_weight_config(&registrar, &voter)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    let quadratic_weight = voter.voter_weight(&context.solana).await;
+    assert!(quadratic_weight < weight);
+    context
+        .addin
+        .update_voter_weight_record_cached(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, quadratic_weight);
+
+    Ok(())
+}
+
+#[cfg(feature = "localnet-test")]
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_cliff_lockup_expiry() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+
+    // lock 10000 tokens for 10 days
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            10,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            reference_account,
+            1,
+            10000,
+        )
+        .await?;
+
+    context.solana.advance_clock_by_slots(2).await;
+
+    // one day before the end of the lockup
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, 9 * 24 * 60 * 60)
+        .await?;
+    // tokens are still locked
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            reference_account,
+            1,
+            1,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientVestedTokens);
+
+    // after the end of the lockup
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, 10 * 24 * 60 * 60)
+        .await?;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            reference_account,
+            1,
+            10000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    Ok(())
+}
+
+// Lockup changes don't count retroactively towards the time-weighted
+// average: the weight right after them is the weight right before.
+#[cfg(feature = "localnet-test")]
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_lockup_changes_keep_twab() -> Result<(), TransportError> {
+    use voter_weight_addin_deposits::account::VoterWeightAction;
+
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_twab_window(&registrar, &realm_authority, 10 * 24 * 60 * 60)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    let reference_account = context.users[1].token_accounts[0];
+
+    // 10000 unlocked tokens, and 10000 locked for 10 days
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            reference_account,
+            0,
+            10000,
+        )
+        .await?;
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Constant,
+            10,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            reference_account,
+            1,
+            10000,
+        )
+        .await?;
+
+    // half the window later
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, 5 * 24 * 60 * 60)
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    let weight = voter.voter_weight(&context.solana).await;
+    assert!(weight > 0);
+
+    // a longer lockup raises the weight only from now on. Each update is
+    // for another action, so the transactions differ without moving the
+    // clock.
+    context
+        .addin
+        .reset_lockup(&registrar, &voter, &voter_authority, 1, 20)
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record_for_action(&registrar, &voter, VoterWeightAction::CastVote)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, weight);
+
+    // and so does locking the unlocked tokens
+    context
+        .addin
+        .internal_transfer(&registrar, &voter, &voter_authority, 0, 1, 10000)
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record_for_action(
+            &registrar,
+            &voter,
+            VoterWeightAction::CommentProposal,
+        )
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, weight);
+
+    // the higher weight is averaged in as time passes
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, 10 * 24 * 60 * 60)
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record_for_action(
+            &registrar,
+            &voter,
+            VoterWeightAction::CreateGovernance,
+        )
+        .await?;
+    assert!(voter.voter_weight(&context.solana).await > weight);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cliff_lockup_expiry_by_clock() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let token_owner_record = realm
+        .create_token_owner_record(voter_authority.pubkey(), &payer)
+        .await;
+
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+
+    // lock 10000 tokens for 10 days
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            10,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            reference_account,
+            1,
+            10000,
+        )
+        .await?;
+
+    // warping resets the unix timestamp, so advance the slots first
+    context.solana.advance_clock_by_slots(2).await;
+
+    // one day before the end of the lockup
+    context
+        .solana
+        .advance_unix_timestamp(9 * 24 * 60 * 60)
+        .await;
+    // tokens are still locked
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            reference_account,
+            1,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientVestedTokens);
+
+    // after the end of the lockup, the same withdraw goes through
+    context.solana.advance_unix_timestamp(24 * 60 * 60).await;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            reference_account,
+            1,
+            10000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use solana_program::{program_option::COption, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::AccountMeta, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transport::TransportError,
+};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_max_vote_weight() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .create_max_voter_weight_record(&registrar, &payer)
+        .await?;
+
+    // the test mints start without supply
+    let mint = context.mints[0].pubkey.unwrap();
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(
+        spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: context.mints[0].decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    context.solana.set_account(mint, spl_token::id(), data);
+    let mint_account = AccountMeta::new_readonly(mint, false);
+
+    // with the maximum lockup, every token has twice the weight
+    context
+        .addin
+        .update_max_vote_weight(&registrar, vec![mint_account.clone()])
+        .await?;
+    assert_eq!(registrar.max_voter_weight(&context.solana).await, 2_000_000);
+
+    // a quarter of the supply is in the treasury and doesn't circulate
+    let treasury = context
+        .solana
+        .create_token_account(&Pubkey::new_unique(), mint)
+        .await;
+    let transfer = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &context.users[0].token_accounts[0],
+        &treasury,
+        &payer.pubkey(),
+        &[],
+        250_000,
+    )
+    .unwrap();
+    context
+        .solana
+        .process_transaction(&[transfer], Some(&[&payer]))
+        .await?;
+    context
+        .addin
+        .configure_excluded_supply_account(&registrar, &realm_authority, 0, treasury)
+        .await?;
+    let treasury_account = AccountMeta::new_readonly(treasury, false);
+
+    // the excluded accounts must all be passed, in order, after the mints
+    context.solana.advance_clock_by_slots(2).await;
+    let invalid_accounts = vec![
+        (vec![], ErrorCode::InvalidVotingMintAccounts),
+        (
+            vec![treasury_account.clone(), mint_account.clone()],
+            ErrorCode::InvalidVotingMintAccounts,
+        ),
+        (
+            vec![mint_account.clone()],
+            ErrorCode::InvalidExcludedSupplyAccounts,
+        ),
+        (
+            vec![
+                mint_account.clone(),
+                AccountMeta::new_readonly(context.users[1].token_accounts[0], false),
+            ],
+            ErrorCode::InvalidExcludedSupplyAccounts,
+        ),
+    ];
+    for (accounts, error) in invalid_accounts {
+        let result = context
+            .addin
+            .update_max_vote_weight(&registrar, accounts)
+            .await;
+        assert_addin_err!(result, error);
+    }
+
+    context
+        .addin
+        .update_max_vote_weight(
+            &registrar,
+            vec![mint_account.clone(), treasury_account.clone()],
+        )
+        .await?;
+    assert_eq!(registrar.max_voter_weight(&context.solana).await, 1_500_000);
+
+    // excluded accounts must hold a voting mint
+    let other_mint_account = context.users[1].token_accounts[1];
+    context
+        .addin
+        .configure_excluded_supply_account(&registrar, &realm_authority, 1, other_mint_account)
+        .await?;
+    let result = context
+        .addin
+        .update_max_vote_weight(
+            &registrar,
+            vec![
+                mint_account,
+                treasury_account,
+                AccountMeta::new_readonly(other_mint_account, false),
+            ],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidExcludedSupplyAccounts);
+
+    Ok(())
+}
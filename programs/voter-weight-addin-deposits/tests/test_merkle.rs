@@ -0,0 +1,203 @@
+use solana_program::keccak;
+use solana_program_test::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{LockupKind, MerkleDistributor};
+use voter_weight_addin_deposits::error::ErrorCode;
+use voter_weight_addin_deposits::merkle;
+
+use program_test::*;
+
+mod program_test;
+
+/// The parent node of `a` and `b`, hashed in sorted order like
+/// `merkle::verify` does.
+fn parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak::hashv(&[&a, &b]).0
+    } else {
+        keccak::hashv(&[&b, &a]).0
+    }
+}
+
+#[tokio::test]
+async fn test_claim_from_merkle() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    // a distribution of 1000 tokens to user 1, 2000 to user 2 and two more
+    // claims, locked for 10 days
+    let voter1_authority = &context.users[1].key;
+    let voter2_authority = &context.users[2].key;
+    let leaf0 = merkle::leaf(0, &voter1_authority.pubkey(), 1000);
+    let leaf1 = merkle::leaf(1, &voter2_authority.pubkey(), 2000);
+    let leaf2 = merkle::leaf(2, &context.users[3].key.pubkey(), 3000);
+    let leaf3 = merkle::leaf(3, &Pubkey::new_unique(), 4000);
+    let node23 = parent(leaf2, leaf3);
+    let root = parent(parent(leaf0, leaf1), node23);
+    let proof0 = vec![leaf1, node23];
+    let proof1 = vec![leaf0, node23];
+
+    let distributor = context
+        .addin
+        .create_merkle_distributor(
+            &registrar,
+            &realm_authority,
+            &payer,
+            root,
+            LockupKind::Cliff,
+            10,
+        )
+        .await?;
+    let distributor_vault =
+        voter_weight_addin_deposits::seeds::find_distributor_vault_address(&distributor).0;
+    let fund = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &context.users[0].token_accounts[0],
+        &distributor_vault,
+        &payer.pubkey(),
+        &[],
+        10000,
+    )
+    .unwrap();
+    context
+        .solana
+        .process_transaction(&[fund], Some(&[&payer]))
+        .await?;
+
+    let voter1 = context
+        .addin
+        .create_voter(&registrar, &voter1_authority, &payer)
+        .await;
+    let voter2 = context
+        .addin
+        .create_voter(&registrar, &voter2_authority, &payer)
+        .await;
+
+    // proofs must match the claimant, amount and index
+    let invalid_claims = vec![
+        (&voter1, voter1_authority, 0, 2000, proof0.clone()),
+        (&voter1, voter1_authority, 0, 1000, proof1.clone()),
+        (&voter1, voter1_authority, 1, 1000, proof0.clone()),
+        (&voter2, voter2_authority, 0, 1000, proof0.clone()),
+    ];
+    for (voter, authority, index, amount, proof) in invalid_claims {
+        let result = context
+            .addin
+            .claim_from_merkle(
+                &registrar,
+                distributor,
+                voter,
+                authority,
+                &payer,
+                index,
+                amount,
+                proof,
+                1,
+            )
+            .await;
+        assert_addin_err!(result, ErrorCode::InvalidMerkleProof);
+    }
+
+    // valid claims arrive in locked deposit entries
+    context
+        .addin
+        .claim_from_merkle(
+            &registrar,
+            distributor,
+            &voter1,
+            &voter1_authority,
+            &payer,
+            0,
+            1000,
+            proof0.clone(),
+            1,
+        )
+        .await?;
+    context
+        .addin
+        .claim_from_merkle(
+            &registrar,
+            distributor,
+            &voter2,
+            &voter2_authority,
+            &payer,
+            1,
+            2000,
+            proof1,
+            1,
+        )
+        .await?;
+    assert_eq!(voter1.deposit_amount(&context.solana).await, 1000);
+    assert_eq!(voter2.deposit_amount(&context.solana).await, 2000);
+    assert_eq!(registrar.vault_balance(&context.solana).await, 3000);
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(distributor_vault)
+            .await,
+        7000
+    );
+    let distributor_data = context
+        .solana
+        .get_account::<MerkleDistributor>(distributor)
+        .await;
+    assert_eq!(distributor_data.total_claimed, 3000);
+
+    let token_owner_record = realm
+        .create_token_owner_record(voter1_authority.pubkey(), &payer)
+        .await;
+    context.solana.advance_clock_by_slots(2).await;
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter1,
+            &token_owner_record,
+            &voter1_authority,
+            context.users[1].token_accounts[0],
+            1,
+            1,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientVestedTokens);
+
+    // each index can only be claimed once
+    context
+        .addin
+        .claim_from_merkle(
+            &registrar,
+            distributor,
+            &voter1,
+            &voter1_authority,
+            &payer,
+            0,
+            1000,
+            proof0,
+            2,
+        )
+        .await
+        .expect_err("index 0 was claimed");
+    assert_eq!(voter1.deposit_amount(&context.solana).await, 1000);
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(distributor_vault)
+            .await,
+        7000
+    );
+
+    Ok(())
+}
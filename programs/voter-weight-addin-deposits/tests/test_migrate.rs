@@ -0,0 +1,113 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_migrate_governance_deposit() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    // The voters of this realm deposited into spl-governance before it
+    // switched to the addin.
+    let realm = context
+        .governance
+        .create_realm_with_options(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            None,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context
+        .solana
+        .create_token_account(&voter_authority.pubkey(), context.mints[0].pubkey.unwrap())
+        .await;
+    let transfer = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &context.users[1].token_accounts[0],
+        &token_account,
+        &voter_authority.pubkey(),
+        &[],
+        10000,
+    )
+    .unwrap();
+    context
+        .solana
+        .process_transaction(&[transfer], Some(&[&voter_authority]))
+        .await?;
+    let token_owner_record = realm
+        .deposit_governing_tokens(voter_authority, token_account, payer)
+        .await;
+    assert_eq!(
+        token_owner_record
+            .governing_token_deposit_amount(&context.solana)
+            .await,
+        10000
+    );
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    // only the owner of the token owner record can move its tokens
+    let other_authority = &context.users[2].key;
+    let other_voter = context
+        .addin
+        .create_voter(&registrar, &other_authority, &payer)
+        .await;
+    context
+        .addin
+        .migrate_governance_deposit(
+            &registrar,
+            &realm,
+            &other_voter,
+            &token_owner_record,
+            &other_authority,
+            0,
+        )
+        .await
+        .expect_err("not the owner of the token owner record");
+
+    context
+        .addin
+        .migrate_governance_deposit(
+            &registrar,
+            &realm,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            0,
+        )
+        .await?;
+    assert_eq!(registrar.vault_balance(&context.solana).await, 10000);
+    assert_eq!(voter.deposit_amount(&context.solana).await, 10000);
+    assert_eq!(
+        token_owner_record
+            .governing_token_deposit_amount(&context.solana)
+            .await,
+        0
+    );
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(realm.community_token_account)
+            .await,
+        0
+    );
+
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use anchor_lang::AnchorSerialize;
+use solana_program::{program_option::COption, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::AccountMeta, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transport::TransportError,
+};
+use voter_weight_addin_deposits::nft::{find_metadata_address, metadata_program};
+
+use program_test::*;
+
+mod program_test;
+
+/// Writes a token account of `token_program` holding one token of a new
+/// NFT for `wallet`, and the NFT's metadata owned by `metadata_owner`.
+/// Returns both accounts, as passed to `update_voter_weight_record`.
+fn set_nft(
+    solana: &SolanaCookie,
+    wallet: Pubkey,
+    token_program: Pubkey,
+    metadata_owner: Pubkey,
+    collection: Option<(bool, Pubkey)>,
+) -> Vec<AccountMeta> {
+    let nft_mint = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+    let token = spl_token::state::Account {
+        mint: nft_mint,
+        owner: wallet,
+        amount: 1,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    let mut token_data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account::pack(token, &mut token_data).unwrap();
+    solana.set_account(token_account, token_program, token_data);
+
+    // Metadata::key, update_authority, mint, name, symbol, uri,
+    // seller_fee_basis_points and creators, then primary_sale_happened,
+    // is_mutable, edition_nonce, token_standard and collection
+    let creators: Option<Vec<(Pubkey, bool, u8)>> = None;
+    let mut metadata = (
+        4u8,
+        Pubkey::new_unique(),
+        nft_mint,
+        "nft".to_string(),
+        "NFT".to_string(),
+        String::new(),
+        0u16,
+        creators,
+    )
+        .try_to_vec()
+        .unwrap();
+    let edition_nonce: Option<u8> = None;
+    let token_standard: Option<u8> = None;
+    metadata.extend(
+        (true, true, edition_nonce, token_standard, collection)
+            .try_to_vec()
+            .unwrap(),
+    );
+    let (metadata_address, _) = find_metadata_address(&nft_mint);
+    solana.set_account(metadata_address, metadata_owner, metadata);
+
+    vec![
+        AccountMeta::new_readonly(token_account, false),
+        AccountMeta::new_readonly(metadata_address, false),
+    ]
+}
+
+#[tokio::test]
+async fn test_nft_boost() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let collection = Pubkey::new_unique();
+    context
+        .addin
+        .configure_nft_boost(&registrar, &realm_authority, collection, 5000)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let wallet = voter_authority.pubkey();
+    let token_account = context.users[1].token_accounts[0];
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    // without the NFT's accounts there is no boost
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    // neither with spoofed accounts, or NFTs that aren't verified members
+    // of the collection or not held by the wallet
+    let solana = &context.solana;
+    let spoofed_owner = Pubkey::new_unique();
+    let verified = Some((true, collection));
+    let no_boost = vec![
+        set_nft(
+            solana,
+            wallet,
+            spoofed_owner,
+            metadata_program::ID,
+            verified,
+        ),
+        set_nft(solana, wallet, spl_token::id(), spoofed_owner, verified),
+        set_nft(
+            solana,
+            wallet,
+            spl_token::id(),
+            metadata_program::ID,
+            Some((true, Pubkey::new_unique())),
+        ),
+        set_nft(
+            solana,
+            wallet,
+            spl_token::id(),
+            metadata_program::ID,
+            Some((false, collection)),
+        ),
+        set_nft(solana, wallet, spl_token::id(), metadata_program::ID, None),
+        set_nft(
+            solana,
+            Pubkey::new_unique(),
+            spl_token::id(),
+            metadata_program::ID,
+            verified,
+        ),
+    ];
+    for accounts in no_boost {
+        context
+            .addin
+            .update_voter_weight_record_with_accounts(&registrar, &voter, accounts)
+            .await?;
+        assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+    }
+
+    // a verified NFT of the collection held by the wallet
+    let accounts = set_nft(
+        solana,
+        wallet,
+        spl_token::id(),
+        metadata_program::ID,
+        verified,
+    );
+    context
+        .addin
+        .update_voter_weight_record_with_accounts(&registrar, &voter, accounts)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 15000);
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::AccountMeta, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transport::TransportError,
+};
+use voter_weight_addin_deposits::error::ErrorCode;
+use voter_weight_addin_deposits::oracle::MAX_ORACLE_STALENESS_SLOTS;
+
+use program_test::*;
+
+mod program_test;
+
+/// A Pyth price account, with the fields `OraclePrice::from_pyth` reads.
+struct PythPrice {
+    magic: u32,
+    account_type: u32,
+    expo: i32,
+    price: i64,
+    status: u32,
+    publish_slot: u64,
+}
+
+impl PythPrice {
+    fn trading(price: i64, expo: i32, publish_slot: u64) -> Self {
+        Self {
+            magic: 0xa1b2_c3d4,
+            account_type: 3,
+            expo,
+            price,
+            status: 1,
+            publish_slot,
+        }
+    }
+
+    fn data(&self) -> Vec<u8> {
+        let mut data = vec![0u8; 240];
+        data[0..4].copy_from_slice(&self.magic.to_le_bytes());
+        data[8..12].copy_from_slice(&self.account_type.to_le_bytes());
+        data[20..24].copy_from_slice(&self.expo.to_le_bytes());
+        data[208..216].copy_from_slice(&self.price.to_le_bytes());
+        data[224..228].copy_from_slice(&self.status.to_le_bytes());
+        data[232..240].copy_from_slice(&self.publish_slot.to_le_bytes());
+        data
+    }
+}
+
+#[tokio::test]
+async fn test_oracle_price() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let oracle = Pubkey::new_unique();
+    let pyth_program = Pubkey::new_unique();
+    context
+        .addin
+        .configure_oracle(&registrar, &realm_authority, 0, oracle)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    context
+        .solana
+        .advance_clock_by_slots(MAX_ORACLE_STALENESS_SLOTS + 5)
+        .await;
+    let slot = context.solana.get_clock().await.slot;
+    let oracle_meta = vec![AccountMeta::new_readonly(oracle, false)];
+
+    // the configured oracle must be passed, a valid price at another
+    // address doesn't count
+    let result = context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingOracleAccount);
+    let spoofed = Pubkey::new_unique();
+    context.solana.set_account(
+        spoofed,
+        pyth_program,
+        PythPrice::trading(100, 0, slot).data(),
+    );
+    let result = context
+        .addin
+        .update_voter_weight_record_with_accounts(
+            &registrar,
+            &voter,
+            vec![AccountMeta::new_readonly(spoofed, false)],
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingOracleAccount);
+
+    // accounts that aren't trading Pyth prices
+    let invalid = vec![
+        PythPrice {
+            magic: 0,
+            ..PythPrice::trading(2, 0, slot)
+        }
+        .data(),
+        PythPrice {
+            account_type: 2,
+            ..PythPrice::trading(2, 0, slot)
+        }
+        .data(),
+        PythPrice::trading(0, 0, slot).data(),
+        PythPrice::trading(-2, 0, slot).data(),
+        PythPrice {
+            status: 0,
+            ..PythPrice::trading(2, 0, slot)
+        }
+        .data(),
+        PythPrice::trading(2, 19, slot).data(),
+        PythPrice::trading(2, 0, slot).data()[..200].to_vec(),
+    ];
+    for data in invalid {
+        // a new blockhash, so the same transaction can be sent again
+        context.solana.advance_clock_by_slots(1).await;
+        context.solana.set_account(oracle, pyth_program, data);
+        let result = context
+            .addin
+            .update_voter_weight_record_with_accounts(&registrar, &voter, oracle_meta.clone())
+            .await;
+        assert_addin_err!(result, ErrorCode::InvalidOraclePrice);
+    }
+
+    // a price from too long ago
+    context.solana.advance_clock_by_slots(1).await;
+    let slot = context.solana.get_clock().await.slot;
+    let stale_slot = slot - MAX_ORACLE_STALENESS_SLOTS - 1;
+    context.solana.set_account(
+        oracle,
+        pyth_program,
+        PythPrice::trading(2, 0, stale_slot).data(),
+    );
+    let result = context
+        .addin
+        .update_voter_weight_record_with_accounts(&registrar, &voter, oracle_meta.clone())
+        .await;
+    assert_addin_err!(result, ErrorCode::StaleOraclePrice);
+
+    // deposits count with their price
+    context.solana.advance_clock_by_slots(1).await;
+    let slot = context.solana.get_clock().await.slot;
+    context.solana.set_account(
+        oracle,
+        pyth_program,
+        PythPrice::trading(2, 0, slot - MAX_ORACLE_STALENESS_SLOTS).data(),
+    );
+    context
+        .addin
+        .update_voter_weight_record_with_accounts(&registrar, &voter, oracle_meta.clone())
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 20000);
+
+    context.solana.advance_clock_by_slots(1).await;
+    let slot = context.solana.get_clock().await.slot;
+    context.solana.set_account(
+        oracle,
+        pyth_program,
+        PythPrice::trading(25, -1, slot).data(),
+    );
+    context
+        .addin
+        .update_voter_weight_record_with_accounts(&registrar, &voter, oracle_meta)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 25000);
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::LockupKind;
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_transfer_position() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            10,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            1,
+            10000,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(&registrar, &voter, &voter_authority, token_account, 0, 5000)
+        .await?;
+
+    let result = context
+        .addin
+        .create_position(&registrar, &voter, &voter_authority, &payer, 1)
+        .await;
+    assert_addin_err!(result, ErrorCode::PositionTransfersDisabled);
+
+    context
+        .addin
+        .configure_position_transfers(&registrar, &realm_authority, true)
+        .await?;
+
+    // only locked deposits can be positions
+    let result = context
+        .addin
+        .create_position(&registrar, &voter, &voter_authority, &payer, 0)
+        .await;
+    assert_addin_err!(result, ErrorCode::DepositNotLocked);
+
+    let position = context
+        .addin
+        .create_position(&registrar, &voter, &voter_authority, &payer, 1)
+        .await?;
+    assert_eq!(
+        context.solana.token_account_balance(position.holder).await,
+        1
+    );
+
+    let target_authority = &context.users[2].key;
+    let target_voter = context
+        .addin
+        .create_voter(&registrar, &target_authority, &payer)
+        .await;
+    let target_holder = context
+        .solana
+        .create_token_account(&target_authority.pubkey(), position.mint)
+        .await;
+    context
+        .addin
+        .transfer_position(
+            &registrar,
+            &position,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            position.holder,
+            &target_voter,
+            target_holder,
+            1,
+        )
+        .await?;
+
+    // the locked tokens moved along with the NFT
+    assert_eq!(voter.deposit_amount(&context.solana).await, 5000);
+    assert_eq!(target_voter.deposit_amount(&context.solana).await, 10000);
+    assert_eq!(
+        context.solana.token_account_balance(position.holder).await,
+        0
+    );
+    assert_eq!(context.solana.token_account_balance(target_holder).await, 1);
+
+    context
+        .addin
+        .close_position(
+            &registrar,
+            &position,
+            &target_voter,
+            &target_authority,
+            target_holder,
+        )
+        .await?;
+    assert_eq!(context.solana.token_account_balance(target_holder).await, 0);
+
+    Ok(())
+}
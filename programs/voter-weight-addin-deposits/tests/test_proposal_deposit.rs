@@ -0,0 +1,157 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{VoterWeightAction, LOCKUP_FACTOR_SCALE};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_proposal_deposit() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_proposal_deposit(&registrar, &realm_authority, 1000)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    // a weight for any action could be used to create proposals
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 0);
+
+    let governance_weight = context.addin.refreshed_voter_weight_for_action(
+        &registrar,
+        &voter,
+        VoterWeightAction::CreateGovernance,
+    );
+    let governance = realm
+        .create_account_governance(
+            registrar.address,
+            &token_owner_record,
+            Some(&governance_weight),
+            voter_authority,
+            payer,
+        )
+        .await;
+
+    // creating the proposal escrows the deposit
+    let proposal_weight = context.addin.escrowed_voter_weight(
+        &registrar,
+        &voter,
+        &realm,
+        &governance,
+        0,
+        payer.pubkey(),
+    );
+    let configure = context.addin.configure_lockup_params_instruction(
+        &registrar,
+        governance.address,
+        365 * 24 * 60 * 60,
+        LOCKUP_FACTOR_SCALE,
+    );
+    let (proposal, _) = realm
+        .create_proposal(
+            &governance,
+            &token_owner_record,
+            Some(&proposal_weight),
+            voter_authority,
+            payer,
+            0,
+            configure,
+        )
+        .await;
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    let result = context
+        .addin
+        .release_proposal_deposit(&registrar, &voter, proposal.address, payer.pubkey())
+        .await;
+    assert_addin_err!(result, ErrorCode::ProposalNotFinal);
+
+    realm
+        .cancel_proposal(&governance, &proposal, &token_owner_record, voter_authority)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // the escrowed tokens stay deposited until released
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::ProposalDepositEscrowed);
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            9000,
+        )
+        .await?;
+
+    context
+        .addin
+        .release_proposal_deposit(&registrar, &voter, proposal.address, payer.pubkey())
+        .await?;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            1000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    Ok(())
+}
@@ -0,0 +1,328 @@
+use solana_program::{program_option::COption, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{LockupKind, VoterWeightAction, LOCKUP_FACTOR_SCALE};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+/// A registrar with proposal locking and a proposal of `users[1]` that is
+/// open for voting.
+async fn setup(
+    context: &TestContext,
+) -> (
+    GovernanceRealmCookie,
+    RegistrarCookie,
+    AccountGovernanceCookie,
+    ProposalCookie,
+    TokenOwnerRecordCookie,
+) {
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    // the test mints start without supply, which lets any vote decide a proposal
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(
+        spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 1_000_000,
+            decimals: context.mints[0].decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    )
+    .unwrap();
+    context
+        .solana
+        .set_account(context.mints[0].pubkey.unwrap(), spl_token::id(), data);
+
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_proposal_locking(&registrar, &realm_authority, true)
+        .await
+        .unwrap();
+
+    let owner_authority = &context.users[1].key;
+    let (owner, owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &owner_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &owner,
+            &owner_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await
+        .unwrap();
+
+    let governance_weight = context.addin.refreshed_voter_weight_for_action(
+        &registrar,
+        &owner,
+        VoterWeightAction::CreateGovernance,
+    );
+    let governance = realm
+        .create_account_governance(
+            registrar.address,
+            &owner_record,
+            Some(&governance_weight),
+            owner_authority,
+            payer,
+        )
+        .await;
+    let proposal_weight = context.addin.refreshed_voter_weight_for_action(
+        &registrar,
+        &owner,
+        VoterWeightAction::CreateProposal,
+    );
+    let configure = context.addin.configure_lockup_params_instruction(
+        &registrar,
+        governance.address,
+        365 * 24 * 60 * 60,
+        LOCKUP_FACTOR_SCALE,
+    );
+    let (proposal, _) = realm
+        .create_proposal(
+            &governance,
+            &owner_record,
+            Some(&proposal_weight),
+            owner_authority,
+            payer,
+            0,
+            configure,
+        )
+        .await;
+
+    (realm, registrar, governance, proposal, owner_record)
+}
+
+#[tokio::test]
+async fn test_proposal_lock() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (realm, registrar, governance, proposal, owner_record) = setup(&context).await;
+    let payer = &context.users[0].key;
+    let owner_authority = &context.users[1].key;
+
+    let voter_authority = &context.users[2].key;
+    let token_account = context.users[2].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    // a weight for any action could be used to vote
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 0);
+
+    // only the locked tokens count for the vote
+    let vote_weight =
+        context
+            .addin
+            .locked_voter_weight(&registrar, &voter, &proposal, 4000, payer.pubkey());
+    realm
+        .cast_yes_vote(
+            &governance,
+            &proposal,
+            &owner_record,
+            &token_owner_record,
+            Some(&vote_weight),
+            voter_authority,
+            payer,
+        )
+        .await?;
+    assert_eq!(proposal.yes_votes(&context.solana).await, 4000);
+    context.solana.advance_clock_by_slots(2).await;
+
+    // while the vote is open, only the locked tokens stay deposited
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            6001,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::ProposalDepositEscrowed);
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            6000,
+        )
+        .await?;
+
+    let result = context
+        .addin
+        .release_proposal_lock(&registrar, &voter, proposal.address, payer.pubkey())
+        .await;
+    assert_addin_err!(result, ErrorCode::ProposalNotFinal);
+
+    realm
+        .cancel_proposal(&governance, &proposal, &owner_record, owner_authority)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .release_proposal_lock(&registrar, &voter, proposal.address, payer.pubkey())
+        .await?;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            4000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proposal_lock_entries() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (realm, registrar, governance, proposal, owner_record) = setup(&context).await;
+    let payer = &context.users[0].key;
+    let owner_authority = &context.users[1].key;
+
+    let voter_authority = &context.users[2].key;
+    let token_account = context.users[2].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .create_deposit_entry(&registrar, &voter, &voter_authority, 1, LockupKind::None, 0)
+        .await?;
+    for &(index, amount) in [(0, 6000), (1, 4000)].iter() {
+        context
+            .addin
+            .deposit(
+                &registrar,
+                &voter,
+                &voter_authority,
+                token_account,
+                index,
+                amount,
+            )
+            .await?;
+    }
+
+    // only the weight of entry 1 counts for the vote
+    let vote_weight = context.addin.entries_locked_voter_weight(
+        &registrar,
+        &voter,
+        &proposal,
+        1 << 1,
+        payer.pubkey(),
+    );
+    realm
+        .cast_yes_vote(
+            &governance,
+            &proposal,
+            &owner_record,
+            &token_owner_record,
+            Some(&vote_weight),
+            voter_authority,
+            payer,
+        )
+        .await?;
+    assert_eq!(proposal.yes_votes(&context.solana).await, 4000);
+    context.solana.advance_clock_by_slots(2).await;
+
+    // while the vote is open, only the other entries can be withdrawn from
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            1,
+            1,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::DepositEntryLockedForProposal);
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            6000,
+        )
+        .await?;
+
+    realm
+        .cancel_proposal(&governance, &proposal, &owner_record, owner_authority)
+        .await?;
+    context
+        .addin
+        .release_proposal_lock(&registrar, &voter, proposal.address, payer.pubkey())
+        .await?;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            1,
+            4000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::isqrt;
+
+use program_test::*;
+
+mod program_test;
+
+#[test]
+fn test_isqrt() {
+    assert_eq!(isqrt(0), 0);
+    assert_eq!(isqrt(1), 1);
+    assert_eq!(isqrt(3), 1);
+    assert_eq!(isqrt(4), 2);
+    assert_eq!(isqrt(99), 9);
+    assert_eq!(isqrt(10000), 100);
+    assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+    for &n in [2u64, 17, 1 << 40, (1 << 62) + 12345, u64::MAX - 1].iter() {
+        let r = isqrt(n) as u128;
+        assert!(r * r <= n as u128);
+        assert!((r + 1) * (r + 1) > n as u128);
+    }
+}
+
+#[allow(unaligned_references)]
+#[tokio::test]
+async fn test_quadratic_weight() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    let reference_account = context.users[1].token_accounts[0];
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            reference_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    // only the registrar authority may change the weight mode
+    context
+        .addin
+        .configure_quadratic_weight(&registrar, &voter_authority, true)
+        .await
+        .expect_err("not the registrar authority");
+
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    context
+        .addin
+        .configure_quadratic_weight(&registrar, &realm_authority, true)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 100);
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_sponsored_voter() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    // an empty sponsor can't pay
+    let voter_authority = Keypair::new();
+    assert_addin_err!(
+        context
+            .addin
+            .create_voter_sponsored(&registrar, &voter_authority)
+            .await
+            .map(|_| ()),
+        ErrorCode::RentPayerUnderfunded
+    );
+
+    let funding = 1_000_000_000;
+    context
+        .addin
+        .fund_rent_sponsor(&registrar, payer, funding)
+        .await?;
+    let sponsor = registrar.rent_sponsor();
+    assert_eq!(context.solana.get_lamports(sponsor).await, funding);
+    context.solana.advance_clock_by_slots(2).await;
+
+    // the voter authority has no SOL at all
+    let voter = context
+        .addin
+        .create_voter_sponsored(&registrar, &voter_authority)
+        .await?;
+    assert_eq!(
+        context.solana.get_lamports(voter_authority.pubkey()).await,
+        0
+    );
+    let rent = context.solana.get_lamports(voter.address).await
+        + context.solana.get_lamports(voter.voter_weight_record).await;
+    assert!(rent > 0);
+    assert_eq!(context.solana.get_lamports(sponsor).await, funding - rent);
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    // closing returns all of the rent to the sponsor
+    let result = context
+        .addin
+        .close_voter(
+            &registrar,
+            &voter,
+            &voter_authority,
+            voter_authority.pubkey(),
+            sponsor,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::SponsoredRentNotReturned);
+    context
+        .addin
+        .close_voter(&registrar, &voter, &voter_authority, sponsor, sponsor)
+        .await?;
+    assert_eq!(context.solana.get_lamports(sponsor).await, funding);
+
+    Ok(())
+}
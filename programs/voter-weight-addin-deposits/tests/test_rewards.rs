@@ -0,0 +1,209 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{Voter, MAX_REWARD_RATE_PER_SEC};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+/// Transfers `amount` reward tokens from `funder`'s account into the vault.
+async fn fund_reward_vault(
+    context: &TestContext,
+    registrar: &RegistrarCookie,
+    funder: &UserCookie,
+    amount: u64,
+) -> Result<(), TransportError> {
+    let instruction = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &funder.token_accounts[1],
+        &registrar.reward_vault(),
+        &funder.key.pubkey(),
+        &[],
+        amount,
+    )
+    .unwrap();
+    context
+        .solana
+        .process_transaction(&[instruction], Some(&[&funder.key]))
+        .await
+}
+
+async fn reward_pending(context: &TestContext, voter: &VoterCookie) -> u64 {
+    let voter = context.solana.get_account::<Voter>(voter.address).await;
+    voter.reward_pending
+}
+
+#[tokio::test]
+async fn test_rewards() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let reward_mint = &context.mints[1];
+    let result = context
+        .addin
+        .create_reward_vault(
+            &registrar,
+            &realm_authority,
+            &payer,
+            reward_mint,
+            MAX_REWARD_RATE_PER_SEC + 1,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidRewardRate);
+    context
+        .addin
+        .create_reward_vault(&registrar, &realm_authority, &payer, reward_mint, 40)
+        .await?;
+    fund_reward_vault(&context, &registrar, &context.users[0], 10000).await?;
+
+    // voter 1 has a quarter of the deposits, voter 2 the rest
+    let now = context.solana.get_clock().await.unix_timestamp;
+    context.solana.set_unix_timestamp(now).await;
+    let voter1_authority = &context.users[1].key;
+    let voter1 = context
+        .addin
+        .create_voter(&registrar, &voter1_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter1,
+            &voter1_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    let voter2_authority = &context.users[2].key;
+    let voter2 = context
+        .addin
+        .create_voter(&registrar, &voter2_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter2,
+            &voter2_authority,
+            context.users[2].token_accounts[0],
+            0,
+            30000,
+        )
+        .await?;
+
+    // 4000 are emitted in the first 100 seconds, then the rate doubles
+    context.solana.set_unix_timestamp(now + 100).await;
+    context
+        .addin
+        .set_reward_rate(&registrar, &voter1_authority, 80)
+        .await
+        .expect_err("not the registrar authority");
+    let result = context
+        .addin
+        .set_reward_rate(&registrar, &realm_authority, MAX_REWARD_RATE_PER_SEC + 1)
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidRewardRate);
+    context
+        .addin
+        .set_reward_rate(&registrar, &realm_authority, 80)
+        .await?;
+
+    // and 8000 in the next 100 seconds, before the emissions are paused
+    context.solana.set_unix_timestamp(now + 200).await;
+    context
+        .addin
+        .set_reward_rate(&registrar, &realm_authority, 0)
+        .await?;
+
+    let voter1_destination = context.users[1].token_accounts[1];
+    let balance = context
+        .solana
+        .token_account_balance(voter1_destination)
+        .await;
+    context
+        .addin
+        .claim_rewards(&registrar, &voter1, &voter1_authority, voter1_destination)
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter1_destination)
+            .await,
+        balance + 1000 + 2000
+    );
+    assert_eq!(reward_pending(&context, &voter1).await, 0);
+
+    // the vault can't pay all of voter 2's 3000 + 6000, the rest stays owed
+    let voter2_destination = context.users[2].token_accounts[1];
+    let balance = context
+        .solana
+        .token_account_balance(voter2_destination)
+        .await;
+    context
+        .addin
+        .claim_rewards(&registrar, &voter2, &voter2_authority, voter2_destination)
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter2_destination)
+            .await,
+        balance + 7000
+    );
+    assert_eq!(reward_pending(&context, &voter2).await, 2000);
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(registrar.reward_vault())
+            .await,
+        0
+    );
+
+    // once the vault is funded again, it is paid
+    fund_reward_vault(&context, &registrar, &context.users[0], 5000).await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context.solana.set_unix_timestamp(now + 300).await;
+    context
+        .addin
+        .claim_rewards(&registrar, &voter2, &voter2_authority, voter2_destination)
+        .await?;
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(voter2_destination)
+            .await,
+        balance + 9000
+    );
+    assert_eq!(reward_pending(&context, &voter2).await, 0);
+    assert_eq!(
+        context
+            .solana
+            .token_account_balance(registrar.reward_vault())
+            .await,
+        3000
+    );
+
+    // only the voter's authority can claim
+    context
+        .addin
+        .claim_rewards(&registrar, &voter1, &voter2_authority, voter2_destination)
+        .await
+        .expect_err("not the voter authority");
+
+    Ok(())
+}
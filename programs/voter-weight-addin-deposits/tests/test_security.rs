@@ -0,0 +1,387 @@
+use anchor_lang::__private::ErrorCode as AnchorErrorCode;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use spl_governance::error::GovernanceError;
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+async fn setup(context: &TestContext) -> (GovernanceRealmCookie, RegistrarCookie, Keypair) {
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    (realm, registrar, realm_authority)
+}
+
+#[tokio::test]
+async fn test_deposit_mismatched_mint() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (_realm, registrar, realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    // a second voting mint, with its own vault
+    let other_mint = &context.mints[1];
+    context
+        .addin
+        .configure_voting_mint(&registrar, &realm_authority, other_mint, 1, 1)
+        .await?;
+    context
+        .addin
+        .create_vault_shard(&registrar, &realm_authority, other_mint, 0, payer)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    // deposit entry 0 holds the community mint
+    let result = context
+        .addin
+        .deposit_mint(
+            &registrar,
+            &voter,
+            &voter_authority,
+            other_mint,
+            context.users[1].token_accounts[1],
+            0,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidMint);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_withdraw_by_non_authority() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (realm, registrar, _realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    let thief = &context.users[2];
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &thief.key,
+            thief.token_accounts[0],
+            0,
+            10000,
+        )
+        .await;
+    assert_instruction_error(result, AnchorErrorCode::ConstraintHasOne);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_withdraw_spoofed_token_owner_record() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (realm, registrar, _realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // an account not owned by spl-governance
+    let fake_record = TokenOwnerRecordCookie {
+        address: voter.address,
+    };
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &fake_record,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidAccountOwner);
+
+    // the record of another token owner, who may be free to withdraw
+    let other_record = realm
+        .create_token_owner_record(context.users[2].key.pubkey(), &payer)
+        .await;
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &other_record,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await;
+    assert_instruction_error(
+        result,
+        GovernanceError::InvalidTokenOwnerRecordAccountAddress,
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_voter_through_cpi() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (_realm, registrar, _realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let create_voter = context.addin.create_voter_instruction(
+        &registrar,
+        voter_authority.pubkey(),
+        payer.pubkey(),
+        vec![],
+    );
+    let result = context
+        .cpi_forwarder
+        .forward(create_voter, &[payer, voter_authority])
+        .await;
+    assert_addin_err!(result, ErrorCode::ForbiddenCpi);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deposit_and_withdraw_in_one_slot() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (realm, registrar, _realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidToDepositAndWithdrawInOneSlot);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deposit_from_delegated_account() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (_realm, registrar, _realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+
+    // a third party that could move the tokens mid-flow
+    let approve = spl_token::instruction::approve(
+        &spl_token::id(),
+        &token_account,
+        &context.users[2].key.pubkey(),
+        &voter_authority.pubkey(),
+        &[],
+        10000,
+    )
+    .unwrap();
+    context
+        .solana
+        .process_transaction(&[approve], Some(&[voter_authority]))
+        .await?;
+    let result = context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidDepositSource);
+
+    let revoke = spl_token::instruction::revoke(
+        &spl_token::id(),
+        &token_account,
+        &voter_authority.pubkey(),
+        &[],
+    )
+    .unwrap();
+    context
+        .solana
+        .process_transaction(&[revoke], Some(&[voter_authority]))
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_withdraw_to_foreign_account() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (realm, registrar, _realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // signed by the voter authority, but paying out to someone else
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            context.users[2].token_accounts[0],
+            0,
+            10000,
+        )
+        .await;
+    assert_instruction_error(result, AnchorErrorCode::ConstraintRaw);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_withdraw_through_cpi() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let (realm, registrar, _realm_authority) = setup(&context).await;
+    let payer = &context.users[0].key;
+
+    let voter_authority = &context.users[1].key;
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    let withdraw = context.addin.withdraw_instruction(
+        &registrar,
+        &voter,
+        &token_owner_record,
+        voter_authority.pubkey(),
+        context.users[1].token_accounts[0],
+        0,
+        10000,
+    );
+    let result = context
+        .cpi_forwarder
+        .forward(withdraw, &[voter_authority])
+        .await;
+    assert_addin_err!(result, ErrorCode::ForbiddenCpi);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+
+use program_test::*;
+
+mod program_test;
+
+/// Compute units every instruction may use. With test-bpf, an instruction
+/// that needs more as the number of voters grows fails the test.
+const STRESS_COMPUTE_MAX_UNITS: u64 = 100000;
+
+/// Number of voters to create, 100 unless set with STRESS_VOTERS.
+fn stress_voter_count() -> usize {
+    std::env::var("STRESS_VOTERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100)
+}
+
+// Slow, run with: cargo test-bpf --test test_stress -- --ignored
+#[ignore]
+#[tokio::test]
+async fn test_many_voters() -> Result<(), TransportError> {
+    let num_voters = stress_voter_count();
+    let context = TestContext::new_with_options(num_voters + 1, STRESS_COMPUTE_MAX_UNITS).await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let amount = 10000;
+    let voters =
+        create_voters_with_deposits(&context, &realm, &registrar, &context.users[1..], amount)
+            .await?;
+    let deposited = assert_deposits_conserved(&context.solana, &registrar, &voters).await;
+    assert_eq!(deposited, 2 * amount * num_voters as u64);
+
+    context.solana.advance_clock_by_slots(2).await;
+
+    // every other voter withdraws half of its unlocked tokens
+    for (user, voter) in context.users[1..].iter().zip(voters.iter()).step_by(2) {
+        context
+            .addin
+            .withdraw(
+                &registrar,
+                &voter.voter,
+                &voter.token_owner_record,
+                &user.key,
+                user.token_accounts[0],
+                0,
+                amount / 2,
+            )
+            .await?;
+    }
+    let withdrawn = (num_voters as u64 + 1) / 2 * (amount / 2);
+    let deposited = assert_deposits_conserved(&context.solana, &registrar, &voters).await;
+    assert_eq!(deposited, 2 * amount * num_voters as u64 - withdrawn);
+
+    // the locked deposit gives extra weight on top of the deposited amount
+    for voter in voters.iter() {
+        context
+            .addin
+            .update_voter_weight_record(&registrar, &voter.voter)
+            .await?;
+        let weight = voter.voter.voter_weight(&context.solana).await;
+        assert!(weight > voter.voter.deposit_amount(&context.solana).await);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use anchor_lang::AccountSerialize;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::AccountMeta, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transport::TransportError,
+};
+use voter_weight_addin_deposits::account::{
+    VoterWeightAction, VoterWeightRecord, WeightCombination,
+};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+/// Writes `record`, owned by `owner`, to a new address and returns it.
+fn set_record(solana: &SolanaCookie, owner: Pubkey, record: &VoterWeightRecord) -> AccountMeta {
+    let mut data = vec![];
+    record.try_serialize(&mut data).unwrap();
+    let address = Pubkey::new_unique();
+    solana.set_account(address, owner, data);
+    AccountMeta::new_readonly(address, false)
+}
+
+#[tokio::test]
+async fn test_upstream_addin() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let upstream_addin = Pubkey::new_unique();
+    context
+        .addin
+        .configure_upstream_addin(
+            &registrar,
+            &realm_authority,
+            upstream_addin,
+            WeightCombination::Sum,
+        )
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+
+    context.solana.advance_clock_by_slots(2).await;
+    let slot = context.solana.get_clock().await.slot;
+    let valid = VoterWeightRecord {
+        realm: realm.realm,
+        governing_token_mint: realm.community_token_mint.pubkey.unwrap(),
+        governing_token_owner: voter_authority.pubkey(),
+        voter_weight: 3000,
+        voter_weight_expiry: Some(slot),
+        weight_action: None,
+        weight_action_target: None,
+        reserved: [0; 8],
+    };
+
+    // the upstream record is required
+    let result = context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await;
+    assert_addin_err!(result, ErrorCode::MissingUpstreamVoterWeightRecord);
+
+    // records of another program or another wallet don't count
+    let solana = &context.solana;
+    let not_found = vec![
+        set_record(solana, Pubkey::new_unique(), &valid),
+        set_record(
+            solana,
+            upstream_addin,
+            &VoterWeightRecord {
+                governing_token_owner: Pubkey::new_unique(),
+                ..valid.clone()
+            },
+        ),
+    ];
+    for record in not_found {
+        let result = context
+            .addin
+            .update_voter_weight_record_with_accounts(&registrar, &voter, vec![record])
+            .await;
+        assert_addin_err!(result, ErrorCode::MissingUpstreamVoterWeightRecord);
+    }
+
+    // records of another realm or mint, expired ones or ones for another
+    // action are rejected
+    let invalid = vec![
+        VoterWeightRecord {
+            realm: Pubkey::new_unique(),
+            ..valid.clone()
+        },
+        VoterWeightRecord {
+            governing_token_mint: Pubkey::new_unique(),
+            ..valid.clone()
+        },
+        VoterWeightRecord {
+            voter_weight_expiry: Some(slot - 1),
+            ..valid.clone()
+        },
+        VoterWeightRecord {
+            weight_action: Some(VoterWeightAction::CreateProposal),
+            ..valid.clone()
+        },
+    ];
+    for record in invalid {
+        let record = set_record(solana, upstream_addin, &record);
+        let result = context
+            .addin
+            .update_voter_weight_record_with_accounts(&registrar, &voter, vec![record])
+            .await;
+        assert_addin_err!(result, ErrorCode::InvalidUpstreamVoterWeightRecord);
+    }
+
+    // a valid record adds its weight
+    let record = set_record(solana, upstream_addin, &valid);
+    context
+        .addin
+        .update_voter_weight_record_with_accounts(&registrar, &voter, vec![record])
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 13000);
+
+    // or only counts if it is larger
+    context
+        .addin
+        .configure_upstream_addin(
+            &registrar,
+            &realm_authority,
+            upstream_addin,
+            WeightCombination::Max,
+        )
+        .await?;
+    let record = set_record(solana, upstream_addin, &valid);
+    context
+        .addin
+        .update_voter_weight_record_with_accounts(&registrar, &voter, vec![record])
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::LOCKUP_FACTOR_SCALE;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_voting() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+
+    // spl-governance reads the weight from the addin's voter weight record
+    // for every step
+    let voter_weight = context.addin.refreshed_voter_weight(&registrar, &voter);
+    let governance = realm
+        .create_account_governance(
+            registrar.address,
+            &token_owner_record,
+            Some(&voter_weight),
+            voter_authority,
+            payer,
+        )
+        .await;
+    let configure = context.addin.configure_lockup_params_instruction(
+        &registrar,
+        governance.address,
+        365 * 24 * 60 * 60,
+        LOCKUP_FACTOR_SCALE,
+    );
+    let (proposal, _) = realm
+        .create_proposal(
+            &governance,
+            &token_owner_record,
+            Some(&voter_weight),
+            voter_authority,
+            payer,
+            0,
+            configure,
+        )
+        .await;
+    assert_eq!(proposal.yes_votes(&context.solana).await, 0);
+
+    realm
+        .cast_yes_vote(
+            &governance,
+            &proposal,
+            &token_owner_record,
+            &token_owner_record,
+            Some(&voter_weight),
+            voter_authority,
+            payer,
+        )
+        .await?;
+
+    // the tally is the deposited amount
+    assert_eq!(proposal.yes_votes(&context.solana).await, 10000);
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::{LockupKind, VoterWeightAction, WeightRule};
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_locked_only_proposal_weight() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            30,
+        )
+        .await?;
+    for index in 0..2 {
+        context
+            .addin
+            .deposit(
+                &registrar,
+                &voter,
+                &voter_authority,
+                token_account,
+                index,
+                10000,
+            )
+            .await?;
+    }
+
+    // proposals may only be created with locked tokens
+    context
+        .addin
+        .configure_weight_action_rule(
+            &registrar,
+            &realm_authority,
+            VoterWeightAction::CreateProposal,
+            WeightRule::LockedOnly,
+        )
+        .await?;
+
+    context
+        .addin
+        .update_voter_weight_record_for_action(&registrar, &voter, VoterWeightAction::CastVote)
+        .await?;
+    let vote_weight = voter.voter_weight(&context.solana).await;
+    assert!(vote_weight > 20000);
+
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .update_voter_weight_record_for_action(
+            &registrar,
+            &voter,
+            VoterWeightAction::CreateProposal,
+        )
+        .await?;
+    let proposal_weight = voter.voter_weight(&context.solana).await;
+
+    // the unlocked deposit doesn't count
+    assert_eq!(proposal_weight, vote_weight - 10000);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_weight_scale() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+
+    let voter_authority = &context.users[1].key;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    let voter = context
+        .addin
+        .create_voter(&registrar, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            context.users[1].token_accounts[0],
+            0,
+            10000,
+        )
+        .await?;
+
+    let result = context
+        .addin
+        .configure_weight_scale(&registrar, &realm_authority, 1, 0)
+        .await;
+    assert_addin_err!(result, ErrorCode::InvalidWeightScale);
+
+    context
+        .addin
+        .configure_weight_scale(&registrar, &realm_authority, 3, 2)
+        .await?;
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 15000);
+
+    // the same numerator and denominator switch scaling off
+    context
+        .addin
+        .configure_weight_scale(&registrar, &realm_authority, 5, 5)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 10000);
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+#[tokio::test]
+async fn test_withdrawal_approval() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_withdrawal_approval(&registrar, &realm_authority, true)
+        .await?;
+    // pays the rent of approvals
+    context
+        .addin
+        .fund_rent_sponsor(&registrar, payer, 1_000_000_000)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            1000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::WithdrawalNotApproved);
+
+    context
+        .addin
+        .approve_withdrawal(&registrar, &realm_authority, &voter, 3000)
+        .await?;
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            4000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::WithdrawalNotApproved);
+
+    // the approval is used up by withdrawing
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            2000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            2000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::WithdrawalNotApproved);
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            1000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 7000);
+
+    // only the registrar authority can approve
+    let result = context
+        .addin
+        .approve_withdrawal(&registrar, &voter_authority, &voter, 7000)
+        .await;
+    assert!(result.is_err());
+
+    // without membership mode, everything can be withdrawn again
+    context
+        .addin
+        .configure_withdrawal_approval(&registrar, &realm_authority, false)
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            7000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 0);
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+// The cooldown is passed with the registrar's time offset.
+#![cfg(feature = "localnet-test")]
+
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer, transport::TransportError};
+use voter_weight_addin_deposits::account::LockupKind;
+use voter_weight_addin_deposits::error::ErrorCode;
+
+use program_test::*;
+
+mod program_test;
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[tokio::test]
+async fn test_withdrawal_cooldown() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_withdrawal_cooldown(&registrar, &realm_authority, SECS_PER_DAY as u64)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            0,
+            10000,
+        )
+        .await?;
+    context.solana.advance_clock_by_slots(2).await;
+
+    // nothing was requested yet
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            4000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientPendingWithdrawal);
+
+    // only unlocked tokens can be requested
+    let result = context
+        .addin
+        .request_withdraw(&registrar, &voter, &voter_authority, 0, 10001)
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientVestedTokens);
+    context
+        .addin
+        .request_withdraw(&registrar, &voter, &voter_authority, 0, 4000)
+        .await?;
+
+    // requested tokens don't count towards the weight
+    context
+        .addin
+        .update_voter_weight_record(&registrar, &voter)
+        .await?;
+    assert_eq!(voter.voter_weight(&context.solana).await, 6000);
+
+    // the cooldown hasn't passed
+    context.solana.advance_clock_by_slots(2).await;
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            4000,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::WithdrawalCooldownActive);
+
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, SECS_PER_DAY)
+        .await?;
+
+    // more than requested
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            4001,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientPendingWithdrawal);
+
+    context.solana.advance_clock_by_slots(2).await;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            4000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 6000);
+
+    // the request is used up
+    let result = context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            0,
+            1,
+        )
+        .await;
+    assert_addin_err!(result, ErrorCode::InsufficientPendingWithdrawal);
+
+    Ok(())
+}
+
+// Resetting the lockup of an entry with a pending withdrawal only locks the
+// tokens that weren't requested.
+#[tokio::test]
+async fn test_reset_lockup_keeps_pending_withdrawal() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+
+    let payer = &context.users[0].key;
+    let realm_authority = Keypair::new();
+    let realm = context
+        .governance
+        .create_realm(
+            "testrealm",
+            realm_authority.pubkey(),
+            &context.mints[0],
+            &payer,
+            &context.addin.program_id,
+        )
+        .await;
+    let registrar = context.addin.create_registrar(&realm, payer).await;
+    context
+        .addin
+        .configure_withdrawal_cooldown(&registrar, &realm_authority, SECS_PER_DAY as u64)
+        .await?;
+
+    let voter_authority = &context.users[1].key;
+    let token_account = context.users[1].token_accounts[0];
+    let (voter, token_owner_record) = context
+        .addin
+        .create_voter_with_token_owner_record(&registrar, &realm, &voter_authority, &payer)
+        .await;
+
+    // 10000 tokens locked for a day
+    context
+        .addin
+        .create_deposit_entry(
+            &registrar,
+            &voter,
+            &voter_authority,
+            1,
+            LockupKind::Cliff,
+            1,
+        )
+        .await?;
+    context
+        .addin
+        .deposit(
+            &registrar,
+            &voter,
+            &voter_authority,
+            token_account,
+            1,
+            10000,
+        )
+        .await?;
+
+    // after the lockup ended, request part of them and lock the rest again
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, SECS_PER_DAY)
+        .await?;
+    context
+        .addin
+        .request_withdraw(&registrar, &voter, &voter_authority, 1, 4000)
+        .await?;
+    context
+        .addin
+        .reset_lockup(&registrar, &voter, &voter_authority, 1, 10)
+        .await?;
+
+    // the requested tokens can still be withdrawn after the cooldown
+    context
+        .addin
+        .set_time_offset(&registrar, &realm_authority, 2 * SECS_PER_DAY)
+        .await?;
+    context
+        .addin
+        .withdraw(
+            &registrar,
+            &voter,
+            &token_owner_record,
+            &voter_authority,
+            token_account,
+            1,
+            4000,
+        )
+        .await?;
+    assert_eq!(voter.deposit_amount(&context.solana).await, 6000);
+
+    // the rest is locked
+    context
+        .addin
+        .request_withdraw(&registrar, &voter, &voter_authority, 1, 1)
+        .await
+        .expect_err("the remaining tokens are locked");
+
+    Ok(())
+}
@@ -9,11 +9,7 @@ import {
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
 } from "@solana/web3.js";
-import {
-  Token,
-  TOKEN_PROGRAM_ID,
-  ASSOCIATED_TOKEN_PROGRAM_ID,
-} from "@solana/spl-token";
+import { Token, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { VoterWeightAddinDeposits } from "../target/types/voter_weight_addin_deposits";
 
 const SYSVAR_INSTRUCTIONS_PUBKEY = new PublicKey(
@@ -31,7 +27,6 @@ describe("voting-rights", () => {
   const realm = Keypair.generate().publicKey;
   const votingMintDecimals = 6;
   const tokenProgram = TOKEN_PROGRAM_ID;
-  const associatedTokenProgram = ASSOCIATED_TOKEN_PROGRAM_ID;
   const rent = SYSVAR_RENT_PUBKEY;
   const systemProgram = SystemProgram.programId;
 
@@ -79,13 +74,17 @@ describe("voting-rights", () => {
         ],
         program.programId
       );
-    exchangeVault = await Token.getAssociatedTokenAddress(
-      associatedTokenProgram,
-      tokenProgram,
-      mintA,
-      _registrar,
-      true
-    );
+    const [_exchangeVault, _exchangeVaultBump] =
+      await PublicKey.findProgramAddress(
+        [
+          _registrar.toBuffer(),
+          anchor.utils.bytes.utf8.encode("vault"),
+          mintA.toBuffer(),
+          Buffer.from([0]),
+        ],
+        program.programId
+      );
+    exchangeVault = _exchangeVault;
 
     registrar = _registrar;
     voter = _voter;
@@ -118,7 +117,6 @@ describe("voting-rights", () => {
         payer: program.provider.wallet.publicKey,
         systemProgram,
         tokenProgram,
-        associatedTokenProgram,
         rent,
       },
     });
@@ -133,8 +131,6 @@ describe("voting-rights", () => {
         authority: program.provider.wallet.publicKey,
         payer: program.provider.wallet.publicKey,
         systemProgram,
-        associatedTokenProgram,
-        tokenProgram,
         rent,
         instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
       },
@@ -143,14 +139,14 @@ describe("voting-rights", () => {
 
   it("Deposits tokens", async () => {
     const amount = new BN(10);
-    await program.rpc.deposit(amount, {
+    await program.rpc.deposit({ v1: { amount } }, {
       accounts: {
         registrar,
         voter,
         vault: exchangeVault,
         depositMint: mintA,
         depositToken: godA,
-        authority: program.provider.wallet.publicKey,
+        depositAuthority: program.provider.wallet.publicKey,
         tokenProgram,
       },
     });
@@ -161,14 +157,14 @@ describe("voting-rights", () => {
 
   it("Deposits more tokens", async () => {
     const amount = new BN(11);
-    await program.rpc.deposit(amount, {
+    await program.rpc.deposit({ v1: { amount } }, {
       accounts: {
         registrar,
         voter,
         vault: exchangeVault,
         depositMint: mintA,
         depositToken: godA,
-        authority: program.provider.wallet.publicKey,
+        depositAuthority: program.provider.wallet.publicKey,
         tokenProgram,
       },
     });
@@ -208,12 +204,11 @@ describe("voting-rights", () => {
 
 
   it("Updates a vote weight record", async () => {
-    await program.rpc.updateVoterWeightRecord({
+    await program.rpc.updateVoterWeightRecord({ v1: { weightAction: null } }, {
       accounts: {
         registrar,
         voter,
         voterWeightRecord,
-        authority: program.provider.wallet.publicKey,
         systemProgram,
       },
     });